@@ -0,0 +1,167 @@
+use core::{fmt, time::Duration};
+
+/// The directives of a `Cache-Control` response header.
+///
+/// Example:
+/// ```
+/// # use static_http_file::CacheControl;
+/// const CC: CacheControl = CacheControl::public().with_max_age(3600).with_must_revalidate();
+/// assert_eq!(CC.to_string(), "public, max-age=3600, must-revalidate");
+/// assert!(CC.is_publicly_cacheable());
+/// assert!(!CacheControl::private().is_publicly_cacheable());
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct CacheControl {
+    public: bool,
+    private: bool,
+    no_cache: bool,
+    no_store: bool,
+    max_age: Option<u64>,
+    must_revalidate: bool,
+    immutable: bool,
+}
+
+impl CacheControl {
+    /// Directives for files which are always revalidated before being reused, which is used for files without cache busting.
+    pub const REVALIDATE: CacheControl = CacheControl::public()
+        .with_max_age(0)
+        .with_must_revalidate();
+
+    /// Directives for files which never change at the same URL, which is used for cache busted files.
+    pub const IMMUTABLE: CacheControl = CacheControl::public()
+        .with_max_age(31536000)
+        .with_immutable();
+
+    /// Create a new [`CacheControl`] without any directives.
+    pub const fn new() -> Self {
+        CacheControl {
+            public: false,
+            private: false,
+            no_cache: false,
+            no_store: false,
+            max_age: None,
+            must_revalidate: false,
+            immutable: false,
+        }
+    }
+
+    /// Create a new [`CacheControl`] with the `public` directive, allowing shared caches to store the response.
+    pub const fn public() -> Self {
+        let mut cc = Self::new();
+        cc.public = true;
+        cc
+    }
+
+    /// Create a new [`CacheControl`] with the `private` directive, only allowing the client to store the response.
+    pub const fn private() -> Self {
+        let mut cc = Self::new();
+        cc.private = true;
+        cc
+    }
+
+    /// Create a new [`CacheControl`] with the `no-store` directive, not allowing any cache to store the response.
+    pub const fn no_store() -> Self {
+        let mut cc = Self::new();
+        cc.no_store = true;
+        cc
+    }
+
+    /// Set the `no-cache` directive, requiring caches to revalidate the response before each use.
+    pub const fn with_no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+
+    /// Set the `max-age` directive in seconds.
+    pub const fn with_max_age(mut self, seconds: u64) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    /// Set the `max-age` directive from a duration, which is truncated to whole seconds.
+    pub const fn max_age(self, duration: Duration) -> Self {
+        self.with_max_age(duration.as_secs())
+    }
+
+    /// Set the `must-revalidate` directive.
+    pub const fn with_must_revalidate(mut self) -> Self {
+        self.must_revalidate = true;
+        self
+    }
+
+    /// Set the `immutable` directive.
+    pub const fn with_immutable(mut self) -> Self {
+        self.immutable = true;
+        self
+    }
+
+    /// Remove the `immutable` directive.
+    pub const fn without_immutable(mut self) -> Self {
+        self.immutable = false;
+        self
+    }
+
+    /// Checks if the `public` directive is set.
+    pub const fn is_public(&self) -> bool {
+        self.public
+    }
+
+    /// Checks if the `private` directive is set.
+    pub const fn is_private(&self) -> bool {
+        self.private
+    }
+
+    /// Checks if the `no-cache` directive is set.
+    pub const fn is_no_cache(&self) -> bool {
+        self.no_cache
+    }
+
+    /// Checks if the `no-store` directive is set.
+    pub const fn is_no_store(&self) -> bool {
+        self.no_store
+    }
+
+    /// The value of the `max-age` directive in seconds.
+    pub const fn max_age_seconds(&self) -> Option<u64> {
+        self.max_age
+    }
+
+    /// Checks if the `must-revalidate` directive is set.
+    pub const fn is_must_revalidate(&self) -> bool {
+        self.must_revalidate
+    }
+
+    /// Checks if the `immutable` directive is set.
+    pub const fn is_immutable(&self) -> bool {
+        self.immutable
+    }
+
+    /// Checks if a shared cache may store the response, which is not the case for `private` or `no-store` responses.
+    pub const fn is_publicly_cacheable(&self) -> bool {
+        !self.private && !self.no_store
+    }
+}
+
+impl fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        let mut directive = |f: &mut fmt::Formatter<'_>, set: bool, name: &str| -> fmt::Result {
+            if set {
+                f.write_str(sep)?;
+                f.write_str(name)?;
+                sep = ", ";
+            }
+            Ok(())
+        };
+        directive(f, self.public, "public")?;
+        directive(f, self.private, "private")?;
+        directive(f, self.no_store, "no-store")?;
+        directive(f, self.no_cache, "no-cache")?;
+        if let Some(max_age) = self.max_age {
+            directive(f, true, "max-age=")?;
+            write!(f, "{}", max_age)?;
+        }
+        directive(f, self.must_revalidate, "must-revalidate")?;
+        directive(f, self.immutable, "immutable")
+    }
+}
@@ -1,6 +1,6 @@
-use core::num::NonZeroU8;
+use core::num::{NonZeroU8, NonZeroUsize};
 
-use alloc::format;
+use alloc::{format, string::String, vec::Vec};
 use bytedata::{ByteData, StringData};
 
 use crate::{CacheBusting, HttpFile, HttpFileResponse};
@@ -47,6 +47,12 @@ impl QueryCacheBustedHttpFile<super::ConstHttpFile> {
     pub const fn const_clone_data(&'static self) -> ByteData<'static> {
         ByteData::from_static(self.inner.data)
     }
+
+    /// The length of the data, which can be used for a `Content-Length` header in constant contexts.
+    #[inline]
+    pub const fn const_data_len(&self) -> usize {
+        self.inner.data.len()
+    }
 }
 
 impl<'l, T: HttpFileResponse<'l>> QueryCacheBustedHttpFile<T> {
@@ -75,7 +81,7 @@ impl<'l, T: HttpFileResponse<'l>> QueryCacheBustedHttpFile<T> {
             "{}?{}={}",
             file_url.as_ref(),
             query_var.as_str(),
-            inner.etag()
+            inner.etag_str()
         );
         QueryCacheBustedHttpFile {
             url: url.into(),
@@ -106,11 +112,31 @@ impl<'l, T: HttpFileResponse<'l>> HttpFile<'l> for QueryCacheBustedHttpFile<T> {
         self.inner.etag_str()
     }
 
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        self.inner.content_encoding()
+    }
+
+    #[inline]
+    fn available_encodings(&self) -> Vec<&str> {
+        self.inner.available_encodings()
+    }
+
     #[inline]
     fn cache_busting(&self) -> &CacheBusting {
         &self.cbust
     }
 
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.inner.last_modified()
+    }
+
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        self.inner.accepts_ranges()
+    }
+
     #[inline]
     fn data(&self) -> &[u8] {
         self.inner.data()
@@ -127,60 +153,235 @@ impl<'l, T: HttpFileResponse<'l>> HttpFile<'l> for QueryCacheBustedHttpFile<T> {
     }
 }
 
+/// Implements the [`HttpFileResponse`] methods of a wrapper by delegating them to its `inner` field.
+/// The response logic itself is not delegated, as it would bypass the behavior of the wrapper.
+///
+/// A wrapper which adds its own security headers can delegate everything else with `@headers` and `@cachebust`.
+macro_rules! wrap_resp {
+    ($l:lifetime) => {
+        $crate::cachebusted_http_file::wrap_resp!(@headers);
+        $crate::cachebusted_http_file::wrap_resp!(@security);
+        $crate::cachebusted_http_file::wrap_resp!(@cachebust $l);
+    };
+    (@headers) => {
+        #[inline]
+        fn etag_header_value(&self) -> http::header::HeaderValue {
+            self.inner.etag_header_value()
+        }
+
+        #[inline]
+        fn vary_headers(&self) -> &[http::header::HeaderName] {
+            self.inner.vary_headers()
+        }
+
+        #[inline]
+        fn network_error_logging(&self) -> Option<&crate::NetworkErrorLogging> {
+            self.inner.network_error_logging()
+        }
+
+        #[inline]
+        fn retry_after(&self) -> Option<u64> {
+            self.inner.retry_after()
+        }
+    };
+    (@security) => {
+        #[inline]
+        fn sniff_protection(&self) -> bool {
+            self.inner.sniff_protection()
+        }
+
+        #[inline]
+        fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+            self.inner.security_policy()
+        }
+
+        #[inline]
+        fn security_headers(&self, response: http::response::Builder) -> http::response::Builder {
+            self.inner.security_headers(response)
+        }
+    };
+    (@cachebust $l:lifetime) => {
+        #[inline]
+        fn cachebust_uri<R: From<ByteData<$l>>>(
+            &self,
+            old_uri: &http::Uri,
+            query_key: &str,
+        ) -> Option<Result<http::Response<R>, http::Error>> {
+            self.inner.cachebust_uri(old_uri, query_key)
+        }
+
+        #[inline]
+        fn cachebust_suffix<R: From<ByteData<$l>>>(
+            &self,
+            old_uri: &http::Uri,
+            left_sep: Option<NonZeroU8>,
+            len: Option<NonZeroUsize>,
+        ) -> Option<Result<http::Response<R>, http::Error>> {
+            self.inner.cachebust_suffix(old_uri, left_sep, len)
+        }
+    };
+}
+pub(crate) use wrap_resp;
+
 impl<'l, T: HttpFileResponse<'l>> HttpFileResponse<'l> for QueryCacheBustedHttpFile<T> {
+    wrap_resp!('l);
+}
+
+pub struct SuffixCacheBustedHttpFile<T> {
+    url: StringData<'static>,
+    cbust: CacheBusting,
+    inner: T,
+}
+
+impl SuffixCacheBustedHttpFile<super::ConstHttpFile> {
+    /// Const specialization of [`HttpFile::content_type`].
     #[inline]
-    fn respond_guard<R: From<ByteData<'l>>>(
-        &self,
-        request: &http::Request<()>,
-    ) -> Result<http::response::Builder, Result<http::Response<R>, http::Error>> {
-        self.inner.respond_guard(request)
+    pub const fn const_content_type(&self) -> &str {
+        self.inner.mime
     }
 
+    /// Const specialization of [`HttpFile::etag`].
     #[inline]
-    fn respond<R: From<ByteData<'l>>>(
-        self,
-        request: &http::Request<()>,
-    ) -> Result<http::Response<R>, http::Error> {
-        self.inner.respond(request)
+    pub const fn const_etag(&self) -> &str {
+        self.inner.etag
     }
 
+    /// Const specialization of [`HttpFile::etag_str`].
     #[inline]
-    fn respond_borrowed<R: From<ByteData<'l>>>(
-        &self,
-        request: &http::Request<()>,
-    ) -> Result<http::Response<R>, http::Error> {
-        self.inner.respond_borrowed(request)
+    pub const fn const_etag_str(&self) -> &str {
+        self.inner.const_etag_str()
     }
 
+    /// Const specialization of [`HttpFile::cache_busting`].
     #[inline]
-    fn response_headers(&self, response: http::response::Builder) -> http::response::Builder {
-        self.inner.response_headers(response)
+    pub const fn const_cache_busting(&self) -> &CacheBusting {
+        &self.cbust
     }
 
+    /// Const specialization of [`HttpFile::data`].
     #[inline]
-    fn into_response<R: From<ByteData<'l>>>(self) -> Result<http::Response<R>, http::Error> {
-        self.inner.into_response()
+    pub const fn const_data(&self) -> &'_ [u8] {
+        self.inner.data
     }
 
+    /// Const specialization of [`HttpFile::clone_data`].
     #[inline]
-    fn cachebust_uri<R: From<ByteData<'l>>>(
-        &self,
-        old_uri: &http::Uri,
-        query_key: &str,
-    ) -> Option<Result<http::Response<R>, http::Error>> {
-        self.inner.cachebust_uri(old_uri, query_key)
+    pub const fn const_clone_data(&'static self) -> ByteData<'static> {
+        ByteData::from_static(self.inner.data)
     }
 
+    /// The length of the data, which can be used for a `Content-Length` header in constant contexts.
     #[inline]
-    fn cachebust_suffix<R: From<ByteData<'l>>>(
-        &self,
-        old_uri: &http::Uri,
+    pub const fn const_data_len(&self) -> usize {
+        self.inner.data.len()
+    }
+}
+
+impl<'l, T: HttpFileResponse<'l>> SuffixCacheBustedHttpFile<T> {
+    /// Create a new [`SuffixCacheBustedHttpFile`] from a precomputed URL.
+    /// The URL must contain the `left_sep` separator followed by the exact unquoted etag before the file extension.
+    pub const fn new_const(
+        url: StringData<'static>,
         left_sep: Option<NonZeroU8>,
-    ) -> Option<Result<http::Response<R>, http::Error>> {
-        self.inner.cachebust_suffix(old_uri, left_sep)
+        inner: T,
+    ) -> Self {
+        SuffixCacheBustedHttpFile {
+            url,
+            cbust: CacheBusting::Suffix(left_sep, None),
+            inner,
+        }
+    }
+
+    /// Create a new [`SuffixCacheBustedHttpFile`] from a base URL, where the separator and etag are inserted before the file extension.
+    pub fn new(file_url: impl AsRef<str>, left_sep: Option<NonZeroU8>, inner: T) -> Self {
+        let file_url = file_url.as_ref();
+        let (stem, ext) = match crate::file_ext(file_url) {
+            Some(ext) => (&file_url[..file_url.len() - ext.len() - 1], ext),
+            None => (file_url, ""),
+        };
+        let mut url = String::with_capacity(file_url.len() + 1 + inner.etag_str().len());
+        url.push_str(stem);
+        if let Some(left_sep) = left_sep {
+            url.push(left_sep.get() as char);
+        }
+        url.push_str(inner.etag_str());
+        if !ext.is_empty() {
+            url.push('.');
+            url.push_str(ext);
+        }
+        SuffixCacheBustedHttpFile {
+            url: url.into(),
+            cbust: CacheBusting::Suffix(left_sep, None),
+            inner,
+        }
+    }
+
+    /// The cachebusted URL.
+    pub const fn url(&self) -> &StringData<'static> {
+        &self.url
     }
 }
 
+impl<'l, T: HttpFileResponse<'l>> HttpFile<'l> for SuffixCacheBustedHttpFile<T> {
+    #[inline]
+    fn content_type(&self) -> &str {
+        self.inner.content_type()
+    }
+
+    #[inline]
+    fn etag(&self) -> &str {
+        self.inner.etag()
+    }
+
+    #[inline]
+    fn etag_str(&self) -> &str {
+        self.inner.etag_str()
+    }
+
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        self.inner.content_encoding()
+    }
+
+    #[inline]
+    fn available_encodings(&self) -> Vec<&str> {
+        self.inner.available_encodings()
+    }
+
+    #[inline]
+    fn cache_busting(&self) -> &CacheBusting {
+        &self.cbust
+    }
+
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.inner.last_modified()
+    }
+
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        self.inner.accepts_ranges()
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.inner.data()
+    }
+
+    #[inline]
+    fn into_data(self) -> ByteData<'l> {
+        self.inner.into_data()
+    }
+
+    #[inline]
+    fn clone_data(&self) -> ByteData<'l> {
+        self.inner.clone_data()
+    }
+}
+
+impl<'l, T: HttpFileResponse<'l>> HttpFileResponse<'l> for SuffixCacheBustedHttpFile<T> {
+    wrap_resp!('l);
+}
 
 /// Create a [`ConstHttpFile`] from a file path. An explicit MIME type can also be provided.
 ///
@@ -220,3 +421,49 @@ macro_rules! static_http_file_querycache {
         $crate::QueryCacheBustedHttpFile::new_const(bytedata::StringData::from_static(__FILE_URL), __FILE_QVAR, __FILE_CONST)
     }};
 }
+
+/// Create a [`SuffixCacheBustedHttpFile`] of a [`ConstHttpFile`](crate::ConstHttpFile) from a file path, where the URL contains the etag before the file extension.
+/// The separator between the file name and the etag must be a single ASCII character and defaults to `~`.
+/// An explicit MIME type can also be provided.
+///
+/// # Examples
+///
+/// ```
+/// # use static_http_file::{ConstHttpFile, SuffixCacheBustedHttpFile, static_http_file_suffixcache};
+/// /// Explicit MIME type provided.
+/// static FILE_0: SuffixCacheBustedHttpFile<ConstHttpFile> = static_http_file_suffixcache!("-", "../Cargo.toml", "application/toml");
+/// assert_eq!(FILE_0.url().as_str(), format!("../Cargo-{}.toml", FILE_0.const_etag_str()));
+/// static FILE_1: SuffixCacheBustedHttpFile<ConstHttpFile> = static_http_file_suffixcache!("../Cargo.toml");
+/// assert_eq!(FILE_1.url().as_str(), format!("../Cargo~{}.toml", FILE_1.const_etag_str()));
+/// ```
+#[macro_export]
+macro_rules! static_http_file_suffixcache {
+    (@url $sep:literal, $file:literal, $inner:ident) => {{
+        const _: () = assert!($sep.len() == 1 && $sep.as_bytes()[0].is_ascii(), "separator must be a single ASCII character");
+        const __FILE_ETAG: &str = $inner.const_etag_str();
+        const __FILE_EXT: &str = match $crate::file_ext($file) {
+            Some(ext) => ext,
+            None => "",
+        };
+        const __FILE_STEM_LEN: usize = if __FILE_EXT.is_empty() { $file.len() } else { $file.len() - __FILE_EXT.len() - 1 };
+        const __FILE_STEM: &str = match ::bytedata::const_slice_str($file, 0..__FILE_STEM_LEN) {
+            Ok(stem) => stem,
+            Err(_) => panic!("invalid file path"),
+        };
+        const __FILE_DOT: &str = if __FILE_EXT.is_empty() { "" } else { "." };
+        static __FILE_URL: &str = ::bytedata::concat_str_static!(__FILE_STEM, $sep, __FILE_ETAG, __FILE_DOT, __FILE_EXT);
+        $crate::SuffixCacheBustedHttpFile::new_const(bytedata::StringData::from_static(__FILE_URL), ::core::num::NonZeroU8::new($sep.as_bytes()[0]), $inner)
+    }};
+    ($sep:literal, $file:literal, $($r:tt)*) => {{
+        const __FILE_CONST: $crate::ConstHttpFile = $crate::const_http_file!($file, $($r)*);
+        $crate::static_http_file_suffixcache!(@url $sep, $file, __FILE_CONST)
+    }};
+    ($sep:literal, $file:literal) => {{
+        const __FILE_CONST: $crate::ConstHttpFile = $crate::const_http_file!($file);
+        $crate::static_http_file_suffixcache!(@url $sep, $file, __FILE_CONST)
+    }};
+    ($file:literal) => {{
+        const __FILE_CONST: $crate::ConstHttpFile = $crate::const_http_file!($file);
+        $crate::static_http_file_suffixcache!(@url "~", $file, __FILE_CONST)
+    }};
+}
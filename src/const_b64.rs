@@ -1,52 +1,200 @@
 const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
 
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The alphabet used by [`b64_const`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Base64Alphabet {
+    /// The URL and filename safe alphabet, using `-` and `_`.
+    #[default]
+    Url,
+    /// The standard alphabet, using `+` and `/`.
+    Standard,
+}
+
+impl Base64Alphabet {
+    const fn chars(self) -> &'static [u8; 64] {
+        match self {
+            Base64Alphabet::Url => BASE64URL,
+            Base64Alphabet::Standard => BASE64,
+        }
+    }
+}
+
 /// Encode a byte slice as base64url.
 /// The output buffer size `S` must be at least 4/3 the size of the input otherwise this function will panic.
 /// The returned offset is the number of bytes written to the output buffer.
 ///
 /// This can be used in constant contexts when the input is a constant byte slice of a known length.
 pub const fn b64url_const<const S: usize>(
+    data: &[u8],
+    trg: [u8; S],
+    offset: usize,
+) -> ([u8; S], usize) {
+    b64_const(data, trg, offset, Base64Alphabet::Url, false)
+}
+
+/// Encode a byte slice as base64 using the given alphabet.
+/// If `pad` is set the output is padded with `=` to a multiple of 4 bytes.
+/// The output buffer size `S` must fit [`b64_encoded_len`] bytes after the offset otherwise this function will panic.
+/// The returned offset is the end of the bytes written to the output buffer.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{b64_const, Base64Alphabet};
+/// let (buf, end) = b64_const(b"\xfb\xff", [0u8; 8], 0, Base64Alphabet::Standard, true);
+/// assert_eq!(&buf[..end], b"+/8=");
+/// let (buf, end) = b64_const(b"\xfb\xff", [0u8; 8], 0, Base64Alphabet::Url, false);
+/// assert_eq!(&buf[..end], b"-_8");
+/// ```
+pub const fn b64_const<const S: usize>(
     data: &[u8],
     mut trg: [u8; S],
     offset: usize,
+    alphabet: Base64Alphabet,
+    pad: bool,
 ) -> ([u8; S], usize) {
     if offset >= S {
         panic!("Offset too large");
     }
     let inp_len = data.len();
     let out_len = S - offset;
-    if out_len < (4 * inp_len) / 3 {
+    if out_len < b64_encoded_len(inp_len, pad) {
         panic!("Output buffer too small");
     }
+    let chars = alphabet.chars();
     let mut i = 0;
     let mut o = offset;
     while inp_len - i >= 3 {
         let b0 = data[i];
         let b1 = data[i + 1];
         let b2 = data[i + 2];
-        trg[o] = BASE64URL[(b0 >> 2) as usize];
-        trg[o + 1] = BASE64URL[(((b0 & 0b0011) << 4) | (b1 >> 4)) as usize];
-        trg[o + 2] = BASE64URL[(((b1 & 0b1111) << 2) | (b2 >> 6)) as usize];
-        trg[o + 3] = BASE64URL[(b2 & 0b111111) as usize];
+        trg[o] = chars[(b0 >> 2) as usize];
+        trg[o + 1] = chars[(((b0 & 0b0011) << 4) | (b1 >> 4)) as usize];
+        trg[o + 2] = chars[(((b1 & 0b1111) << 2) | (b2 >> 6)) as usize];
+        trg[o + 3] = chars[(b2 & 0b111111) as usize];
         i += 3;
         o += 4;
     }
     let o = match inp_len - i {
         1 => {
             let b0 = data[i];
-            trg[o] = BASE64URL[(b0 >> 2) as usize];
-            trg[o + 1] = BASE64URL[((b0 & 0b0011) << 4) as usize];
-            o + 2
+            trg[o] = chars[(b0 >> 2) as usize];
+            trg[o + 1] = chars[((b0 & 0b0011) << 4) as usize];
+            if pad {
+                trg[o + 2] = b'=';
+                trg[o + 3] = b'=';
+                o + 4
+            } else {
+                o + 2
+            }
         }
         2 => {
             let b0 = data[i];
             let b1 = data[i + 1];
-            trg[o] = BASE64URL[(b0 >> 2) as usize];
-            trg[o + 1] = BASE64URL[(((b0 & 0b0011) << 4) | (b1 >> 4)) as usize];
-            trg[o + 2] = BASE64URL[((b1 & 0b1111) << 2) as usize];
-            o + 3
+            trg[o] = chars[(b0 >> 2) as usize];
+            trg[o + 1] = chars[(((b0 & 0b0011) << 4) | (b1 >> 4)) as usize];
+            trg[o + 2] = chars[((b1 & 0b1111) << 2) as usize];
+            if pad {
+                trg[o + 3] = b'=';
+                o + 4
+            } else {
+                o + 3
+            }
         }
         _ => o,
     };
     (trg, o)
 }
+
+/// The number of bytes needed to encode `len` bytes as base64, with or without padding.
+///
+/// Example:
+/// ```
+/// # use static_http_file::b64_encoded_len;
+/// assert_eq!(b64_encoded_len(8, false), 11);
+/// assert_eq!(b64_encoded_len(8, true), 12);
+/// ```
+pub const fn b64_encoded_len(len: usize, pad: bool) -> usize {
+    if pad {
+        (len + 2) / 3 * 4
+    } else {
+        b64url_encoded_len(len)
+    }
+}
+
+/// The number of bytes needed to encode `len` bytes as unpadded base64url.
+///
+/// Example:
+/// ```
+/// # use static_http_file::b64url_encoded_len;
+/// assert_eq!(b64url_encoded_len(8), 11);
+/// assert_eq!(b64url_encoded_len(9), 12);
+/// ```
+pub const fn b64url_encoded_len(len: usize) -> usize {
+    len / 3 * 4
+        + match len % 3 {
+            1 => 2,
+            2 => 3,
+            _ => 0,
+        }
+}
+
+/// The error returned by [`b64url_try`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum B64Error {
+    /// The offset is past the end of the output buffer.
+    OffsetOutOfBounds,
+    /// The output buffer is too small, where `required` is the number of bytes needed after the offset.
+    BufferTooSmall { required: usize },
+}
+
+impl core::fmt::Display for B64Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            B64Error::OffsetOutOfBounds => f.write_str("offset is out of bounds"),
+            B64Error::BufferTooSmall { required } => {
+                write!(f, "output buffer too small, {} bytes required", required)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for B64Error {}
+
+/// Encode a byte slice as base64url into `trg` starting at `offset`, returning the number of bytes written.
+/// Unlike [`b64url_const`] this fails instead of panicking if the output does not fit,
+/// and the required size can be computed using [`b64url_encoded_len`].
+///
+/// Example:
+/// ```
+/// # use static_http_file::{b64url_try, B64Error};
+/// let mut buf = [b'"'; 8];
+/// assert_eq!(b64url_try(b"foo", &mut buf, 1), Ok(4));
+/// assert_eq!(&buf[..6], b"\"Zm9v\"");
+/// assert_eq!(b64url_try(b"foobar", &mut buf, 1), Err(B64Error::BufferTooSmall { required: 8 }));
+/// ```
+pub fn b64url_try(data: &[u8], trg: &mut [u8], offset: usize) -> Result<usize, B64Error> {
+    if offset > trg.len() {
+        return Err(B64Error::OffsetOutOfBounds);
+    }
+    let required = b64url_encoded_len(data.len());
+    let Some(trg) = trg[offset..].get_mut(..required) else {
+        return Err(B64Error::BufferTooSmall { required });
+    };
+    for (inp, out) in data.chunks(3).zip(trg.chunks_mut(4)) {
+        let b0 = inp[0];
+        let b1 = inp.get(1).copied().unwrap_or(0);
+        let b2 = inp.get(2).copied().unwrap_or(0);
+        let chars = [
+            BASE64URL[(b0 >> 2) as usize],
+            BASE64URL[(((b0 & 0b0011) << 4) | (b1 >> 4)) as usize],
+            BASE64URL[(((b1 & 0b1111) << 2) | (b2 >> 6)) as usize],
+            BASE64URL[(b2 & 0b111111) as usize],
+        ];
+        out.copy_from_slice(&chars[..out.len()]);
+    }
+    Ok(required)
+}
@@ -0,0 +1,141 @@
+use core::cmp::Ordering;
+
+use bytedata::ByteData;
+
+use crate::{ConstHttpFile, HttpFileResponse};
+
+/// A directory of files embedded at compile time, for targets without a file system.
+///
+/// The files are stored with their request paths, sorted by path, so that they can be found with a binary search.
+/// The easiest way to create a `ConstDirectory` is with the [`const_directory!`] macro.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_http_file, ConstDirectory, ConstHttpFile};
+/// const DIR: ConstDirectory = ConstDirectory::new(&[
+///     ("/app.js", const_http_file!(b"console.log(1);" as &[u8], "text/javascript")),
+///     ("/index.html", const_http_file!(b"<!DOCTYPE html>" as &[u8], "text/html")),
+/// ]);
+/// const INDEX: Option<&ConstHttpFile> = DIR.get("/index.html");
+/// assert_eq!(INDEX.map(|file| file.mime), Some("text/html"));
+/// assert!(DIR.get("/missing.js").is_none());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConstDirectory {
+    files: &'static [(&'static str, ConstHttpFile)],
+}
+
+impl ConstDirectory {
+    /// Create a new [`ConstDirectory`] from files and their request paths.
+    ///
+    /// Panics if the paths are not sorted or not unique, which fails the build when used in a constant.
+    pub const fn new(files: &'static [(&'static str, ConstHttpFile)]) -> Self {
+        let mut i = 1;
+        while i < files.len() {
+            if !matches!(const_str_cmp(files[i - 1].0, files[i].0), Ordering::Less) {
+                panic!("the paths of a ConstDirectory must be sorted and unique");
+            }
+            i += 1;
+        }
+        ConstDirectory { files }
+    }
+
+    /// Returns the files and their request paths, sorted by path.
+    pub const fn files(&self) -> &'static [(&'static str, ConstHttpFile)] {
+        self.files
+    }
+
+    /// Returns the number of files.
+    pub const fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Checks if the directory has no files.
+    pub const fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Get the file for a request path, which must match the path of the file exactly.
+    pub const fn get(&self, path: &str) -> Option<&'static ConstHttpFile> {
+        let files = self.files;
+        let mut low = 0;
+        let mut high = files.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match const_str_cmp(files[mid].0, path) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(&files[mid].1),
+            }
+        }
+        None
+    }
+
+    /// Responds to a request for a file in the directory.
+    ///
+    /// Returns `None` if the request path does not match a file, which should usually be answered with `404 Not Found`.
+    pub fn respond<T: From<ByteData<'static>>>(
+        &self,
+        request: &http::Request<()>,
+    ) -> Option<Result<http::Response<T>, http::Error>> {
+        let file = self.get(request.uri().path())?;
+        Some(file.respond_borrowed(request))
+    }
+}
+
+/// Compares two strings by their bytes in constant contexts.
+const fn const_str_cmp(lhs: &str, rhs: &str) -> Ordering {
+    let lhs = lhs.as_bytes();
+    let rhs = rhs.as_bytes();
+    let mut i = 0;
+    while i < lhs.len() && i < rhs.len() {
+        if lhs[i] != rhs[i] {
+            return if lhs[i] < rhs[i] {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+        i += 1;
+    }
+    if lhs.len() < rhs.len() {
+        Ordering::Less
+    } else if lhs.len() > rhs.len() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Create a [`ConstDirectory`] from request paths and the paths of the files to include, which must be sorted by request path.
+///
+/// The files are included like [`const_http_file!`], so the file paths are relative to the current source file,
+/// and the MIME types are detected from the file extensions or file contents.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_directory, ConstDirectory};
+/// const DIR: ConstDirectory = const_directory! {
+///     "/.gitignore" => "../.gitignore",
+///     "/Cargo.toml" => "../Cargo.toml",
+/// };
+/// assert_eq!(DIR.len(), 2);
+/// assert!(DIR.get("/Cargo.toml").is_some());
+/// ```
+///
+/// ```compile_fail
+/// # use static_http_file::{const_directory, ConstDirectory};
+/// /// Unsorted paths fail at compile time.
+/// const DIR: ConstDirectory = const_directory! {
+///     "/Cargo.toml" => "../Cargo.toml",
+///     "/.gitignore" => "../.gitignore",
+/// };
+/// ```
+#[macro_export]
+macro_rules! const_directory {
+    ($($path:literal => $file:literal),* $(,)?) => {{
+        const __DIR_FILES: &[(&str, $crate::ConstHttpFile)] = &[$(($path, $crate::const_http_file!($file))),*];
+        const __DIR: $crate::ConstDirectory = $crate::ConstDirectory::new(__DIR_FILES);
+        __DIR
+    }};
+}
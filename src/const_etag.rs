@@ -15,6 +15,41 @@ macro_rules! const_etag {
     }};
 }
 
+/// Compute a weak etag from a byte slice, which is the etag of [`const_etag!`] prefixed by `W/`.
+///
+/// Example:
+/// ```
+/// # use static_http_file::const_weak_etag;
+/// const ETAG: &str = const_weak_etag!(b"foo");
+/// assert_eq!(ETAG, "W/\"q25fZAd-fY\"");
+/// ```
+#[macro_export]
+macro_rules! const_weak_etag {
+    ($data:expr) => {{
+        const __FILE_ETAG: &[u8; 14] = &$crate::compute_weak_etag($data);
+        const __FILE_ETAG_STR: &str = unsafe { core::str::from_utf8_unchecked(__FILE_ETAG) };
+        __FILE_ETAG_STR
+    }};
+}
+
+/// Compute an etag from a byte slice and a seed, such that deployments using different seeds produce different etags for the same data.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_etag, const_etag_seeded};
+/// const ETAG: &str = const_etag_seeded!(b"foo", 0x5eed);
+/// assert_ne!(ETAG, const_etag!(b"foo"));
+/// assert_eq!(const_etag_seeded!(b"foo", 0), const_etag!(b"foo"));
+/// ```
+#[macro_export]
+macro_rules! const_etag_seeded {
+    ($data:expr, $seed:expr) => {{
+        const __FILE_ETAG: &[u8; 12] = &$crate::compute_etag_seeded_const($data, $seed);
+        const __FILE_ETAG_STR: &str = unsafe { core::str::from_utf8_unchecked(__FILE_ETAG) };
+        __FILE_ETAG_STR
+    }};
+}
+
 /// Compute an etag from a byte slice. The returned etag is a base64url-encoded 64-bit xxhash3 hash of the data wrapped in quotes.
 ///
 /// Example:
@@ -24,7 +59,47 @@ macro_rules! const_etag {
 /// assert_eq!(&ETAG, b"\"q25fZAd-fY\"");
 /// ```
 pub const fn compute_etag(data: &[u8]) -> [u8; 12] {
-    let h = xxhash_rust::const_xxh3::xxh3_64(data).to_be_bytes();
+    etag_from_hash(xxhash_rust::const_xxh3::xxh3_64(data))
+}
+
+/// Compute a weak etag from a byte slice, for data which is semantically equal even if its bytes differ,
+/// such as generated files with embedded timestamps. This is the etag of [`compute_etag`] prefixed by `W/`.
+///
+/// Example:
+/// ```
+/// # use static_http_file::compute_weak_etag;
+/// const ETAG: [u8; 14] = compute_weak_etag(b"foo");
+/// assert_eq!(&ETAG, b"W/\"q25fZAd-fY\"");
+/// ```
+pub const fn compute_weak_etag(data: &[u8]) -> [u8; 14] {
+    let strong = compute_etag(data);
+    let mut etag = [0; 14];
+    etag[0] = b'W';
+    etag[1] = b'/';
+    let mut i = 0;
+    while i < strong.len() {
+        etag[i + 2] = strong[i];
+        i += 1;
+    }
+    etag
+}
+
+/// Compute an etag from a byte slice using a seeded xxhash3 hash. A seed of `0` gives the same etag as [`compute_etag`].
+///
+/// Example:
+/// ```
+/// # use static_http_file::{compute_etag, compute_etag_seeded_const};
+/// const ETAG: [u8; 12] = compute_etag_seeded_const(b"foo", 0x5eed);
+/// assert_ne!(ETAG, compute_etag(b"foo"));
+/// ```
+pub const fn compute_etag_seeded_const(data: &[u8], seed: u64) -> [u8; 12] {
+    etag_from_hash(xxhash_rust::const_xxh3::xxh3_64_with_seed(data, seed))
+}
+
+/// Formats a 64-bit hash as a quoted etag.
+/// The hash is encoded as big-endian bytes so that the etag is identical on all platforms.
+pub(crate) const fn etag_from_hash(hash: u64) -> [u8; 12] {
+    let h = hash.to_be_bytes();
     let (mut etag, _n) = crate::b64url_const(&h, [0; 12], 1);
     #[cfg(debug_assertions)]
     if _n != 12 {
@@ -34,3 +109,144 @@ pub const fn compute_etag(data: &[u8]) -> [u8; 12] {
     etag[11] = b'"';
     etag
 }
+
+/// Checks if an etag is valid for use in an `ETag` header.
+/// The empty string, meaning no etag, is accepted along with quoted strings of any length, which may be prefixed by `W/` to mark a weak etag.
+///
+/// Example:
+/// ```
+/// # use static_http_file::validate_etag;
+/// const _: () = assert!(validate_etag("\"q25fZAd-fY\""));
+/// const _: () = assert!(validate_etag("W/\"q25fZAd-fY\""));
+/// const _: () = assert!(!validate_etag("q25fZAd-fY"));
+/// ```
+pub const fn validate_etag(etag: &str) -> bool {
+    let etag = etag.as_bytes();
+    if etag.is_empty() {
+        return true;
+    }
+    let start = if etag.len() >= 2 && etag[0] == b'W' && etag[1] == b'/' {
+        2
+    } else {
+        0
+    };
+    if etag.len() < start + 2 || etag[start] != b'"' || etag[etag.len() - 1] != b'"' {
+        return false;
+    }
+    let mut i = start + 1;
+    while i < etag.len() - 1 {
+        let b = etag[i];
+        // etagc = %x21 / %x23-7E / obs-text
+        if b == b'"' || b < 0x21 || b == 0x7F {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Checks if an etag is matched by the value of an `If-Match` header.
+/// The header value is a comma separated list of etags, where the wildcard `*` matches any etag.
+///
+/// This uses the strong comparison, so weak etags prefixed by `W/` only match the wildcard.
+/// This performs the same matching as [`HttpFileResponse::respond_guard`](crate::HttpFileResponse::respond_guard) but can be used in constant contexts.
+///
+/// Example:
+/// ```
+/// # use static_http_file::const_etag_matches;
+/// const _: () = assert!(const_etag_matches("\"q25fZAd-fY\"", "\"other\", \"q25fZAd-fY\""));
+/// const _: () = assert!(!const_etag_matches("\"q25fZAd-fY\"", "\"other\""));
+/// const _: () = assert!(!const_etag_matches("\"q25fZAd-fY\"", "W/\"q25fZAd-fY\""));
+/// const _: () = assert!(!const_etag_matches("", ""));
+/// ```
+pub const fn const_etag_matches(etag: &str, header_value: &str) -> bool {
+    etag_list_matches(etag.as_bytes(), header_value.as_bytes(), false)
+}
+
+/// Checks if an etag is matched by the value of an `If-None-Match` header.
+/// The header value is a comma separated list of etags, where the wildcard `*` matches any etag.
+///
+/// This uses the weak comparison, where the `W/` prefix of weak etags is ignored.
+///
+/// Example:
+/// ```
+/// # use static_http_file::const_etag_matches_weak;
+/// const _: () = assert!(const_etag_matches_weak("\"q25fZAd-fY\"", "W/\"q25fZAd-fY\""));
+/// const _: () = assert!(const_etag_matches_weak("W/\"q25fZAd-fY\"", "\"q25fZAd-fY\""));
+/// const _: () = assert!(!const_etag_matches_weak("W/\"q25fZAd-fY\"", "\"other\""));
+/// ```
+pub const fn const_etag_matches_weak(etag: &str, header_value: &str) -> bool {
+    etag_list_matches(etag.as_bytes(), header_value.as_bytes(), true)
+}
+
+/// Returns the offset of the opaque tag, skipping the `W/` prefix of weak etags, and if the etag is weak.
+const fn opaque_tag_start(value: &[u8], start: usize, end: usize) -> (usize, bool) {
+    if end - start >= 2 && value[start] == b'W' && value[start + 1] == b'/' {
+        (start + 2, true)
+    } else {
+        (start, false)
+    }
+}
+
+const fn etag_list_matches(etag: &[u8], value: &[u8], weak: bool) -> bool {
+    let (etag_start, etag_weak) = opaque_tag_start(etag, 0, etag.len());
+    let etag_len = etag.len() - etag_start;
+    let mut start = 0;
+    while start <= value.len() {
+        let mut end = start;
+        while end < value.len() && value[end] != b',' {
+            end += 1;
+        }
+        let mut item_start = start;
+        let mut item_end = end;
+        while item_start < item_end && is_whitespace(value[item_start]) {
+            item_start += 1;
+        }
+        while item_end > item_start && is_whitespace(value[item_end - 1]) {
+            item_end -= 1;
+        }
+        if item_end - item_start == 1 && value[item_start] == b'*' {
+            return true;
+        }
+        let (item_start, item_weak) = opaque_tag_start(value, item_start, item_end);
+        let item_len = item_end - item_start;
+        // an empty item, such as from an empty header or a trailing comma, never matches
+        if item_len != 0 && (weak || (!etag_weak && !item_weak)) && item_len == etag_len {
+            let mut i = 0;
+            while i < item_len && value[item_start + i] == etag[etag_start + i] {
+                i += 1;
+            }
+            if i == item_len {
+                return true;
+            }
+        }
+        start = end + 1;
+    }
+    false
+}
+
+const fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n')
+}
+
+/// Compute a short version string from a byte slice, such as `vrhf5xu2`, for display in user interfaces and logs.
+/// The version is a `v` followed by 7 base36 characters derived from the same 64-bit xxhash3 hash as the etag.
+///
+/// Example:
+/// ```
+/// # use static_http_file::short_version;
+/// const VERSION: [u8; 8] = short_version(b"foo");
+/// assert_eq!(&VERSION, b"vrhf5xu2");
+/// ```
+pub const fn short_version(data: &[u8]) -> [u8; 8] {
+    const BASE36: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut n = xxhash_rust::const_xxh3::xxh3_64(data) % 36u64.pow(7);
+    let mut version = [b'v'; 8];
+    let mut i = version.len();
+    while i > 1 {
+        i -= 1;
+        version[i] = BASE36[(n % 36) as usize];
+        n /= 36;
+    }
+    version
+}
@@ -0,0 +1,249 @@
+/// The size of the hash table used to find earlier occurrences of three bytes.
+const HASH_BITS: u32 = 13;
+/// The largest distance of a match in deflate.
+const WINDOW: usize = 32768;
+/// The longest match in deflate.
+const MAX_MATCH: usize = 258;
+/// How many earlier occurrences are compared before settling for the longest match found so far.
+const MAX_CHAIN: usize = 32;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            k += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Computes the CRC-32 checksum of the gzip trailer.
+const fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    let mut i = 0;
+    while i < data.len() {
+        crc = CRC_TABLE[((crc ^ data[i] as u32) & 0xff) as usize] ^ (crc >> 8);
+        i += 1;
+    }
+    !crc
+}
+
+/// Reverses the `len` lowest bits, as Huffman codes are stored starting with the most significant bit.
+const fn reverse_bits(code: u32, len: u32) -> u32 {
+    let mut reversed = 0;
+    let mut i = 0;
+    while i < len {
+        reversed = (reversed << 1) | ((code >> i) & 1);
+        i += 1;
+    }
+    reversed
+}
+
+/// Hashes the three bytes starting at `i`.
+const fn hash3(data: &[u8], i: usize) -> usize {
+    let key = data[i] as u32 | (data[i + 1] as u32) << 8 | (data[i + 2] as u32) << 16;
+    (key.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Compresses `data` as a gzip member into the first bytes of the returned array, and returns the length of the member,
+/// which may be larger than `N` in which case the bytes which do not fit are left out.
+///
+/// The data is compressed as a single deflate block with the fixed Huffman codes,
+/// since building the dynamic codes would make the constant evaluation much slower.
+pub(crate) const fn gzip_into<const N: usize>(data: &[u8]) -> ([u8; N], usize) {
+    let mut out = [0u8; N];
+    let mut len = 0usize;
+    let mut bits = 0u64;
+    let mut nbits = 0u32;
+
+    macro_rules! put_byte {
+        ($byte:expr) => {{
+            let byte = $byte;
+            if len < N {
+                out[len] = byte;
+            }
+            len += 1;
+        }};
+    }
+    macro_rules! put_bits {
+        ($value:expr, $n:expr) => {{
+            bits |= (($value) as u64) << nbits;
+            nbits += ($n) as u32;
+            while nbits >= 8 {
+                put_byte!(bits as u8);
+                bits >>= 8;
+                nbits -= 8;
+            }
+        }};
+    }
+    macro_rules! put_code {
+        ($code:expr, $n:expr) => {
+            put_bits!(reverse_bits($code, $n), $n)
+        };
+    }
+    macro_rules! put_symbol {
+        ($symbol:expr) => {{
+            let symbol = ($symbol) as u32;
+            if symbol < 144 {
+                put_code!(0x30 + symbol, 8)
+            } else if symbol < 256 {
+                put_code!(0x190 + symbol - 144, 9)
+            } else if symbol < 280 {
+                put_code!(symbol - 256, 7)
+            } else {
+                put_code!(0xc0 + symbol - 280, 8)
+            }
+        }};
+    }
+
+    // magic, deflate, no flags, no modification time, no extra flags and an unknown operating system
+    let header = [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+    let mut i = 0;
+    while i < header.len() {
+        put_byte!(header[i]);
+        i += 1;
+    }
+    // the final block, compressed with the fixed Huffman codes
+    put_bits!(0b011, 3);
+
+    let mut head = [u32::MAX; 1 << HASH_BITS];
+    let mut prev = [u32::MAX; WINDOW];
+    macro_rules! insert {
+        ($pos:expr) => {{
+            let pos = $pos;
+            if pos + 3 <= data.len() {
+                let hash = hash3(data, pos);
+                prev[pos % WINDOW] = head[hash];
+                head[hash] = pos as u32;
+            }
+        }};
+    }
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best_len = 0;
+        let mut best_dist = 0;
+        if i + 3 <= data.len() {
+            let max = if data.len() - i < MAX_MATCH {
+                data.len() - i
+            } else {
+                MAX_MATCH
+            };
+            // every inserted position is before `i`, and stale entries of the chain are cut off by the window
+            let mut candidate = head[hash3(data, i)];
+            let mut chain = 0;
+            while candidate != u32::MAX && chain < MAX_CHAIN && i - (candidate as usize) <= WINDOW {
+                let start = candidate as usize;
+                let mut l = 0;
+                while l < max && data[start + l] == data[i + l] {
+                    l += 1;
+                }
+                if l > best_len {
+                    best_len = l;
+                    best_dist = i - start;
+                    if l == max {
+                        break;
+                    }
+                }
+                candidate = prev[start % WINDOW];
+                chain += 1;
+            }
+        }
+        if best_len >= 3 {
+            let mut code = LENGTH_BASE.len() - 1;
+            while LENGTH_BASE[code] as usize > best_len {
+                code -= 1;
+            }
+            put_symbol!(257 + code);
+            put_bits!(best_len - LENGTH_BASE[code] as usize, LENGTH_EXTRA[code]);
+            let mut code = DIST_BASE.len() - 1;
+            while DIST_BASE[code] as usize > best_dist {
+                code -= 1;
+            }
+            put_code!(code as u32, 5);
+            put_bits!(best_dist - DIST_BASE[code] as usize, DIST_EXTRA[code]);
+            let end = i + best_len;
+            while i < end {
+                insert!(i);
+                i += 1;
+            }
+        } else {
+            put_symbol!(data[i]);
+            insert!(i);
+            i += 1;
+        }
+    }
+    put_symbol!(256);
+    if nbits > 0 {
+        put_byte!(bits as u8);
+    }
+
+    let crc = crc32(data).to_le_bytes();
+    let size = (data.len() as u32).to_le_bytes();
+    let mut i = 0;
+    while i < 4 {
+        put_byte!(crc[i]);
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 4 {
+        put_byte!(size[i]);
+        i += 1;
+    }
+    (out, len)
+}
+
+/// Returns the length of the gzip compressed `data` produced by [`const_gzip`], which is its `N`.
+pub const fn const_gzip_len(data: &[u8]) -> usize {
+    gzip_into::<0>(data).1
+}
+
+/// Compresses `data` with gzip in a constant context, where `N` is the length given by [`const_gzip_len`].
+/// This is used by [`const_http_file_gz!`](crate::const_http_file_gz).
+///
+/// Only the fixed Huffman codes of deflate are used, so the data is compressed less than by a gzip tool,
+/// and the time of the constant evaluation grows with the length of the data.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_gzip, const_gzip_len};
+/// const DATA: &[u8] = b"hello hello hello hello hello hello";
+/// const GZIP: [u8; const_gzip_len(DATA)] = const_gzip(DATA);
+/// assert_eq!(&GZIP[..2], b"\x1f\x8b");
+/// assert!(GZIP.len() < DATA.len() + 18);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `N` is not the length given by [`const_gzip_len`], which is a compile time error in constant contexts.
+pub const fn const_gzip<const N: usize>(data: &[u8]) -> [u8; N] {
+    let (out, len) = gzip_into::<N>(data);
+    assert!(len == N, "N must be the length given by const_gzip_len");
+    out
+}
@@ -1,6 +1,6 @@
 use bytedata::ByteData;
 
-use crate::{HttpFile, HttpFileResponse};
+use crate::{CacheBusting, HttpFile, HttpFileResponse};
 
 /// A static HTTP file that can be computed at compile time or in other constant contexts.
 ///
@@ -12,43 +12,147 @@ pub struct ConstHttpFile {
     pub data: &'static [u8],
     pub mime: &'static str,
     pub etag: &'static str,
+    pub encoding: Option<&'static str>,
+    /// The cache busting method, which is referenced so that the file can still be copied.
+    pub cache_busting: &'static CacheBusting,
 }
 
 impl ConstHttpFile {
     /// Create a new [`ConstHttpFile`] with an explicit filename.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the etag is not valid according to [`validate_etag`](crate::validate_etag),
+    /// which is a compile time error in constant contexts.
     pub const fn new_named(
         data: &'static [u8],
         mime: &'static str,
         etag: &'static str,
         file: &'static str,
     ) -> Self {
+        assert!(
+            crate::validate_etag(etag),
+            "etag must be empty or a quoted string"
+        );
         ConstHttpFile {
             file: Some(file),
             data,
             mime,
             etag,
+            encoding: None,
+            cache_busting: &CacheBusting::None,
         }
     }
 
     /// Create a new [`ConstHttpFile`] without an explicit filename.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the etag is not valid according to [`validate_etag`](crate::validate_etag),
+    /// which is a compile time error in constant contexts.
     pub const fn new(data: &'static [u8], mime: &'static str, etag: &'static str) -> Self {
+        assert!(
+            crate::validate_etag(etag),
+            "etag must be empty or a quoted string"
+        );
+        ConstHttpFile {
+            file: None,
+            data,
+            mime,
+            etag,
+            encoding: None,
+            cache_busting: &CacheBusting::None,
+        }
+    }
+
+    /// Create a new [`ConstHttpFile`] where the data is already encoded using the content encoding `encoding`, such as `gzip`.
+    /// The etag should be computed from the encoded data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the etag is not valid according to [`validate_etag`](crate::validate_etag),
+    /// which is a compile time error in constant contexts.
+    pub const fn new_encoded(
+        data: &'static [u8],
+        mime: &'static str,
+        etag: &'static str,
+        encoding: &'static str,
+    ) -> Self {
+        assert!(
+            crate::validate_etag(etag),
+            "etag must be empty or a quoted string"
+        );
         ConstHttpFile {
             file: None,
             data,
             mime,
             etag,
+            encoding: Some(encoding),
+            cache_busting: &CacheBusting::None,
+        }
+    }
+
+    /// Set the cache busting method, which makes the file immutable and redirects requests without the current etag.
+    ///
+    /// Example:
+    /// ```
+    /// # use core::num::NonZeroU8;
+    /// # use static_http_file::{const_http_file, CacheBusting, ConstHttpFile, HttpFile};
+    /// const BUSTING: &CacheBusting = &CacheBusting::Suffix(NonZeroU8::new(b'.'), None);
+    /// const FILE: ConstHttpFile =
+    ///     const_http_file!(b"console.log(1);" as &[u8], "text/javascript").with_cache_busting(BUSTING);
+    /// assert!(FILE.cache_control().is_immutable());
+    /// ```
+    pub const fn with_cache_busting(mut self, cache_busting: &'static CacheBusting) -> Self {
+        self.cache_busting = cache_busting;
+        self
+    }
+
+    /// The final component of the file path, without the directories of the path used when the file was included.
+    /// This is the name that should be presented to clients, such as in a `Content-Disposition` header or a directory listing.
+    pub const fn basename(&self) -> Option<&'static str> {
+        match self.file {
+            Some(file) => crate::file_basename(file),
+            None => None,
         }
     }
 
+    /// The length of the data, which can be used for a `Content-Length` header in constant contexts.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{const_http_file, ConstHttpFile};
+    /// const FILE: ConstHttpFile = const_http_file!(b"Hello, world!" as &[u8], "text/plain");
+    /// const LEN: usize = FILE.const_len();
+    /// assert_eq!(LEN, 13);
+    /// ```
+    #[inline]
+    pub const fn const_len(&self) -> usize {
+        self.data.len()
+    }
+
     pub const fn const_etag_str(&self) -> &'static str {
-        if self.etag.is_empty() || !bytedata::const_starts_with(self.etag.as_bytes(), b"\"") {
+        let etag = if bytedata::const_starts_with(self.etag.as_bytes(), b"W/") {
+            match bytedata::const_slice_str(self.etag, 2..self.etag.len()) {
+                Ok(etag) => etag,
+                Err(_) => panic!("Invalid etag in ConstHttpFile"),
+            }
+        } else {
             self.etag
-        } else if let Some(a) = bytedata::const_slice_str(self.etag, 1..(self.etag.len() - 1)).ok() {
+        };
+        if etag.is_empty() || !bytedata::const_starts_with(etag.as_bytes(), b"\"") {
+            etag
+        } else if let Some(a) = bytedata::const_slice_str(etag, 1..(etag.len() - 1)).ok() {
             a
         } else {
             panic!("Invalid etag in ConstHttpFile")
         }
     }
+
+    /// Const specialization of [`HttpFile::etag_bytes`].
+    pub const fn const_etag_bytes(&self) -> &'static [u8] {
+        self.const_etag_str().as_bytes()
+    }
 }
 
 impl Default for ConstHttpFile {
@@ -56,8 +160,10 @@ impl Default for ConstHttpFile {
         ConstHttpFile {
             file: None,
             data: &[],
-            mime: "application/octet-data",
+            mime: crate::DEFAULT_MIME,
             etag: "",
+            encoding: None,
+            cache_busting: &CacheBusting::None,
         }
     }
 }
@@ -71,6 +177,14 @@ impl HttpFile<'static> for ConstHttpFile {
         self.etag
     }
 
+    fn content_encoding(&self) -> Option<&str> {
+        self.encoding
+    }
+
+    fn cache_busting(&self) -> &CacheBusting {
+        self.cache_busting
+    }
+
     fn data(&self) -> &[u8] {
         self.data
     }
@@ -84,7 +198,16 @@ impl HttpFile<'static> for ConstHttpFile {
     }
 }
 
-impl HttpFileResponse<'static> for ConstHttpFile {}
+impl HttpFileResponse<'static> for ConstHttpFile {
+    // the static etag can be referenced without copying it, unless it contains `obs-text` which `from_static` rejects
+    fn etag_header_value(&self) -> http::header::HeaderValue {
+        if self.etag.is_ascii() && crate::validate_etag(self.etag) {
+            http::header::HeaderValue::from_static(self.etag)
+        } else {
+            crate::traits::etag_to_header_value(self.etag)
+        }
+    }
+}
 
 /// Create a [`ConstHttpFile`] from a file path or bytes. An explicit MIME type can also be provided.
 ///
@@ -99,15 +222,34 @@ impl HttpFileResponse<'static> for ConstHttpFile {}
 ///
 /// /// No MIME type provided, so it will be detected from the file extension or file contents.
 /// /// Unfortunately, `.gitignore` files are not in the detection list for file extensions and have no detectable early content,
-/// /// so the MIME type will default to `application/octet-data`.
+/// /// so the MIME type will default to `application/octet-stream`.
 /// const FILE_1: ConstHttpFile = const_http_file!("../.gitignore");
 ///
 /// const FILE_2_BYTES: &[u8] = include_bytes!("../.gitignore");
 /// /// If the first argument is a non-literal expression, it will be used as the file contents instead of as a build-time path.
 /// const FILE_2: ConstHttpFile = const_http_file!(FILE_2_BYTES, "text/plain; charset=utf-8");
+///
+/// /// A pinned etag can be provided to avoid depending on the hashing implementation.
+/// /// The etag must either be empty or a quoted string, as checked by [`validate_etag`](crate::validate_etag).
+/// const FILE_3: ConstHttpFile = const_http_file!("../.gitignore", "text/plain; charset=utf-8", etag = "\"bk4EOvJYzH\"");
+/// ```
+///
+/// ```compile_fail
+/// # use static_http_file::{ConstHttpFile, const_http_file};
+/// /// A malformed etag fails at compile time.
+/// const FILE: ConstHttpFile = const_http_file!("../.gitignore", "text/plain", etag = "bk4EOvJYzH");
 /// ```
 #[macro_export]
 macro_rules! const_http_file {
+    ($file:literal, $mime:expr, etag = $etag:expr) => {{
+        const __FILE_BYTES: &[u8] = include_bytes!($file);
+        const __FILE_ETAG: &str = $etag;
+        const _: () = assert!(
+            $crate::validate_etag(__FILE_ETAG),
+            "etag must be empty or a quoted string"
+        );
+        $crate::ConstHttpFile::new_named(__FILE_BYTES, $mime, __FILE_ETAG, $file)
+    }};
     ($file:literal, $mime:expr) => {{
         const __FILE_BYTES: &[u8] = include_bytes!($file);
         const __FILE_ETAG: &str = $crate::const_etag!(__FILE_BYTES);
@@ -118,7 +260,7 @@ macro_rules! const_http_file {
         const __FILE_ETAG: &str = $crate::const_etag!(__FILE_BYTES);
         const __FILE_MIME: &str = ::bytedata::const_or_str(
             $crate::detect_mime_type($file, __FILE_BYTES),
-            "application/octet-data",
+            $crate::DEFAULT_MIME,
         );
         $crate::ConstHttpFile::new_named(__FILE_BYTES, __FILE_MIME, __FILE_ETAG, $file)
     }};
@@ -132,8 +274,73 @@ macro_rules! const_http_file {
         const __FILE_ETAG: &str = $crate::const_etag!(__FILE_BYTES);
         const __FILE_MIME: &str = ::bytedata::const_or_str(
             $crate::detect_mime_type_magic(__FILE_BYTES),
-            "application/octet-data",
+            $crate::DEFAULT_MIME,
         );
         $crate::ConstHttpFile::new(__FILE_BYTES, __FILE_MIME, __FILE_ETAG)
     }};
 }
+
+/// Create a new [`ConstHttpFile`] like [`const_http_file!`], where the data is compressed with gzip at compile time.
+///
+/// As with [`const_http_file!`], a string literal is a path to include, while data given directly must not be a literal,
+/// so a byte string is written as `b"..." as &[u8]`.
+///
+/// The MIME type is detected from the uncompressed data, while the etag is computed from the compressed data,
+/// which is served with `Content-Encoding: gzip` to every client. The compression is done by [`const_gzip`](crate::const_gzip),
+/// which is meant for small files, since the compile time grows with the length of the data.
+///
+/// # Examples
+///
+/// ```
+/// # use static_http_file::{const_etag, const_http_file_gz, ConstHttpFile, HttpFile};
+/// const FILE: ConstHttpFile = const_http_file_gz!(
+///     b"[1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]" as &[u8],
+///     "application/json"
+/// );
+/// assert_eq!(FILE.content_encoding(), Some("gzip"));
+/// assert_eq!(FILE.etag, const_etag!(FILE.data));
+/// assert_eq!(&FILE.data[..2], b"\x1f\x8b");
+///
+/// const GITIGNORE: ConstHttpFile = const_http_file_gz!("../.gitignore", "text/plain; charset=utf-8");
+/// assert_eq!(GITIGNORE.file, Some("../.gitignore"));
+/// ```
+#[macro_export]
+macro_rules! const_http_file_gz {
+    (@gzip $bytes:ident, $mime:expr, $file:expr) => {{
+        const __GZIP_LEN: usize = $crate::const_gzip_len($bytes);
+        const __GZIP: [u8; __GZIP_LEN] = $crate::const_gzip($bytes);
+        const __GZIP_BYTES: &[u8] = &__GZIP;
+        const __GZIP_ETAG: &str = $crate::const_etag!(__GZIP_BYTES);
+        const __GZIP_FILE: $crate::ConstHttpFile = {
+            let mut file =
+                $crate::ConstHttpFile::new_encoded(__GZIP_BYTES, $mime, __GZIP_ETAG, "gzip");
+            file.file = $file;
+            file
+        };
+        __GZIP_FILE
+    }};
+    ($file:literal, $mime:expr) => {{
+        const __FILE_BYTES: &[u8] = include_bytes!($file);
+        $crate::const_http_file_gz!(@gzip __FILE_BYTES, $mime, Some($file))
+    }};
+    ($file:literal) => {{
+        const __FILE_BYTES: &[u8] = include_bytes!($file);
+        const __FILE_MIME: &str = ::bytedata::const_or_str(
+            $crate::detect_mime_type($file, __FILE_BYTES),
+            $crate::DEFAULT_MIME,
+        );
+        $crate::const_http_file_gz!(@gzip __FILE_BYTES, __FILE_MIME, Some($file))
+    }};
+    ($file:expr, $mime:expr) => {{
+        const __FILE_BYTES: &[u8] = $file;
+        $crate::const_http_file_gz!(@gzip __FILE_BYTES, $mime, None)
+    }};
+    ($file:expr) => {{
+        const __FILE_BYTES: &[u8] = $file;
+        const __FILE_MIME: &str = ::bytedata::const_or_str(
+            $crate::detect_mime_type_magic(__FILE_BYTES),
+            $crate::DEFAULT_MIME,
+        );
+        $crate::const_http_file_gz!(@gzip __FILE_BYTES, __FILE_MIME, None)
+    }};
+}
@@ -1,10 +1,203 @@
+use crate::MimeType;
+
+/// The mime type used when the type of a file could not be detected.
+pub const DEFAULT_MIME: &str = MimeType::ApplicationOctetStream.as_str();
+
 /// Detects the mime type of a file based on its extension or magic bytes.
 pub const fn detect_mime_type(path: &str, data: &[u8]) -> Option<&'static str> {
-    let ext = detect_mime_type_ext(path);
+    mime_str(detect_mime_type_enum(path, data))
+}
+
+/// How a mime type was detected by [`detect_mime_type_detailed`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum MimeSource {
+    /// The mime type was detected from the file extension.
+    Extension,
+    /// The mime type was detected from the magic bytes at the start of the data.
+    Magic,
+}
+
+/// Detects the mime type of a file based on its extension or magic bytes, like [`detect_mime_type`],
+/// and reports which of them the mime type was detected from.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{detect_mime_type_detailed, MimeSource};
+/// assert_eq!(detect_mime_type_detailed("index.txt", b"<html>"), Some((MimeSource::Extension, "text/plain")));
+/// assert_eq!(detect_mime_type_detailed("index", b"<html>"), Some((MimeSource::Magic, "text/html")));
+/// assert_eq!(detect_mime_type_detailed("index", b""), None);
+/// ```
+pub const fn detect_mime_type_detailed(
+    path: &str,
+    data: &[u8],
+) -> Option<(MimeSource, &'static str)> {
+    if let Some(mime) = detect_mime_type_ext(path) {
+        return Some((MimeSource::Extension, mime));
+    }
+    match detect_mime_type_magic(data) {
+        Some(mime) => Some((MimeSource::Magic, mime)),
+        None => None,
+    }
+}
+
+/// Detects the mime type of a file based on its extension or magic bytes, returning it as a [`MimeType`].
+pub const fn detect_mime_type_enum(path: &str, data: &[u8]) -> Option<MimeType> {
+    let ext = ext_mime_type(path);
     if ext.is_some() {
         return ext;
     }
-    detect_mime_type_magic(data)
+    magic_mime_type(data)
+}
+
+const fn mime_str(mime: Option<MimeType>) -> Option<&'static str> {
+    match mime {
+        Some(mime) => Some(mime.as_str()),
+        None => None,
+    }
+}
+
+/// Detects the mime type of a file based on its extension or magic bytes, like [`detect_mime_type`], returning it as a [`mime::Mime`].
+///
+/// Returns `None` if the mime type could not be detected, where [`mime::APPLICATION_OCTET_STREAM`] is the equivalent of [`DEFAULT_MIME`].
+///
+/// Example:
+/// ```
+/// # use static_http_file::detect_mime;
+/// assert_eq!(detect_mime("style.css", b""), Some(mime::TEXT_CSS));
+/// assert_eq!(detect_mime("unknown", b""), None);
+/// ```
+#[cfg(feature = "mime")]
+pub fn detect_mime(path: &str, data: &[u8]) -> Option<mime::Mime> {
+    match detect_mime_type(path, data)? {
+        DEFAULT_MIME => Some(mime::APPLICATION_OCTET_STREAM),
+        // every mime type of the tables is a valid media type
+        detected => detected.parse().ok(),
+    }
+}
+
+/// Checks if the mime type detected from the file extension agrees with the one detected from the magic bytes.
+///
+/// If either detection fails there is nothing to compare and the file is considered to match.
+/// Container formats, such as zip archives and XML documents, match the more specific types built on top of them.
+///
+/// Example:
+/// ```
+/// # use static_http_file::ext_matches_magic;
+/// const _: () = assert!(ext_matches_magic("photo.jpg", b"\xFF\xD8\xFF\xE0"));
+/// const _: () = assert!(!ext_matches_magic("photo.gif", b"\xFF\xD8\xFF\xE0"));
+/// ```
+pub const fn ext_matches_magic(path: &str, data: &[u8]) -> bool {
+    let (Some(ext), Some(magic)) = (ext_mime_type(path), magic_mime_type(data)) else {
+        return true;
+    };
+    let ext_str = ext.as_str();
+    if const_str_eq(ext_str, magic.as_str()) {
+        return true;
+    }
+    match magic {
+        MimeType::ApplicationZip => {
+            matches!(ext, MimeType::ApplicationJavaArchive)
+                || const_str_starts_with(ext_str, "application/vnd.openxmlformats-officedocument.")
+                || const_str_starts_with(ext_str, "application/vnd.oasis.opendocument.")
+        }
+        MimeType::TextXml => {
+            matches!(ext, MimeType::ApplicationXml) || const_str_ends_with(ext_str, "+xml")
+        }
+        MimeType::ApplicationXGzip => matches!(ext, MimeType::ApplicationGzip),
+        MimeType::AudioMp3 => matches!(ext, MimeType::AudioMpeg),
+        _ => false,
+    }
+}
+
+/// Asserts at compile time that the extension of a file agrees with the mime type detected from its contents.
+/// See [`ext_matches_magic`] for the rules used.
+///
+/// # Examples
+///
+/// ```
+/// # use static_http_file::assert_ext_matches_magic;
+/// assert_ext_matches_magic!("../Cargo.toml");
+/// ```
+///
+/// ```compile_fail
+/// # use static_http_file::assert_ext_matches_magic;
+/// assert_ext_matches_magic!("../Cargo.toml", b"%PDF-1.7");
+/// ```
+#[macro_export]
+macro_rules! assert_ext_matches_magic {
+    ($file:literal) => {
+        $crate::assert_ext_matches_magic!($file, include_bytes!($file));
+    };
+    ($file:literal, $data:expr) => {
+        const _: () = assert!(
+            $crate::ext_matches_magic($file, $data),
+            concat!(
+                "the extension of ",
+                $file,
+                " does not match the mime type detected from its contents"
+            )
+        );
+    };
+}
+
+pub(crate) const fn const_str_eq(lhs: &str, rhs: &str) -> bool {
+    lhs.len() == rhs.len() && const_str_starts_with(lhs, rhs)
+}
+
+const fn const_str_starts_with(value: &str, prefix: &str) -> bool {
+    if value.len() < prefix.len() {
+        return false;
+    }
+    unsafe { bytes_matches(value.as_ptr(), prefix.as_bytes()) }
+}
+
+const fn const_str_ends_with(value: &str, suffix: &str) -> bool {
+    if value.len() < suffix.len() {
+        return false;
+    }
+    unsafe {
+        bytes_matches(
+            value.as_ptr().add(value.len() - suffix.len()),
+            suffix.as_bytes(),
+        )
+    }
+}
+
+/// Checks if a mime type describes textual data, which is useful for logging and compression heuristics.
+/// Any parameters of the mime type, such as the charset, are ignored.
+///
+/// Example:
+/// ```
+/// # use static_http_file::is_text_mime;
+/// const _: () = assert!(is_text_mime("text/html; charset=utf-8"));
+/// const _: () = assert!(is_text_mime("image/svg+xml"));
+/// const _: () = assert!(!is_text_mime("image/png"));
+/// ```
+pub const fn is_text_mime(mime: &str) -> bool {
+    let bytes = mime.as_bytes();
+    let mut len = 0;
+    while len < bytes.len() && bytes[len] != b';' {
+        len += 1;
+    }
+    while len > 0 && (bytes[len - 1] == b' ' || bytes[len - 1] == b'\t') {
+        len -= 1;
+    }
+    let essence =
+        unsafe { core::str::from_utf8_unchecked(core::slice::from_raw_parts(bytes.as_ptr(), len)) };
+    if const_str_starts_with(essence, "text/")
+        || const_str_ends_with(essence, "+xml")
+        || const_str_ends_with(essence, "+json")
+    {
+        return true;
+    }
+    matches!(
+        essence.as_bytes(),
+        b"application/json"
+            | b"application/javascript"
+            | b"application/xml"
+            | b"application/x-yaml"
+            | b"application/toml"
+    )
 }
 
 /// Returns the extension of a file, if any is found.
@@ -33,170 +226,370 @@ pub const fn file_ext(path: &'_ str) -> Option<&'_ str> {
     }
 }
 
-/// Detects the mime type of a file based on its extension.
+/// Returns the final component of a path, if it is not empty.
+///
+/// Example:
+/// ```
+/// # use static_http_file::file_basename;
+/// assert_eq!(file_basename("../assets/app.js"), Some("app.js"));
+/// assert_eq!(file_basename("..\\.gitignore"), Some(".gitignore"));
+/// assert_eq!(file_basename("assets/"), None);
+/// ```
+pub const fn file_basename(path: &'_ str) -> Option<&'_ str> {
+    let pathb = path.as_bytes();
+    let mut i = pathb.len();
+    while i != 0 {
+        let b = pathb[i - 1];
+        if b == b'/' || b == b'\\' {
+            break;
+        }
+        i -= 1;
+    }
+    if i == pathb.len() {
+        return None;
+    }
+    Some(unsafe {
+        core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+            pathb.as_ptr().add(i),
+            pathb.len() - i,
+        ))
+    })
+}
+
+/// Checks if a path is likely a development artifact which should not be served in production,
+/// such as source maps, TypeScript and stylesheet sources, or test files like `app.test.js`.
+///
+/// Example:
+/// ```
+/// # use static_http_file::is_dev_artifact;
+/// const _: () = assert!(is_dev_artifact("assets/app.js.map"));
+/// const _: () = assert!(!is_dev_artifact("assets/app.js"));
+/// ```
+pub const fn is_dev_artifact(path: &str) -> bool {
+    let Some(name) = file_basename(path) else {
+        return false;
+    };
+    if let Some(ext) = file_ext(name) {
+        if matches!(
+            ext.as_bytes(),
+            b"map" | b"ts" | b"tsx" | b"mts" | b"cts" | b"scss" | b"sass" | b"less"
+        ) {
+            return true;
+        }
+    }
+    // test files, such as `app.test.js`
+    const TEST: &[u8] = b".test.";
+    let name = name.as_bytes();
+    let mut i = 0;
+    while i + TEST.len() <= name.len() {
+        if unsafe { bytes_matches(name.as_ptr().add(i), TEST) } {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// The file extensions, without the leading dot, and mime types recognized by [`detect_mime_type_ext`].
+const EXTENSIONS: &[(&str, MimeType)] = &[
+    // common web formats
+    ("css", MimeType::TextCss),
+    ("html", MimeType::TextHtml),
+    ("htm", MimeType::TextHtml),
+    ("js", MimeType::ApplicationJavascript),
+    ("mjs", MimeType::ApplicationJavascript),
+    ("json", MimeType::ApplicationJson),
+    ("jsonld", MimeType::ApplicationLdJson),
+    ("wasm", MimeType::ApplicationWasm),
+    ("webmanifest", MimeType::ApplicationManifestJson),
+    ("xhtml", MimeType::ApplicationXhtmlXml),
+    // config files
+    ("yaml", MimeType::ApplicationXYaml),
+    ("yml", MimeType::ApplicationXYaml),
+    ("toml", MimeType::ApplicationToml),
+    ("ini", MimeType::TextPlain),
+    // shell files
+    ("sh", MimeType::ApplicationXSh),
+    ("bat", MimeType::ApplicationXBat),
+    ("cmd", MimeType::ApplicationXCmd),
+    // image types
+    ("avif", MimeType::ImageAvif),
+    ("apng", MimeType::ImageApng),
+    ("bmp", MimeType::ImageBmp),
+    ("png", MimeType::ImagePng),
+    ("jpg", MimeType::ImageJpeg),
+    ("jpeg", MimeType::ImageJpeg),
+    ("gif", MimeType::ImageGif),
+    ("ico", MimeType::ImageVndMicrosoftIcon),
+    ("svg", MimeType::ImageSvgXml),
+    ("tiff", MimeType::ImageTiff),
+    ("tif", MimeType::ImageTiff),
+    ("webp", MimeType::ImageWebp),
+    // fonts
+    ("eot", MimeType::ApplicationVndMsFontobject),
+    ("otf", MimeType::FontOtf),
+    ("ttf", MimeType::FontTtf),
+    ("woff", MimeType::FontWoff),
+    ("woff2", MimeType::FontWoff2),
+    // documents
+    ("atom", MimeType::ApplicationAtomXml),
+    ("csv", MimeType::TextCsv),
+    ("doc", MimeType::ApplicationMsword),
+    (
+        "docx",
+        MimeType::ApplicationVndOpenxmlformatsOfficedocumentWordprocessingmlDocument,
+    ),
+    ("ics", MimeType::TextCalendar),
+    ("md", MimeType::TextMarkdown),
+    ("odp", MimeType::ApplicationVndOasisOpendocumentPresentation),
+    ("ods", MimeType::ApplicationVndOasisOpendocumentSpreadsheet),
+    ("odt", MimeType::ApplicationVndOasisOpendocumentText),
+    ("pdf", MimeType::ApplicationPdf),
+    ("ppt", MimeType::ApplicationVndMsPowerpoint),
+    (
+        "pptx",
+        MimeType::ApplicationVndOpenxmlformatsOfficedocumentPresentationmlPresentation,
+    ),
+    ("rss", MimeType::ApplicationRssXml),
+    ("rtf", MimeType::ApplicationRtf),
+    ("txt", MimeType::TextPlain),
+    ("vsd", MimeType::ApplicationVndVisio),
+    ("xls", MimeType::ApplicationVndMsExcel),
+    (
+        "xlsx",
+        MimeType::ApplicationVndOpenxmlformatsOfficedocumentSpreadsheetmlSheet,
+    ),
+    ("xml", MimeType::ApplicationXml),
+    // comressed/archived
+    ("7z", MimeType::ApplicationX7zCompressed),
+    ("bz2", MimeType::ApplicationXBzip2),
+    ("gz", MimeType::ApplicationGzip),
+    ("jar", MimeType::ApplicationJavaArchive),
+    ("mpkg", MimeType::ApplicationVndAppleInstallerXml),
+    ("rar", MimeType::ApplicationVndRar),
+    ("tar", MimeType::ApplicationXTar),
+    ("war", MimeType::ApplicationJavaArchive),
+    ("xz", MimeType::ApplicationXXz),
+    ("zip", MimeType::ApplicationZip),
+    // audio
+    ("aac", MimeType::AudioAac),
+    ("flac", MimeType::AudioFlac),
+    ("m4a", MimeType::AudioMp4),
+    ("mid", MimeType::AudioMidi),
+    ("midi", MimeType::AudioMidi),
+    ("mp3", MimeType::AudioMpeg),
+    ("oga", MimeType::AudioOgg),
+    ("opus", MimeType::AudioOpus),
+    ("wav", MimeType::AudioWav),
+    ("weba", MimeType::AudioWebm),
+    // video
+    ("mp4", MimeType::VideoMp4),
+    ("m4v", MimeType::VideoMp4),
+    ("mpeg", MimeType::VideoMpeg),
+    ("mpg", MimeType::VideoMpeg),
+    ("mkv", MimeType::VideoXMatroska),
+    ("webm", MimeType::VideoWebm),
+    // media containers
+    ("m3u8", MimeType::ApplicationXMpegurl),
+    ("ogg", MimeType::ApplicationOgg),
+    ("ogx", MimeType::ApplicationOgg),
+];
+
+/// The file extensions, without the leading dot, and mime types recognized by [`detect_mime_type_ext`].
+pub const KNOWN_EXTENSIONS: &[(&str, &str)] = &{
+    let mut known = [("", ""); EXTENSIONS.len()];
+    let mut i = 0;
+    while i < known.len() {
+        known[i] = (EXTENSIONS[i].0, EXTENSIONS[i].1.as_str());
+        i += 1;
+    }
+    known
+};
+
+/// Returns an iterator over the file extensions and mime types recognized by [`detect_mime_type_ext`].
+///
+/// Example:
+/// ```
+/// # use static_http_file::known_extensions;
+/// assert!(known_extensions().any(|(ext, mime)| ext == "png" && mime == "image/png"));
+/// ```
+pub fn known_extensions() -> impl Iterator<Item = (&'static str, &'static str)> + Clone {
+    KNOWN_EXTENSIONS.iter().copied()
+}
+
+/// Returns all known file extensions for a mime type, in the order of [`KNOWN_EXTENSIONS`].
+/// Parameters such as `charset` are ignored and the mime type is compared case-insensitively.
+///
+/// Example:
+/// ```
+/// # use static_http_file::exts_for_mime;
+/// assert_eq!(exts_for_mime("image/jpeg").collect::<Vec<_>>(), ["jpg", "jpeg"]);
+/// assert_eq!(exts_for_mime("text/html; charset=utf-8").collect::<Vec<_>>(), ["html", "htm"]);
+/// assert_eq!(exts_for_mime("application/x-unknown").count(), 0);
+/// ```
+pub fn exts_for_mime(mime: &str) -> impl Iterator<Item = &'static str> + Clone + '_ {
+    let essence = mime.split(';').next().unwrap_or_default().trim();
+    KNOWN_EXTENSIONS
+        .iter()
+        .filter(move |(_, known)| known.eq_ignore_ascii_case(essence))
+        .map(|(ext, _)| *ext)
+}
+
+/// Returns the canonical file extension for a mime type, which is the first one returned by [`exts_for_mime`].
+///
+/// Example:
+/// ```
+/// # use static_http_file::ext_for_mime;
+/// assert_eq!(ext_for_mime("image/jpeg"), Some("jpg"));
+/// assert_eq!(ext_for_mime("application/x-unknown"), None);
+/// ```
+pub fn ext_for_mime(mime: &str) -> Option<&'static str> {
+    exts_for_mime(mime).next()
+}
+
+/// Detects the mime type of a file based on its extension, as listed in [`KNOWN_EXTENSIONS`].
 pub const fn detect_mime_type_ext(path: &str) -> Option<&'static str> {
+    mime_str(ext_mime_type(path))
+}
+
+const fn ext_mime_type(path: &str) -> Option<MimeType> {
     let Some(ext) = file_ext(path) else {
         return None;
     };
-    match ext.as_bytes() {
-        // common web formats
-        b"css" => Some("text/css"),
-        b"html" | b"htm" => Some("text/html"),
-        b"js" | b"mjs" => Some("application/javascript"),
-        b"json" => Some("application/json"),
-        b"jsonld" => Some("application/ld+json"),
-        b"wasm" => Some("application/wasm"),
-        b"webmanifest" => Some("application/manifest+json"),
-        b"xhtml" => Some("application/xhtml+xml"),
-
-        // config files
-        b"yaml" | b"yml" => Some("application/x-yaml"),
-        b"toml" => Some("application/toml"),
-        b"ini" => Some("text/plain"),
-
-        // shell files
-        b"sh" => Some("application/x-sh"),
-        b"bat" => Some("application/x-bat"),
-        b"cmd" => Some("application/x-cmd"),
-
-        // image types
-        b"avif" => Some("image/avif"),
-        b"apng" => Some("image/apng"),
-        b"bmp" => Some("image/bmp"),
-        b"png" => Some("image/png"),
-        b"jpg" | b"jpeg" => Some("image/jpeg"),
-        b"gif" => Some("image/gif"),
-        b"ico" => Some("image/vnd.microsoft.icon"),
-        b"svg" => Some("image/svg+xml"),
-        b"tiff" | b"tif" => Some("image/tiff"),
-        b"webp" => Some("image/webp"),
-
-        // fonts
-        b"eot" => Some("application/vnd.ms-fontobject"),
-        b"otf" => Some("font/otf"),
-        b"ttf" => Some("font/ttf"),
-        b"woff" => Some("font/woff"),
-        b"woff2" => Some("font/woff2"),
-
-        // documents
-        b"atom" => Some("application/atom+xml"),
-        b"csv" => Some("text/csv"),
-        b"doc" => Some("application/msword"),
-        b"docx" => Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
-        b"ics" => Some("text/calendar"),
-        b"md" => Some("text/markdown"),
-        b"odp" => Some("application/vnd.oasis.opendocument.presentation"),
-        b"ods" => Some("application/vnd.oasis.opendocument.spreadsheet"),
-        b"odt" => Some("application/vnd.oasis.opendocument.text"),
-        b"pdf" => Some("application/pdf"),
-        b"ppt" => Some("application/vnd.ms-powerpoint"),
-        b"pptx" => {
-            Some("application/vnd.openxmlformats-officedocument.presentationml.presentation")
+    let mut i = 0;
+    while i < EXTENSIONS.len() {
+        let (known, mime) = EXTENSIONS[i];
+        if const_str_eq(ext, known) {
+            return Some(mime);
         }
-        b"rss" => Some("application/rss+xml"),
-        b"rtf" => Some("application/rtf"),
-        b"txt" => Some("text/plain"),
-        b"vsd" => Some("application/vnd.visio"),
-        b"xls" => Some("application/vnd.ms-excel"),
-        b"xlsx" => Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
-        b"xml" => Some("application/xml"),
-
-        // comressed/archived
-        b"7z" => Some("application/x-7z-compressed"),
-        b"bz2" => Some("application/x-bzip2"),
-        b"gz" => Some("application/gzip"),
-        b"jar" => Some("application/java-archive"),
-        b"mpkg" => Some("application/vnd.apple.installer+xml"),
-        b"rar" => Some("application/vnd.rar"),
-        b"tar" => Some("application/x-tar"),
-        b"war" => Some("application/java-archive"),
-        b"xz" => Some("application/x-xz"),
-        b"zip" => Some("application/zip"),
-
-        // audio
-        b"aac" => Some("audio/aac"),
-        b"flac" => Some("audio/flac"),
-        b"m4a" => Some("audio/mp4"),
-        b"mid" | b"midi" => Some("audio/midi"),
-        b"mp3" => Some("audio/mpeg"),
-        b"oga" => Some("audio/ogg"),
-        b"opus" => Some("audio/opus"),
-        b"wav" => Some("audio/wav"),
-        b"weba" => Some("audio/webm"),
-
-        // video
-        b"mp4" | b"m4v" => Some("video/mp4"),
-        b"mpeg" | b"mpg" => Some("video/mpeg"),
-        b"mkv" => Some("video/x-matroska"),
-        b"webm" => Some("video/webm"),
-
-        // media containers
-        b"m3u8" => Some("application/x-mpegURL"),
-        b"ogg" | b"ogx" => Some("application/ogg"),
-
-        _ => None,
+        i += 1;
     }
+    None
 }
 
 type MagicLookup = (MagicOffset, &'static [u8], Magic);
 
 enum Magic {
-    Mime(&'static str),
-    Specialized(Option<&'static str>, &'static [MagicLookup]),
+    Mime(MimeType),
+    Specialized(Option<MimeType>, &'static [MagicLookup]),
 }
 
 enum MagicOffset {
     At(usize),
     Before(usize),
+    /// Matches a file name in the central directory of a zip archive.
+    ZipEntry,
 }
 
 const FTYP: &[MagicLookup] = &[
-    (MagicOffset::At(4), b"avif", Magic::Mime("image/avif")),
-    (MagicOffset::At(4), b"heic", Magic::Mime("image/heic")),
-    (MagicOffset::At(4), b"isom", Magic::Mime("video/mp4")),
-    (MagicOffset::At(4), b"mp41", Magic::Mime("video/mp4")),
-    (MagicOffset::At(4), b"mp42", Magic::Mime("video/mp4")),
-    (MagicOffset::At(4), b"mmp4", Magic::Mime("video/mp4")),
-    (MagicOffset::At(4), b"M4A", Magic::Mime("audio/mp4")),
+    (
+        MagicOffset::At(4),
+        b"avif",
+        Magic::Mime(MimeType::ImageAvif),
+    ),
+    (
+        MagicOffset::At(4),
+        b"heic",
+        Magic::Mime(MimeType::ImageHeic),
+    ),
+    (MagicOffset::At(4), b"isom", Magic::Mime(MimeType::VideoMp4)),
+    (MagicOffset::At(4), b"mp41", Magic::Mime(MimeType::VideoMp4)),
+    (MagicOffset::At(4), b"mp42", Magic::Mime(MimeType::VideoMp4)),
+    (MagicOffset::At(4), b"mmp4", Magic::Mime(MimeType::VideoMp4)),
+    (MagicOffset::At(4), b"M4A", Magic::Mime(MimeType::AudioMp4)),
 ];
 
+/// The first chunk of a WebP file describes the variant of the image.
+const WEBP: &[MagicLookup] = &[
+    // lossy
+    (
+        MagicOffset::At(12),
+        b"VP8 ",
+        Magic::Mime(MimeType::ImageWebp),
+    ),
+    // lossless
+    (
+        MagicOffset::At(12),
+        b"VP8L",
+        Magic::Mime(MimeType::ImageWebp),
+    ),
+    // extended, which may be animated or have an alpha channel
+    (
+        MagicOffset::At(12),
+        b"VP8X",
+        Magic::Mime(MimeType::ImageWebp),
+    ),
+];
+
+// the form type follows the 4 byte chunk size
 const RIFF: &[MagicLookup] = &[
-    (MagicOffset::At(4), b"AVI ", Magic::Mime("video/x-msvideo")),
-    (MagicOffset::At(4), b"CDDA", Magic::Mime("audio/aiff")),
-    (MagicOffset::At(4), b"WAVE", Magic::Mime("audio/wav")),
-    (MagicOffset::At(4), b"WEBP", Magic::Mime("image/webp")),
+    (
+        MagicOffset::At(8),
+        b"AVI ",
+        Magic::Mime(MimeType::VideoXMsvideo),
+    ),
+    (
+        MagicOffset::At(8),
+        b"CDDA",
+        Magic::Mime(MimeType::AudioAiff),
+    ),
+    (MagicOffset::At(8), b"WAVE", Magic::Mime(MimeType::AudioWav)),
+    (
+        MagicOffset::At(8),
+        b"WEBP",
+        Magic::Specialized(Some(MimeType::ImageWebp), WEBP),
+    ),
+];
+
+const ZIP_APK: &[MagicLookup] = &[(
+    MagicOffset::ZipEntry,
+    b"classes.dex",
+    Magic::Mime(MimeType::ApplicationVndAndroidPackageArchive),
+)];
+
+const ZIP: &[MagicLookup] = &[
+    (
+        MagicOffset::ZipEntry,
+        b"AndroidManifest.xml",
+        Magic::Specialized(Some(MimeType::ApplicationZip), ZIP_APK),
+    ),
+    (
+        MagicOffset::ZipEntry,
+        b"META-INF/MANIFEST.MF",
+        Magic::Mime(MimeType::ApplicationJavaArchive),
+    ),
 ];
 
 const XML: &[MagicLookup] = &[
     (
         MagicOffset::Before(46),
         b"<!DOCTYPE html",
-        Magic::Mime("application/xhtml+xml"),
+        Magic::Mime(MimeType::ApplicationXhtmlXml),
     ),
     (
         MagicOffset::Before(46),
         b"<!DOCTYPE svg",
-        Magic::Mime("image/svg+xml"),
+        Magic::Mime(MimeType::ImageSvgXml),
     ),
     (
         MagicOffset::Before(120),
         b"xmlns=\"http://www.w3.org/1999/html\"",
-        Magic::Mime("application/xhtml+xml"),
+        Magic::Mime(MimeType::ApplicationXhtmlXml),
     ),
     (
         MagicOffset::Before(120),
         b"xmlns=\"http://www.w3.org/2000/svg\"",
-        Magic::Mime("image/svg+xml"),
+        Magic::Mime(MimeType::ImageSvgXml),
     ),
     (
         MagicOffset::Before(46),
         b"<html",
-        Magic::Mime("application/xhtml+xml"),
+        Magic::Mime(MimeType::ApplicationXhtmlXml),
     ),
     (
         MagicOffset::Before(46),
         b"<svg",
-        Magic::Mime("image/svg+xml"),
+        Magic::Mime(MimeType::ImageSvgXml),
     ),
 ];
 
@@ -204,109 +597,199 @@ const MAGICS: &[MagicLookup] = &[
     (
         MagicOffset::At(0),
         b"\0\0\x01\xBA",
-        Magic::Mime("video/mpeg"),
+        Magic::Mime(MimeType::VideoMpeg),
     ),
     (
         MagicOffset::At(0),
         b"\0\0\x01\xBB",
-        Magic::Mime("video/mpeg"),
+        Magic::Mime(MimeType::VideoMpeg),
+    ),
+    (
+        MagicOffset::At(0),
+        b"\0\x01\0\0",
+        Magic::Mime(MimeType::FontTtf),
+    ),
+    (
+        MagicOffset::At(0),
+        b"\0asm",
+        Magic::Mime(MimeType::ApplicationWasm),
     ),
-    (MagicOffset::At(0), b"\0asm", Magic::Mime("text/x-asm")),
     (
         MagicOffset::At(0),
         b"\x1A\x45\xDF\xA3",
-        Magic::Mime("video/webm"),
+        Magic::Mime(MimeType::VideoWebm),
     ),
     (
         MagicOffset::At(0),
         b"\x1F\x8B\x08",
-        Magic::Mime("application/x-gzip"),
+        Magic::Mime(MimeType::ApplicationXGzip),
     ),
     (
         MagicOffset::At(0),
-        b"#!/bin/bash\n",
-        Magic::Mime("application/x-sh"),
+        b"7z\xBC\xAF\x27\x1C",
+        Magic::Mime(MimeType::ApplicationX7zCompressed),
     ),
     (
         MagicOffset::At(0),
-        b"#!/bin/sh\n",
-        Magic::Mime("application/x-sh"),
+        b"BZh",
+        Magic::Mime(MimeType::ApplicationXBzip2),
     ),
     (
         MagicOffset::At(0),
-        b"7z\xBC\xAF\x27\x1C",
-        Magic::Mime("application/x-7z-compressed"),
+        b"GIF87a",
+        Magic::Mime(MimeType::ImageGif),
     ),
     (
         MagicOffset::At(0),
-        b"<?xml",
-        Magic::Specialized(Some("text/xml"), XML),
+        b"GIF89a",
+        Magic::Mime(MimeType::ImageGif),
     ),
+    (MagicOffset::At(0), b"I I", Magic::Mime(MimeType::ImageTiff)),
+    (MagicOffset::At(0), b"ID3", Magic::Mime(MimeType::AudioMp3)),
     (
         MagicOffset::At(0),
-        b"<!DOCTYPE html",
-        Magic::Mime("text/html"),
+        b"II*\0",
+        Magic::Mime(MimeType::ImageTiff),
     ),
-    (MagicOffset::At(0), b"<html", Magic::Mime("text/html")),
-    (MagicOffset::At(0), b"<svg", Magic::Mime("image/svg+xml")),
     (
         MagicOffset::At(0),
-        b"BZh",
-        Magic::Mime("application/x-bzip2"),
+        b"MM\0*",
+        Magic::Mime(MimeType::ImageTiff),
     ),
-    (MagicOffset::At(0), b"GIF87a", Magic::Mime("image/gif")),
-    (MagicOffset::At(0), b"GIF89a", Magic::Mime("image/gif")),
-    (MagicOffset::At(0), b"I I", Magic::Mime("image/tiff")),
-    (MagicOffset::At(0), b"ID3", Magic::Mime("audio/mp3")),
-    (MagicOffset::At(0), b"II*\0", Magic::Mime("image/tiff")),
-    (MagicOffset::At(0), b"MM\0*", Magic::Mime("image/tiff")),
-    (MagicOffset::At(0), b"MM\0+", Magic::Mime("image/tiff")),
-    (MagicOffset::At(0), b"MThd", Magic::Mime("audio/midi")),
+    (
+        MagicOffset::At(0),
+        b"MM\0+",
+        Magic::Mime(MimeType::ImageTiff),
+    ),
+    (
+        MagicOffset::At(0),
+        b"MThd",
+        Magic::Mime(MimeType::AudioMidi),
+    ),
+    (MagicOffset::At(0), b"OTTO", Magic::Mime(MimeType::FontOtf)),
     (
         MagicOffset::At(0),
         b"OggS\0\x02\0\0\0\0\0\0\0\0",
-        Magic::Mime("application/ogg"),
+        Magic::Mime(MimeType::ApplicationOgg),
     ),
     (
         MagicOffset::At(0),
         b"PK\x03\x04",
-        Magic::Mime("application/ogg"),
+        Magic::Specialized(Some(MimeType::ApplicationZip), ZIP),
     ),
     (MagicOffset::At(0), b"RIFF", Magic::Specialized(None, RIFF)),
     (
         MagicOffset::At(0),
         b"Rar!\x1A\x07",
-        Magic::Mime("application/vnd.rar"),
+        Magic::Mime(MimeType::ApplicationVndRar),
+    ),
+    (
+        MagicOffset::At(0),
+        b"gimp xcf ",
+        Magic::Mime(MimeType::ImageXXcf),
+    ),
+    (
+        MagicOffset::At(0),
+        b"icns",
+        Magic::Mime(MimeType::ImageXIcns),
+    ),
+    (
+        MagicOffset::At(0),
+        b"true\0",
+        Magic::Mime(MimeType::FontTtf),
+    ),
+    (MagicOffset::At(0), b"wOFF", Magic::Mime(MimeType::FontWoff)),
+    (
+        MagicOffset::At(0),
+        b"wOF2",
+        Magic::Mime(MimeType::FontWoff2),
+    ),
+    (
+        MagicOffset::At(0),
+        b"%PDF-",
+        Magic::Mime(MimeType::ApplicationPdf),
     ),
-    (MagicOffset::At(0), b"gimp xcf ", Magic::Mime("image/x-xcf")),
-    (MagicOffset::At(0), b"icns", Magic::Mime("image/x-icns")),
-    (MagicOffset::At(0), b"true\0", Magic::Mime("font/ttf")),
-    (MagicOffset::At(0), b"wOFF", Magic::Mime("font/woff")),
-    (MagicOffset::At(0), b"wOF2", Magic::Mime("font/woff2")),
-    (MagicOffset::At(0), b"%PDF-", Magic::Mime("application/pdf")),
     (
         MagicOffset::At(0),
         b"%PNG\x0D\x0A\x1A\x0A",
-        Magic::Mime("image/png"),
+        Magic::Mime(MimeType::ImagePng),
+    ),
+    (
+        MagicOffset::At(0),
+        b"\xFF\xD8",
+        Magic::Mime(MimeType::ImageJpeg),
     ),
-    (MagicOffset::At(0), b"\xFF\xD8", Magic::Mime("image/jpeg")),
     (MagicOffset::At(4), b"ftyp", Magic::Specialized(None, FTYP)),
-    (MagicOffset::At(4), b"moov", Magic::Mime("video/quicktime")),
+    (
+        MagicOffset::At(4),
+        b"moov",
+        Magic::Mime(MimeType::VideoQuicktime),
+    ),
     (
         MagicOffset::At(257),
         b"ustar",
-        Magic::Mime("application/x-tar"),
+        Magic::Mime(MimeType::ApplicationXTar),
+    ),
+];
+
+/// Magics of text formats, which may be preceded by a UTF-8 byte order mark.
+const TEXT_MAGICS: &[MagicLookup] = &[
+    (
+        MagicOffset::At(0),
+        b"#!/bin/bash\n",
+        Magic::Mime(MimeType::ApplicationXSh),
+    ),
+    (
+        MagicOffset::At(0),
+        b"#!/bin/sh\n",
+        Magic::Mime(MimeType::ApplicationXSh),
+    ),
+    (
+        MagicOffset::At(0),
+        b"<?xml",
+        Magic::Specialized(Some(MimeType::TextXml), XML),
+    ),
+    (
+        MagicOffset::At(0),
+        b"<!DOCTYPE html",
+        Magic::Mime(MimeType::TextHtml),
+    ),
+    (
+        MagicOffset::At(0),
+        b"<html",
+        Magic::Mime(MimeType::TextHtml),
+    ),
+    (
+        MagicOffset::At(0),
+        b"<svg",
+        Magic::Mime(MimeType::ImageSvgXml),
     ),
 ];
 
 /// Detects the mime type of a file based on its magic bytes.
+///
+/// A leading UTF-8 byte order mark is skipped when looking for text formats.
 pub const fn detect_mime_type_magic(data: &[u8]) -> Option<&'static str> {
+    mime_str(magic_mime_type(data))
+}
+
+const fn magic_mime_type(data: &[u8]) -> Option<MimeType> {
+    const BOM: &[u8] = b"\xEF\xBB\xBF";
     let data_len = data.len();
-    if data_len != 0 {
-        let data_ptr = data.as_ptr();
-        lookup_magic(MAGICS, data_len, data_ptr)
+    if data_len == 0 {
+        return None;
+    }
+    let data_ptr = data.as_ptr();
+    let r = lookup_magic(MAGICS, data_len, data_ptr);
+    if r.is_some() {
+        return r;
+    }
+    if data_len >= BOM.len() && unsafe { bytes_matches(data_ptr, BOM) } {
+        lookup_magic(TEXT_MAGICS, data_len - BOM.len(), unsafe {
+            data_ptr.add(BOM.len())
+        })
     } else {
-        None
+        lookup_magic(TEXT_MAGICS, data_len, data_ptr)
     }
 }
 
@@ -314,7 +797,7 @@ const fn lookup_magic(
     magics: &[MagicLookup],
     data_len: usize,
     data_ptr: *const u8,
-) -> Option<&'static str> {
+) -> Option<MimeType> {
     let mut i = 0;
     loop {
         if i == magics.len() {
@@ -337,30 +820,35 @@ const fn lookup_magic(
                 continue;
             }
             MagicOffset::Before(offset) => {
+                // the magic may start at any position before `offset` as long as it fits in the data
                 let offset = *offset;
-                let mx = data_len - magic.len();
-                let mx = if offset < mx { offset } else { mx };
+                let last = data_len - magic.len();
                 let mut j = 0;
                 let is_matching = loop {
-                    if !unsafe { bytes_matches(data_ptr.add(j), magic) } {
-                        j += 1;
-                        if j != mx {
-                            continue;
-                        } else {
-                            break false;
-                        }
+                    if j >= offset || j > last {
+                        break false;
                     }
-                    break true;
+                    if unsafe { bytes_matches(data_ptr.add(j), magic) } {
+                        break true;
+                    }
+                    j += 1;
                 };
                 if !is_matching {
                     i += 1;
                     continue;
                 }
             }
+            MagicOffset::ZipEntry => {
+                let data = unsafe { core::slice::from_raw_parts(data_ptr, data_len) };
+                if !zip_has_entry(data, magic) {
+                    i += 1;
+                    continue;
+                }
+            }
         }
         match magic_type {
             Magic::Mime(mime) => {
-                return Some(mime);
+                return Some(*mime);
             }
             Magic::Specialized(mime, magics) => {
                 let r = lookup_magic(magics, data_len, data_ptr);
@@ -373,6 +861,71 @@ const fn lookup_magic(
     }
 }
 
+/// Looks up the magics used to specialize XML documents, without requiring the XML declaration.
+#[cfg(test)]
+pub(crate) const fn detect_mime_type_xml(data: &[u8]) -> Option<&'static str> {
+    mime_str(lookup_magic(XML, data.len(), data.as_ptr()))
+}
+
+const fn read_u16_le(data: &[u8], offset: usize) -> usize {
+    u16::from_le_bytes([data[offset], data[offset + 1]]) as usize
+}
+
+const fn read_u32_le(data: &[u8], offset: usize) -> usize {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ]) as usize
+}
+
+/// Checks if the central directory of a zip archive contains an entry with the exact name.
+const fn zip_has_entry(data: &[u8], name: &[u8]) -> bool {
+    const EOCD_LEN: usize = 22;
+    const CDFH_LEN: usize = 46;
+    if data.len() < EOCD_LEN {
+        return false;
+    }
+    // the end of central directory record is followed by a comment of at most 65535 bytes
+    let mut eocd = data.len() - EOCD_LEN;
+    let min_eocd = eocd.saturating_sub(u16::MAX as usize);
+    loop {
+        if unsafe { bytes_matches(data.as_ptr().add(eocd), b"PK\x05\x06") } {
+            break;
+        }
+        if eocd == min_eocd {
+            return false;
+        }
+        eocd -= 1;
+    }
+    let entries = read_u16_le(data, eocd + 10);
+    let mut offset = read_u32_le(data, eocd + 16);
+    let mut i = 0;
+    while i < entries {
+        // the offset is read from the data, so it is compared without adding to it to avoid overflows on 32-bit targets
+        if offset > data.len()
+            || data.len() - offset < CDFH_LEN
+            || !unsafe { bytes_matches(data.as_ptr().add(offset), b"PK\x01\x02") }
+        {
+            return false;
+        }
+        let name_len = read_u16_le(data, offset + 28);
+        let extra_len = read_u16_le(data, offset + 30);
+        let comment_len = read_u16_le(data, offset + 32);
+        let name_start = offset + CDFH_LEN;
+        if data.len() - name_start < name_len {
+            return false;
+        }
+        if name_len == name.len() && unsafe { bytes_matches(data.as_ptr().add(name_start), name) } {
+            return true;
+        }
+        offset = name_start + name_len + extra_len + comment_len;
+        i += 1;
+    }
+    false
+}
+
 const unsafe fn bytes_matches(lhs: *const u8, rhs: &[u8]) -> bool {
     let mut i = 0;
     loop {
@@ -0,0 +1,173 @@
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
+use crate::{CacheBusting, HttpFile, HttpFileResponse, RangeParseResult};
+
+/// The methods a file responds to, as advertised in the `Allow` header.
+pub const ALLOWED_METHODS: &str = "GET, HEAD, OPTIONS";
+
+/// The outcome of evaluating a request against a file, independent of any HTTP library.
+///
+/// This contains the method check, the cache busting redirect, the conditional headers and the range resolution,
+/// which [`HttpFileResponse::respond_guard`](crate::HttpFileResponse::respond_guard) turns into an `http` response.
+/// Other HTTP stacks can use it directly and build the response themselves.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_http_file, ConstHttpFile, ResponseDecision};
+/// const FILE: ConstHttpFile = const_http_file!(b"Hello, world!" as &[u8], "text/plain");
+/// let decision = ResponseDecision::new(&FILE, "GET", "/hello.txt", None, |name| match name {
+///     "range" => Some("bytes=0-4"),
+///     _ => None,
+/// });
+/// assert_eq!(decision, ResponseDecision::PartialContent { first: 0, last: 4, total: 13 });
+/// assert_eq!(decision.status(), 206);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ResponseDecision {
+    /// The method is not supported; respond with `405` and an `Allow` header.
+    MethodNotAllowed,
+    /// An `OPTIONS *` request which targets the server rather than the file; respond with `204` and an `Allow` header.
+    ServerOptions,
+    /// The request should be redirected to the cache-busted location with a `307` that is not cached.
+    Redirect(String),
+    /// An `OPTIONS` request for the file; respond with `204`, an `Allow` header and the file headers.
+    Options,
+    /// The `If-Match` header did not match, or the file was modified after the `If-Unmodified-Since` date; respond with `412`.
+    PreconditionFailed,
+    /// The `If-None-Match` header matched, or the file was not modified after the `If-Modified-Since` date;
+    /// respond with `304` and the file headers.
+    NotModified,
+    /// A single satisfiable range was requested; respond with `206` and `Content-Range: bytes first-last/total`.
+    PartialContent { first: u64, last: u64, total: u64 },
+    /// The requested range is not satisfiable; respond with `416` and `Content-Range: bytes */total`.
+    RangeNotSatisfiable { total: u64 },
+    /// Respond with `200` and the file headers, including the data unless `body` is `false` for a `HEAD` request.
+    Full { body: bool },
+}
+
+impl ResponseDecision {
+    /// Evaluate a request against a file.
+    ///
+    /// The `method` is compared case-sensitively, `path` and `query` are the parts of the request target,
+    /// and `header` returns the value of a request header by its lowercase name.
+    pub fn new<'a, 'h, F: HttpFile<'a> + ?Sized>(
+        file: &F,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+        header: impl Fn(&str) -> Option<&'h str>,
+    ) -> Self {
+        if method != "GET" && method != "HEAD" && method != "OPTIONS" {
+            return ResponseDecision::MethodNotAllowed;
+        }
+        if method == "OPTIONS" && path == "*" {
+            return ResponseDecision::ServerOptions;
+        }
+        let location = match file.cache_busting() {
+            CacheBusting::None => None,
+            CacheBusting::Query(query_key) => crate::traits::cachebust_query_location(
+                path,
+                query,
+                query_key.as_str(),
+                file.etag_str(),
+            ),
+            CacheBusting::Suffix(left_sep, len) => {
+                crate::traits::cachebust_suffix_location(path, file.etag_str(), *left_sep, *len)
+            }
+        };
+        if let Some(location) = location {
+            return ResponseDecision::Redirect(location);
+        }
+        // conditional headers do not apply to OPTIONS, so it must be answered before they are evaluated
+        if method == "OPTIONS" {
+            return ResponseDecision::Options;
+        }
+        // the preconditions are evaluated in the order of RFC 9110, where a date is only compared if there is no etag condition
+        if let Some(etag) = header("if-match") {
+            if !crate::const_etag_matches(file.etag(), etag) {
+                return ResponseDecision::PreconditionFailed;
+            }
+        } else if let Some(since) = header("if-unmodified-since") {
+            let modified = file.last_modified();
+            if let (Some(modified), Some(since)) = (modified, crate::parse_http_date(since)) {
+                if modified > since {
+                    return ResponseDecision::PreconditionFailed;
+                }
+            }
+        }
+        if let Some(etag) = header("if-none-match") {
+            if crate::const_etag_matches_weak(file.etag(), etag) {
+                return ResponseDecision::NotModified;
+            }
+        } else if let Some(since) = header("if-modified-since") {
+            let modified = file.last_modified();
+            if let (Some(modified), Some(since)) = (modified, crate::parse_http_date(since)) {
+                if modified <= since {
+                    return ResponseDecision::NotModified;
+                }
+            }
+        }
+        if method == "GET" && file.accepts_ranges() {
+            if let Some(range) = header("range") {
+                // a range is only applied if the representation is unchanged since the client cached the rest of it,
+                // which requires a strong comparison so a weak etag never satisfies it
+                let if_range = header("if-range")
+                    .map(|value| !file.etag().starts_with("W/") && value == file.etag())
+                    .unwrap_or(true);
+                let total = file.data().len() as u64;
+                match crate::parse_ranges(range, total) {
+                    // multiple ranges would require a multipart response, so the full data is sent instead
+                    RangeParseResult::Ranges(ranges) if if_range && ranges.len() == 1 => {
+                        let (first, last) = ranges[0];
+                        return ResponseDecision::PartialContent { first, last, total };
+                    }
+                    RangeParseResult::Unsatisfiable if if_range => {
+                        return ResponseDecision::RangeNotSatisfiable { total };
+                    }
+                    _ => {}
+                }
+            }
+        }
+        ResponseDecision::Full {
+            body: method != "HEAD",
+        }
+    }
+
+    /// The status code of the response.
+    pub const fn status(&self) -> u16 {
+        match self {
+            ResponseDecision::MethodNotAllowed => 405,
+            ResponseDecision::ServerOptions | ResponseDecision::Options => 204,
+            ResponseDecision::Redirect(_) => 307,
+            ResponseDecision::PreconditionFailed => 412,
+            ResponseDecision::NotModified => 304,
+            ResponseDecision::PartialContent { .. } => 206,
+            ResponseDecision::RangeNotSatisfiable { .. } => 416,
+            ResponseDecision::Full { .. } => 200,
+        }
+    }
+}
+
+/// Returns the headers describing a file as lowercase name and value pairs, for servers which are not based on the `http` crate.
+///
+/// These are the headers of [`HttpFileResponse::response_headers`] in the same order, including `Vary` and any security headers.
+/// Use [`HttpFileResponse::response_header_pairs`] for the headers of the response to a specific request.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_http_file, header_pairs, ConstHttpFile};
+/// const FILE: ConstHttpFile = const_http_file!(b"body {}" as &[u8], "text/css");
+/// let headers = header_pairs(&FILE);
+/// assert_eq!(headers[0].0, "content-type");
+/// assert_eq!(headers[0].1, "text/css");
+/// assert!(headers.iter().any(|(name, value)| name == "etag" && value == FILE.etag));
+/// ```
+pub fn header_pairs<'a>(
+    file: &impl HttpFileResponse<'a>,
+) -> Vec<(Cow<'static, str>, Cow<'static, str>)> {
+    match file.response_headers(http::Response::builder()).body(()) {
+        Ok(response) => crate::traits::header_map_pairs(response.headers()),
+        Err(_) => Vec::new(),
+    }
+}
@@ -0,0 +1,76 @@
+use alloc::{borrow::Cow, sync::Arc, vec::Vec};
+
+use super::{ExposeError, ExposedDirectory};
+use crate::StdHttpFile;
+
+/// Dispatches requests to multiple [`ExposedDirectory`]s mounted at different path prefixes.
+///
+/// When mounts overlap, the directory with the longest matching prefix is used.
+#[derive(Default)]
+pub struct DirectoryRouter {
+    /// The mounts, ordered from the longest prefix to the shortest.
+    mounts: Vec<(Cow<'static, str>, Arc<ExposedDirectory>)>,
+}
+
+impl DirectoryRouter {
+    /// Create a new [`DirectoryRouter`] without any mounts.
+    pub const fn new() -> Self {
+        DirectoryRouter { mounts: Vec::new() }
+    }
+
+    /// Mount a directory at a path prefix, such as `/static`.
+    /// The remainder of a request path after the prefix is resolved relative to the web path of the directory.
+    ///
+    /// A directory previously mounted at the same prefix is replaced.
+    pub fn add(
+        &mut self,
+        prefix: impl Into<Cow<'static, str>>,
+        dir: impl Into<Arc<ExposedDirectory>>,
+    ) -> &mut Self {
+        let prefix: Cow<'static, str> = prefix.into();
+        let prefix = match prefix.strip_suffix('/') {
+            Some(trimmed) => Cow::Owned(trimmed.to_owned()),
+            None => prefix,
+        };
+        let dir = dir.into();
+        match self.mounts.iter_mut().find(|(p, _)| *p == prefix) {
+            Some(mount) => mount.1 = dir,
+            None => {
+                let i = self
+                    .mounts
+                    .iter()
+                    .position(|(p, _)| p.len() < prefix.len())
+                    .unwrap_or(self.mounts.len());
+                self.mounts.insert(i, (prefix, dir));
+            }
+        }
+        self
+    }
+
+    /// Get the directory and the remaining path for a request path, using the longest matching prefix.
+    fn mount<'p>(&self, path: &'p str) -> Option<(&Arc<ExposedDirectory>, &'p str)> {
+        self.mounts.iter().find_map(|(prefix, dir)| {
+            let rest = path.strip_prefix(prefix.as_ref())?;
+            if rest.is_empty() || rest.starts_with('/') {
+                Some((dir, rest))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Get the file for a request path from the directory mounted at the longest matching prefix.
+    ///
+    /// Returns [`ExposeError::Io`] if the file is exposed but could not be read.
+    pub fn get(&self, path: &str) -> Result<Arc<StdHttpFile>, ExposeError> {
+        let (dir, rest) = self.mount(path).ok_or(ExposeError::NotFound)?;
+        if path.len() - rest.len() == dir.web_path().len() && path.starts_with(dir.web_path()) {
+            // the directory is exposed at the same path as it is mounted
+            return dir.get(path);
+        }
+        let mut dir_path = String::with_capacity(dir.web_path().len() + rest.len());
+        dir_path.push_str(dir.web_path());
+        dir_path.push_str(rest);
+        dir.get(&dir_path)
+    }
+}
@@ -0,0 +1,150 @@
+use alloc::sync::Arc;
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use bytedata::ByteData;
+
+use super::ExposedDirectory;
+
+/// A [`tower_service::Service`] answering requests with the files of an [`ExposedDirectory`].
+///
+/// Requests which do not resolve to a file are answered with `404 Not Found`.
+/// The service is always ready, and files of a [`DirWarmup::Warm`] or [`DirWarmup::Hot`] directory are looked up synchronously.
+/// A [`DirWarmup::Cold`] directory reads the file system for every request, which is done with
+/// `tokio::task::spawn_blocking` when the `tokio_1` feature is enabled and the service is called
+/// within a tokio runtime, and otherwise blocks the caller.
+///
+/// Example:
+/// ```no_run
+/// # use static_http_file::{DirWarmup, DirectoryService, ExposedDirectory};
+/// # use tower_service::Service;
+/// let dir = ExposedDirectory::new_blocking(DirWarmup::Hot, "/static", "./public", |_: &str, _: &str| true).unwrap();
+/// let mut service: DirectoryService = DirectoryService::new(dir);
+/// let request = http::Request::get("/static/index.html").body(()).unwrap();
+/// let response = service.call(request);
+/// ```
+pub struct DirectoryService<B = ByteData<'static>> {
+    dir: Arc<ExposedDirectory>,
+    body: PhantomData<fn() -> B>,
+}
+
+impl<B> DirectoryService<B> {
+    /// Create a new [`DirectoryService`] serving the files of a directory.
+    pub fn new(dir: impl Into<Arc<ExposedDirectory>>) -> Self {
+        DirectoryService {
+            dir: dir.into(),
+            body: PhantomData,
+        }
+    }
+
+    /// Returns the directory served by the service.
+    pub fn directory(&self) -> &Arc<ExposedDirectory> {
+        &self.dir
+    }
+}
+
+impl<B> Clone for DirectoryService<B> {
+    fn clone(&self) -> Self {
+        DirectoryService {
+            dir: self.dir.clone(),
+            body: PhantomData,
+        }
+    }
+}
+
+impl<B: From<ByteData<'static>>> tower_service::Service<http::Request<()>> for DirectoryService<B> {
+    type Response = http::Response<B>;
+    type Error = http::Error;
+    type Future = DirectoryFuture<B>;
+
+    #[inline]
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<()>) -> Self::Future {
+        #[cfg(feature = "tokio_1")]
+        if self.dir.warmup() == super::DirWarmup::Cold {
+            if let Ok(handle) = tokio_1::runtime::Handle::try_current() {
+                let dir = self.dir.clone();
+                let task = handle.spawn_blocking(move || respond(&dir, &request));
+                return DirectoryFuture {
+                    state: State::Blocking(task),
+                };
+            }
+        }
+        DirectoryFuture {
+            state: State::Ready(Some(
+                respond(&self.dir, &request).map(|res| res.map(B::from)),
+            )),
+        }
+    }
+}
+
+/// Responds to a request with the files of a directory, answering `404 Not Found` if it does not resolve to a file.
+fn respond(
+    dir: &ExposedDirectory,
+    request: &http::Request<()>,
+) -> Result<http::Response<ByteData<'static>>, http::Error> {
+    dir.respond(request).unwrap_or_else(|| {
+        http::Response::builder()
+            .status(http::StatusCode::NOT_FOUND)
+            .body(ByteData::from_static(&[]))
+    })
+}
+
+/// Responds with `503 Service Unavailable`, like [`ExposedDirectory::respond`] does for a file which could not be read.
+#[cfg(feature = "tokio_1")]
+fn service_unavailable() -> Result<http::Response<ByteData<'static>>, http::Error> {
+    http::Response::builder()
+        .status(http::StatusCode::SERVICE_UNAVAILABLE)
+        .header(
+            http::header::CACHE_CONTROL,
+            http::header::HeaderValue::from_static("no-store"),
+        )
+        .body(ByteData::from_static(&[]))
+}
+
+/// The [`Future`] of a response from a [`DirectoryService`].
+pub struct DirectoryFuture<B> {
+    state: State<B>,
+}
+
+enum State<B> {
+    Ready(Option<Result<http::Response<B>, http::Error>>),
+    #[cfg(feature = "tokio_1")]
+    Blocking(tokio_1::task::JoinHandle<Result<http::Response<ByteData<'static>>, http::Error>>),
+}
+
+// the response is never pinned, so the future can be moved regardless of the body type
+impl<B> Unpin for DirectoryFuture<B> {}
+
+impl<B: From<ByteData<'static>>> Future for DirectoryFuture<B> {
+    type Output = Result<http::Response<B>, http::Error>;
+
+    #[cfg_attr(not(feature = "tokio_1"), allow(unused_variables))]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &mut self.get_mut().state {
+            State::Ready(response) => Poll::Ready(
+                response
+                    .take()
+                    .expect("DirectoryFuture polled after completion"),
+            ),
+            #[cfg(feature = "tokio_1")]
+            State::Blocking(task) => match Pin::new(task).poll(cx) {
+                Poll::Ready(Ok(response)) => Poll::Ready(response.map(|res| res.map(B::from))),
+                // a panic while responding is propagated as if the response was computed synchronously
+                Poll::Ready(Err(err)) if err.is_panic() => {
+                    std::panic::resume_unwind(err.into_panic())
+                }
+                // the task is only cancelled when the runtime shuts down, which is transient like an I/O error
+                Poll::Ready(Err(_)) => {
+                    Poll::Ready(service_unavailable().map(|res| res.map(B::from)))
+                }
+                Poll::Pending => Poll::Pending,
+            },
+        }
+    }
+}
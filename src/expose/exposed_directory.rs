@@ -1,9 +1,11 @@
-use core::sync::atomic::{AtomicPtr, AtomicU8};
+use core::sync::atomic::AtomicPtr;
+use std::path::Path;
 
 use alloc::{borrow::Cow, collections::BTreeMap, sync::Arc};
-use bytedata::StringData;
+use bytedata::{ByteData, StringData};
 
-// TODO: complete this file
+use super::super::std::StdHttpFile;
+use crate::{CacheBusting, HttpFile, HttpFileResponse, SharedHttpFile};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DirWarmup {
@@ -28,6 +30,13 @@ pub trait ExposeFilterTrait: core::any::Any + Send + Sync + 'static {
         path: &'b str,
     ) -> Option<StringData<'s>>;
     fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send);
+    /// Returns whether the filter may expose an entry under another name than its name on disk.
+    /// A lookup of a name which does not exist on disk lists the directory only if the filter renames entries.
+    ///
+    /// Defaults to `true`, so filters which only accept or reject names should return `false`.
+    fn renames_entries(&self) -> bool {
+        true
+    }
 }
 
 impl ExposeFilterTrait for ExposeFilter {
@@ -48,6 +57,9 @@ impl ExposeFilterTrait for ExposeFilter {
     fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send) {
         self
     }
+    fn renames_entries(&self) -> bool {
+        self.filter.renames_entries()
+    }
 }
 
 impl ExposeFilterTrait for fn(&str) -> bool {
@@ -76,6 +88,9 @@ impl ExposeFilterTrait for fn(&str) -> bool {
     fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send) {
         self
     }
+    fn renames_entries(&self) -> bool {
+        false
+    }
 }
 
 impl ExposeFilterTrait for for<'a> fn(&'a str) -> Option<&'a str> {
@@ -126,6 +141,168 @@ impl<F: Fn(&str, &str) -> bool + Send + Sync + 'static> ExposeFilterTrait for F
     fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send) {
         self
     }
+    fn renames_entries(&self) -> bool {
+        false
+    }
+}
+
+/// Removes a prefix from the names of files and directories, rejecting names without it.
+struct StripPrefix(&'static str);
+
+impl ExposeFilterTrait for StripPrefix {
+    fn filter_map_file<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        _base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        path.strip_prefix(self.0).map(StringData::from_borrowed)
+    }
+    fn filter_map_dir<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        _base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        path.strip_prefix(self.0).map(StringData::from_borrowed)
+    }
+    fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send) {
+        self
+    }
+}
+
+/// Applies the second filter to the output of the first one.
+struct Chain(ExposeFilter, ExposeFilter);
+
+/// Maps the output of a previous filter, only copying the path if it was changed.
+fn map_output<'s>(
+    path: StringData<'s>,
+    map: impl FnOnce(&str) -> Option<StringData<'_>>,
+) -> Option<StringData<'s>> {
+    let mapped = map(path.as_str())?;
+    if mapped.as_str() == path.as_str() {
+        Some(path)
+    } else {
+        Some(StringData::from(mapped.as_str().to_owned()))
+    }
+}
+
+impl ExposeFilterTrait for Chain {
+    fn filter_map_file<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        let path = self.0.filter_map_file(base, path)?;
+        map_output(path, |path| self.1.filter_map_file(base, path))
+    }
+    fn filter_map_dir<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        let path = self.0.filter_map_dir(base, path)?;
+        map_output(path, |path| self.1.filter_map_dir(base, path))
+    }
+    fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send) {
+        self
+    }
+    fn renames_entries(&self) -> bool {
+        self.0.renames_entries() || self.1.renames_entries()
+    }
+}
+
+/// Only exposes files with one of the extensions, while directories are always exposed.
+struct Extensions(&'static [&'static str]);
+
+impl ExposeFilterTrait for Extensions {
+    fn filter_map_file<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        _base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        let ext = crate::file_ext(path)?;
+        if self.0.contains(&ext) {
+            Some(StringData::from_borrowed(path))
+        } else {
+            None
+        }
+    }
+    fn filter_map_dir<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        _base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        Some(StringData::from_borrowed(path))
+    }
+    fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send) {
+        self
+    }
+    fn renames_entries(&self) -> bool {
+        false
+    }
+}
+
+/// Requires every filter to pass, where each filter is applied to the name returned by the previous one.
+struct All(Vec<ExposeFilter>);
+
+impl ExposeFilterTrait for All {
+    fn filter_map_file<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        let mut path = StringData::from_borrowed(path);
+        for filter in self.0.iter() {
+            path = map_output(path, |path| filter.filter_map_file(base, path))?;
+        }
+        Some(path)
+    }
+    fn filter_map_dir<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        let mut path = StringData::from_borrowed(path);
+        for filter in self.0.iter() {
+            path = map_output(path, |path| filter.filter_map_dir(base, path))?;
+        }
+        Some(path)
+    }
+    fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send) {
+        self
+    }
+    fn renames_entries(&self) -> bool {
+        self.0.iter().any(|filter| filter.renames_entries())
+    }
+}
+
+/// Requires any filter to pass, where the name returned by the first passing filter is used.
+struct Any(Vec<ExposeFilter>);
+
+impl ExposeFilterTrait for Any {
+    fn filter_map_file<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        self.0
+            .iter()
+            .find_map(|filter| filter.filter_map_file(base, path))
+    }
+    fn filter_map_dir<'s, 'a: 's, 'b: 's>(
+        &'s self,
+        base: &'a str,
+        path: &'b str,
+    ) -> Option<StringData<'s>> {
+        self.0
+            .iter()
+            .find_map(|filter| filter.filter_map_dir(base, path))
+    }
+    fn as_any(&'_ self) -> &'_ (dyn core::any::Any + Sync + Send) {
+        self
+    }
+    fn renames_entries(&self) -> bool {
+        self.0.iter().any(|filter| filter.renames_entries())
+    }
 }
 
 /// A filter that can be used to limit exposure of files and directories.
@@ -182,48 +359,235 @@ impl ExposeFilter {
         };
         ExposeFilter { filter }
     }
+    /// Create a filter that only exposes files and directories starting with `prefix`, which is removed from the exposed name.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{ExposeFilter, ExposeFilterTrait};
+    /// let filter = ExposeFilter::strip_prefix("public-");
+    /// assert_eq!(filter.filter_map_file("", "public-app.js").unwrap().as_str(), "app.js");
+    /// assert!(filter.filter_map_file("", "secret.js").is_none());
+    /// ```
+    pub fn strip_prefix(prefix: &'static str) -> Self {
+        ExposeFilter::new(StripPrefix(prefix))
+    }
+    /// Combine two filters, where an entry must pass both and `other` is applied to the name returned by `self`.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{ExposeFilter, ExposeFilterTrait};
+    /// let filter = ExposeFilter::strip_prefix("public-").chain(ExposeFilter::not_hidden());
+    /// assert_eq!(filter.filter_map_file("", "public-app.js").unwrap().as_str(), "app.js");
+    /// assert!(filter.filter_map_file("", "public-.env").is_none());
+    /// ```
+    pub fn chain(self, other: ExposeFilter) -> Self {
+        ExposeFilter::new(Chain(self, other))
+    }
+    /// Create a filter that only exposes files with one of the extensions, given without the leading dot.
+    /// Directories are always exposed, so files in nested directories can still be reached.
+    pub fn extensions(extensions: &'static [&'static str]) -> Self {
+        ExposeFilter::new(Extensions(extensions))
+    }
+    /// Create a filter that exposes entries passing every filter, which passes everything if `filters` is empty.
+    ///
+    /// The filters are applied in order, where each filter receives the name returned by the previous one,
+    /// so a filter renaming entries affects the names seen by the filters after it.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{ExposeFilter, ExposeFilterTrait};
+    /// let filter = ExposeFilter::all(&[ExposeFilter::not_hidden(), ExposeFilter::extensions(&["js"])]);
+    /// assert!(filter.filter_map_file("", "app.js").is_some());
+    /// assert!(filter.filter_map_file("", ".app.js").is_none());
+    /// assert!(filter.filter_map_file("", "app.css").is_none());
+    /// ```
+    pub fn all(filters: &[ExposeFilter]) -> Self {
+        ExposeFilter::new(All(filters.to_vec()))
+    }
+    /// Create a filter that exposes entries passing any of the filters, which passes nothing if `filters` is empty.
+    ///
+    /// The filters are tried in order, and the name returned by the first passing filter is used.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{ExposeFilter, ExposeFilterTrait};
+    /// let filter = ExposeFilter::any(&[ExposeFilter::strip_prefix("public-"), ExposeFilter::extensions(&["js"])]);
+    /// assert_eq!(filter.filter_map_file("", "public-app.css").unwrap().as_str(), "app.css");
+    /// assert_eq!(filter.filter_map_file("", "app.js").unwrap().as_str(), "app.js");
+    /// assert!(filter.filter_map_file("", "app.css").is_none());
+    /// ```
+    pub fn any(filters: &[ExposeFilter]) -> Self {
+        ExposeFilter::new(Any(filters.to_vec()))
+    }
 }
 
-type FileEntry = (
-    AtomicU8,
-    AtomicU8,
-    parking_lot::RwLock<Arc<super::super::std::StdHttpFile>>,
-);
+/// A file in an [`ExposedDirectory`], which is `None` until it has been read.
+type FileEntry = parking_lot::RwLock<Option<Arc<StdHttpFile>>>;
 
+/// A directory on the file system exposed as a set of HTTP files.
 pub struct ExposedDirectory {
     warmup: DirWarmup,
     web_path: Cow<'static, str>,
     file_path: Cow<'static, str>,
-    files: parking_lot::RwLock<BTreeMap<Cow<'static, str>, FileEntry>>,
-    nested: parking_lot::RwLock<BTreeMap<Cow<'static, str>, ExposedDirectory>>,
+    files: parking_lot::RwLock<BTreeMap<Cow<'static, str>, Arc<FileEntry>>>,
+    nested: parking_lot::RwLock<BTreeMap<Cow<'static, str>, Arc<ExposedDirectory>>>,
     filter: ExposeFilter,
+    index_file: Cow<'static, str>,
+    trailing_slash_redirect: bool,
+    directory_listing: bool,
+}
+
+/// The error returned when a request path cannot be served from an [`ExposedDirectory`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExposeError {
+    /// The path does not resolve to an exposed file, which should usually be answered with `404 Not Found`.
+    NotFound,
+    /// The path resolves to an exposed file which could not be read, such as a file deleted after the directory was scanned.
+    /// This is usually transient and should be answered with `503 Service Unavailable`.
+    Io(std::io::Error),
+}
+
+impl core::fmt::Display for ExposeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ExposeError::NotFound => f.write_str("file not found"),
+            ExposeError::Io(err) => write!(f, "failed to read file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ExposeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExposeError::NotFound => None,
+            ExposeError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for ExposeError {
+    fn from(err: std::io::Error) -> Self {
+        ExposeError::Io(err)
+    }
+}
+
+/// The result of resolving a request path in an [`ExposedDirectory`].
+enum Resolved {
+    File(Arc<StdHttpFile>),
+    Directory {
+        /// The resolved directory, which is `None` for the root directory.
+        dir: Option<Arc<ExposedDirectory>>,
+        index: Option<Arc<StdHttpFile>>,
+        trailing_slash: bool,
+    },
 }
 
 impl ExposedDirectory {
+    /// Create a new [`ExposedDirectory`] serving the files of `file_path` below `web_path`.
+    /// Depending on `warmup` the files are read immediately or when first requested.
+    ///
+    /// Only a `file_path` which cannot be listed is an error. Files which cannot be read while warming up,
+    /// such as files larger than [`DEFAULT_MAX_FILE_SIZE`](crate::DEFAULT_MAX_FILE_SIZE), are read again when requested
+    /// and nested directories which cannot be listed are not exposed.
     pub fn new_blocking(
         warmup: DirWarmup,
         web_path: impl Into<Cow<'static, str>>,
         file_path: impl Into<Cow<'static, str>>,
         filter: impl ExposeFilterTrait,
     ) -> std::io::Result<Self> {
-        let web_path = web_path.into();
-        let file_path = file_path.into();
-        let filter = ExposeFilter::new(filter);
+        Self::new_blocking_filtered(
+            warmup,
+            web_path.into(),
+            file_path.into(),
+            ExposeFilter::new(filter),
+            false,
+        )
+    }
+
+    /// Create a new [`ExposedDirectory`] like [`ExposedDirectory::new_blocking`],
+    /// but files and nested directories are read and hashed concurrently on the rayon thread pool.
+    ///
+    /// The speedup depends on the number of threads and the storage, so it can be measured for a tree of 10k files
+    /// with `cargo test --release --features rayon -- --ignored --nocapture bench_exposed_directory_parallel`.
+    #[cfg(feature = "rayon")]
+    pub fn new_blocking_parallel(
+        warmup: DirWarmup,
+        web_path: impl Into<Cow<'static, str>>,
+        file_path: impl Into<Cow<'static, str>>,
+        filter: impl ExposeFilterTrait,
+    ) -> std::io::Result<Self> {
+        Self::new_blocking_filtered(
+            warmup,
+            web_path.into(),
+            file_path.into(),
+            ExposeFilter::new(filter),
+            true,
+        )
+    }
+
+    fn new_blocking_filtered(
+        warmup: DirWarmup,
+        web_path: Cow<'static, str>,
+        file_path: Cow<'static, str>,
+        filter: ExposeFilter,
+        parallel: bool,
+    ) -> std::io::Result<Self> {
+        let web_path = match web_path.strip_suffix('/') {
+            Some(trimmed) => Cow::Owned(trimmed.to_owned()),
+            None => web_path,
+        };
         let mut files = BTreeMap::new();
         let mut nested = BTreeMap::new();
         if matches!(warmup, DirWarmup::Hot | DirWarmup::Warm) {
-            let mut walker = std::fs::read_dir(file_path.as_ref())?;
-            while let Some(entry) = walker.next().and_then(|entry| entry.ok()) {
+            let mut file_entries = Vec::new();
+            let mut dir_entries = Vec::new();
+            for entry in std::fs::read_dir(file_path.as_ref())?.flatten() {
+                let Some(name) = entry.file_name().to_str().map(String::from) else {
+                    continue;
+                };
                 let path = entry.path();
-                if path.is_file() {
-                    // TODO: files.insert(endpoint, file_entry);
-                    1
-                } else if path.is_dir() {
-                    // TODO: nested.insert(endpoint, ExposedDirectory::new_blocking(warmup, endpoint, file_path)?);
-                    2
-                } else {
+                let Some(path_str) = path.to_str() else {
                     continue;
                 };
+                if path.is_file() {
+                    let Some(endpoint) = filter.filter_map_file(&web_path, &name) else {
+                        continue;
+                    };
+                    file_entries.push((endpoint.as_str().to_owned(), path_str.to_owned()));
+                } else if path.is_dir() {
+                    let Some(endpoint) = filter.filter_map_dir(&web_path, &name) else {
+                        continue;
+                    };
+                    dir_entries.push((endpoint.as_str().to_owned(), path_str.to_owned()));
+                }
+            }
+            // the entries are loaded first and then inserted, so the maps are only touched from this thread
+            let loaded = map_entries(parallel, file_entries, |(endpoint, path)| {
+                (endpoint, StdHttpFile::new(path).ok())
+            });
+            for (endpoint, file) in loaded {
+                // a file which could not be read is kept unread, so it is read again when requested
+                files.insert(
+                    Cow::Owned(endpoint),
+                    Arc::new(parking_lot::RwLock::new(file.map(Arc::new))),
+                );
+            }
+            let loaded = map_entries(parallel, dir_entries, |(endpoint, path)| {
+                let dir = Self::new_blocking_filtered(
+                    warmup,
+                    Cow::Owned(format!("{}/{}", web_path, endpoint)),
+                    Cow::Owned(path),
+                    filter.clone(),
+                    parallel,
+                );
+                (endpoint, dir.ok())
+            });
+            // a nested directory which could not be listed is skipped instead of failing the whole directory
+            for (endpoint, dir) in loaded {
+                if let Some(dir) = dir {
+                    nested.insert(Cow::Owned(endpoint), Arc::new(dir));
+                }
             }
         }
         Ok(ExposedDirectory {
@@ -233,6 +597,400 @@ impl ExposedDirectory {
             files: parking_lot::RwLock::new(files),
             nested: parking_lot::RwLock::new(nested),
             filter,
+            index_file: Cow::Borrowed("index.html"),
+            trailing_slash_redirect: false,
+            directory_listing: false,
+        })
+    }
+
+    /// Sets the name of the file served for requests to a directory. Defaults to `index.html`.
+    pub fn with_index_file(mut self, index_file: impl Into<Cow<'static, str>>) -> Self {
+        self.index_file = index_file.into();
+        self
+    }
+
+    /// Redirects requests to a directory without a trailing slash, such as `/docs`, to the path with a trailing slash using `301 Moved Permanently`.
+    /// When disabled, which is the default, the directory index is served directly.
+    pub fn with_trailing_slash_redirect(mut self, redirect: bool) -> Self {
+        self.trailing_slash_redirect = redirect;
+        self
+    }
+
+    /// Serves a generated HTML listing of the contents for requests to a directory without an index file.
+    /// Disabled by default.
+    pub fn with_directory_listing(mut self, listing: bool) -> Self {
+        self.directory_listing = listing;
+        self
+    }
+
+    /// How the files of the directory are read and cached.
+    pub fn warmup(&self) -> DirWarmup {
+        self.warmup
+    }
+
+    /// The web path where the directory is exposed.
+    pub fn web_path(&self) -> &str {
+        self.web_path.as_ref()
+    }
+
+    /// The file system path of the directory.
+    pub fn file_path(&self) -> &str {
+        self.file_path.as_ref()
+    }
+
+    /// Get the file for a request path. Requests to a directory resolve to its index file.
+    ///
+    /// Returns [`ExposeError::Io`] if the file is exposed but could not be read, which may happen for files read lazily.
+    pub fn get(&self, path: &str) -> Result<Arc<StdHttpFile>, ExposeError> {
+        match self.resolve(path)? {
+            Resolved::File(file) => Ok(file),
+            Resolved::Directory { index, .. } => index.ok_or(ExposeError::NotFound),
+        }
+    }
+
+    /// Get the generated HTML listing for a request path, if it resolves to a directory without an index file and listing is enabled.
+    pub fn listing(&self, path: &str) -> Option<SharedHttpFile<'static>> {
+        match self.resolve(path).ok()? {
+            Resolved::Directory {
+                dir, index: None, ..
+            } if self.directory_listing => Some(dir.as_deref().unwrap_or(self).render_listing()),
+            _ => None,
+        }
+    }
+
+    /// Responds to a request for a file in the directory.
+    ///
+    /// Returns `None` if the request path does not resolve to a file, which should usually be answered with `404 Not Found`.
+    /// Files which could not be read are answered with `503 Service Unavailable`.
+    pub fn respond<T: From<ByteData<'static>>>(
+        &self,
+        request: &http::Request<()>,
+    ) -> Option<Result<http::Response<T>, http::Error>> {
+        let resolved = match self.resolve(request.uri().path()) {
+            Ok(resolved) => resolved,
+            Err(ExposeError::NotFound) => return None,
+            Err(ExposeError::Io(_)) => {
+                return Some(
+                    http::Response::builder()
+                        .status(http::StatusCode::SERVICE_UNAVAILABLE)
+                        .header(
+                            http::header::CACHE_CONTROL,
+                            http::header::HeaderValue::from_static("no-store"),
+                        )
+                        .body(ByteData::from_static(&[]).into()),
+                )
+            }
+        };
+        match resolved {
+            Resolved::File(file) => Some(file.respond_borrowed(request)),
+            Resolved::Directory {
+                index: Some(_),
+                trailing_slash: false,
+                ..
+            } if self.trailing_slash_redirect => {
+                let uri = request.uri();
+                let mut location = String::with_capacity(
+                    uri.path().len() + 2 + uri.query().map_or(0, |query| query.len()),
+                );
+                location.push_str(uri.path());
+                location.push('/');
+                if let Some(query) = uri.query() {
+                    location.push('?');
+                    location.push_str(query);
+                }
+                Some(
+                    http::Response::builder()
+                        .status(http::StatusCode::MOVED_PERMANENTLY)
+                        .header(http::header::LOCATION, location)
+                        .body(ByteData::from_static(&[]).into()),
+                )
+            }
+            Resolved::Directory {
+                dir, index: None, ..
+            } if self.directory_listing => Some(
+                dir.as_deref()
+                    .unwrap_or(self)
+                    .render_listing()
+                    .respond(request),
+            ),
+            Resolved::Directory { index, .. } => Some(index?.respond_borrowed(request)),
+        }
+    }
+
+    fn resolve(&self, path: &str) -> Result<Resolved, ExposeError> {
+        let relative = path
+            .strip_prefix(self.web_path.as_ref())
+            .ok_or(ExposeError::NotFound)?;
+        if !relative.is_empty() && !relative.starts_with('/') {
+            return Err(ExposeError::NotFound);
+        }
+        let trailing_slash = relative.ends_with('/');
+        let mut segments = relative.split('/').filter(|s| !s.is_empty()).peekable();
+        let mut dir: Option<Arc<ExposedDirectory>> = None;
+        while let Some(segment) = segments.next() {
+            let segment = crate::urldecode(segment).map_err(|_| ExposeError::NotFound)?;
+            let segment = core::str::from_utf8(&segment).map_err(|_| ExposeError::NotFound)?;
+            if segment == "." || segment == ".." || segment.contains(['/', '\\']) {
+                return Err(ExposeError::NotFound);
+            }
+            let current = dir.as_deref().unwrap_or(self);
+            if segments.peek().is_none() && !trailing_slash {
+                match current.file(segment) {
+                    Ok(file) => return Ok(Resolved::File(file)),
+                    Err(ExposeError::NotFound) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            dir = Some(current.dir(segment).ok_or(ExposeError::NotFound)?);
+        }
+        let index = match dir.as_deref().unwrap_or(self).file(&self.index_file) {
+            Ok(index) => Some(index),
+            Err(ExposeError::NotFound) => None,
+            Err(err) => return Err(err),
+        };
+        Ok(Resolved::Directory {
+            dir,
+            index,
+            trailing_slash,
         })
     }
+
+    /// Lists the endpoints of the directories and files directly in this directory, in that order.
+    fn entries(&self) -> (Vec<String>, Vec<String>) {
+        if self.warmup != DirWarmup::Cold {
+            let dirs = self.nested.read().keys().map(|k| k.to_string()).collect();
+            let files = self.files.read().keys().map(|k| k.to_string()).collect();
+            return (dirs, files);
+        }
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        let Ok(read_dir) = std::fs::read_dir(self.file_path.as_ref()) else {
+            return (dirs, files);
+        };
+        for entry in read_dir.flatten() {
+            let Some(name) = entry.file_name().to_str().map(String::from) else {
+                continue;
+            };
+            let path = entry.path();
+            if path.is_file() {
+                if let Some(endpoint) = self.filter.filter_map_file(&self.web_path, &name) {
+                    files.push(endpoint.as_str().to_owned());
+                }
+            } else if path.is_dir() {
+                if let Some(endpoint) = self.filter.filter_map_dir(&self.web_path, &name) {
+                    dirs.push(endpoint.as_str().to_owned());
+                }
+            }
+        }
+        dirs.sort();
+        files.sort();
+        (dirs, files)
+    }
+
+    /// Renders an HTML listing of the directory.
+    /// Links to files are cache-busted according to the [`CacheBusting`] of each file.
+    fn render_listing(&self) -> SharedHttpFile<'static> {
+        let (dirs, files) = self.entries();
+        let mut html = String::with_capacity(256 + 64 * (dirs.len() + files.len()));
+        html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Index of ");
+        push_html_escaped(&mut html, &self.web_path);
+        html.push_str("/</title></head><body><h1>Index of ");
+        push_html_escaped(&mut html, &self.web_path);
+        html.push_str("/</h1><ul>\n");
+        for name in dirs.iter() {
+            html.push_str("<li><a href=\"");
+            push_urlencoded_path(&mut html, &self.web_path);
+            html.push('/');
+            push_urlencoded(&mut html, name);
+            html.push_str("/\">");
+            push_html_escaped(&mut html, name);
+            html.push_str("/</a></li>\n");
+        }
+        for name in files.iter() {
+            let Ok(file) = self.file(name) else {
+                continue;
+            };
+            let mut href = String::with_capacity(self.web_path.len() + 1 + name.len());
+            push_urlencoded_path(&mut href, &self.web_path);
+            href.push('/');
+            push_urlencoded(&mut href, name);
+            let busted = match file.cache_busting() {
+                CacheBusting::None => None,
+                CacheBusting::Query(query_key) => crate::traits::cachebust_query_location(
+                    &href,
+                    None,
+                    query_key.as_str(),
+                    file.etag_str(),
+                ),
+                CacheBusting::Suffix(left_sep, len) => crate::traits::cachebust_suffix_location(
+                    &href,
+                    file.etag_str(),
+                    *left_sep,
+                    *len,
+                ),
+            };
+            html.push_str("<li><a href=\"");
+            // the etag is not percent-encoded, so it is escaped like the other text
+            push_html_escaped(&mut html, busted.as_deref().unwrap_or(&href));
+            html.push_str("\">");
+            push_html_escaped(&mut html, name);
+            html.push_str("</a></li>\n");
+        }
+        html.push_str("</ul></body></html>\n");
+        let etag = crate::compute_etag_nonconst(html.as_bytes());
+        SharedHttpFile::new(
+            ByteData::from(html.into_bytes()),
+            StringData::from_static("text/html; charset=utf-8"),
+            StringData::from(etag),
+        )
+    }
+
+    /// Get a file directly in this directory, reading it if needed.
+    fn file(&self, name: &str) -> Result<Arc<StdHttpFile>, ExposeError> {
+        let entry = self.files.read().get(name).cloned();
+        let entry = match entry {
+            Some(entry) => entry,
+            None if self.warmup == DirWarmup::Cold => {
+                if self.source_name(name, false).is_none() {
+                    return Err(ExposeError::NotFound);
+                }
+                self.files
+                    .write()
+                    .entry(Cow::Owned(name.to_owned()))
+                    .or_default()
+                    .clone()
+            }
+            None => return Err(ExposeError::NotFound),
+        };
+        if let Some(file) = entry.read().as_ref() {
+            return Ok(file.clone());
+        }
+        let mut entry = entry.write();
+        if let Some(file) = entry.as_ref() {
+            return Ok(file.clone());
+        }
+        // the entry is keyed by its endpoint, which differs from the name on disk if the filter renames files
+        let source = self.source_name(name, false).ok_or(ExposeError::NotFound)?;
+        let path = Path::new(self.file_path.as_ref()).join(source);
+        let path = path.to_str().ok_or(ExposeError::NotFound)?;
+        // a failed read is not stored, so the file is read again by the next request
+        let file = Arc::new(StdHttpFile::new(path.to_owned())?);
+        *entry = Some(file.clone());
+        Ok(file)
+    }
+
+    /// Get a directory directly in this directory, creating it if needed.
+    fn dir(&self, name: &str) -> Option<Arc<ExposedDirectory>> {
+        if let Some(dir) = self.nested.read().get(name) {
+            return Some(dir.clone());
+        }
+        if self.warmup != DirWarmup::Cold {
+            return None;
+        }
+        let path = Path::new(self.file_path.as_ref()).join(self.source_name(name, true)?);
+        let dir = Self::new_blocking_filtered(
+            DirWarmup::Cold,
+            Cow::Owned(format!("{}/{}", self.web_path, name)),
+            Cow::Owned(path.to_str()?.to_owned()),
+            self.filter.clone(),
+            false,
+        )
+        .ok()?;
+        let mut nested = self.nested.write();
+        Some(
+            nested
+                .entry(Cow::Owned(name.to_owned()))
+                .or_insert_with(|| Arc::new(dir))
+                .clone(),
+        )
+    }
+
+    /// Finds the name on disk of the file, or directory if `dir` is set, directly in this directory
+    /// which the filter exposes as `endpoint`, since a filter such as [`ExposeFilter::strip_prefix`] renames entries.
+    fn source_name(&self, endpoint: &str, dir: bool) -> Option<String> {
+        let exposed_as_endpoint = |name: &str| {
+            if name == "." || name == ".." || name.contains(['/', '\\']) {
+                return false;
+            }
+            let path = Path::new(self.file_path.as_ref()).join(name);
+            if dir {
+                path.is_dir()
+                    && self
+                        .filter
+                        .filter_map_dir(&self.web_path, name)
+                        .is_some_and(|e| e.as_str() == endpoint)
+            } else {
+                path.is_file()
+                    && self
+                        .filter
+                        .filter_map_file(&self.web_path, name)
+                        .is_some_and(|e| e.as_str() == endpoint)
+            }
+        };
+        // most filters keep the name, which is checked first to avoid listing the directory
+        if exposed_as_endpoint(endpoint) {
+            return Some(endpoint.to_owned());
+        }
+        // a filter keeping every name cannot expose another entry as `endpoint`, so a missing file does not list the directory
+        if !self.filter.renames_entries() {
+            return None;
+        }
+        std::fs::read_dir(self.file_path.as_ref())
+            .ok()?
+            .flatten()
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .find(|name| exposed_as_endpoint(name))
+    }
+}
+
+/// Maps the entries of a directory, concurrently if `parallel` is set and the `rayon` feature is enabled.
+fn map_entries<T: Send, R: Send>(
+    parallel: bool,
+    entries: Vec<T>,
+    f: impl Fn(T) -> R + Send + Sync,
+) -> Vec<R> {
+    #[cfg(feature = "rayon")]
+    if parallel {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+        return entries.into_par_iter().map(f).collect();
+    }
+    #[cfg(not(feature = "rayon"))]
+    let _ = parallel;
+    entries.into_iter().map(f).collect()
+}
+
+fn push_html_escaped(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Percent-encodes each segment of a path, keeping the `/` between them.
+fn push_urlencoded_path(out: &mut String, path: &str) {
+    for (i, segment) in path.split('/').enumerate() {
+        if i != 0 {
+            out.push('/');
+        }
+        push_urlencoded(out, segment);
+    }
+}
+
+fn push_urlencoded(out: &mut String, value: &str) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    for &b in value.as_bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push('%');
+            out.push(HEX[(b >> 4) as usize] as char);
+            out.push(HEX[(b & 0xF) as usize] as char);
+        }
+    }
 }
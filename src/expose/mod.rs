@@ -1,2 +1,10 @@
 mod exposed_directory;
 pub use self::exposed_directory::*;
+
+mod directory_router;
+pub use self::directory_router::DirectoryRouter;
+
+#[cfg(feature = "tower")]
+mod directory_service;
+#[cfg(feature = "tower")]
+pub use self::directory_service::{DirectoryFuture, DirectoryService};
@@ -0,0 +1,252 @@
+use core::num::{NonZeroU8, NonZeroUsize};
+use std::io::Write;
+
+use bytedata::ByteData;
+
+use crate::on_demand::{EncodedCell, EncodedVariant, OnDemandEncoding};
+use crate::{CacheBusting, CacheControl, HttpFile, HttpFileResponse};
+
+/// A wrapper which compresses the data of the inner file with gzip when the client accepts it.
+///
+/// The compressed data is computed on the first response which accepts gzip and is then reused.
+/// If the compressed data is not smaller than the original data, or the inner file is already encoded,
+/// the inner file is always served as is.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_http_file, ConstHttpFile, GzipOnDemand, HttpFileResponse};
+/// const FILE: ConstHttpFile = const_http_file!(b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa" as &[u8], "text/plain");
+/// let file = GzipOnDemand::new(FILE);
+/// let request = http::Request::get("/").header("accept-encoding", "gzip, br").body(()).unwrap();
+/// let response = file.respond_borrowed::<bytedata::ByteData>(&request).unwrap();
+/// assert_eq!(response.headers()["content-encoding"], "gzip");
+/// assert_eq!(response.headers()["vary"], "accept-encoding");
+/// ```
+pub struct GzipOnDemand<T> {
+    inner: T,
+    vary: Vec<http::header::HeaderName>,
+    gzip: EncodedCell,
+}
+
+impl<'l, T: HttpFileResponse<'l>> GzipOnDemand<T> {
+    /// Wrap a file which will be compressed on demand.
+    pub fn new(inner: T) -> Self {
+        let vary = crate::on_demand::encoding_vary(inner.vary_headers());
+        GzipOnDemand {
+            inner,
+            vary,
+            gzip: EncodedCell::new(),
+        }
+    }
+
+    /// Returns a reference to the wrapped file.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwraps the file.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns the compressed data and its etag, compressing the data if it has not been done yet.
+    /// Returns `None` if the data should not be compressed.
+    pub fn gzip(&self) -> Option<(&ByteData<'static>, &str)> {
+        crate::on_demand::encoded::<Gzip, _>(&self.gzip, &self.inner)
+    }
+
+    /// Returns the size of the compressed data relative to the original data, such as `0.25` for data compressed to a quarter,
+    /// which can be logged to tune which files are compressed. `encoding` is a coding such as `gzip`.
+    ///
+    /// Returns `None` if `encoding` is not gzip or the data is not compressed, see [`GzipOnDemand::gzip`].
+    pub fn compression_ratio(&self, encoding: &str) -> Option<f32> {
+        crate::on_demand::compression_ratio::<Gzip, _>(&self.gzip, &self.inner, encoding)
+    }
+
+    fn gzip_variant(&self, request: &http::Request<()>) -> Option<EncodedVariant<'_, T>> {
+        if !crate::on_demand::accepts::<Gzip>(request, &[]) {
+            return None;
+        }
+        let (data, etag) = self.gzip()?;
+        Some(EncodedVariant {
+            inner: &self.inner,
+            data,
+            etag,
+            encoding: Gzip::ENCODING,
+        })
+    }
+}
+
+/// The gzip content coding, which is also accepted as `x-gzip`.
+struct Gzip;
+
+impl OnDemandEncoding for Gzip {
+    const ENCODING: &'static str = "gzip";
+    const ACCEPTED: &'static [&'static str] = &["gzip", "x-gzip"];
+    const ETAG_SUFFIX: &'static str = "-gz";
+
+    fn encode(data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut encoder = flate2::write::GzEncoder::new(
+            Vec::with_capacity(data.len() / 2 + 32),
+            flate2::Compression::best(),
+        );
+        encoder.write_all(data)?;
+        encoder.finish()
+    }
+}
+
+impl<'l, T: HttpFile<'l>> HttpFile<'l> for GzipOnDemand<T> {
+    #[inline]
+    fn content_type(&self) -> &str {
+        self.inner.content_type()
+    }
+
+    #[inline]
+    fn etag(&self) -> &str {
+        self.inner.etag()
+    }
+
+    #[inline]
+    fn etag_str(&self) -> &str {
+        self.inner.etag_str()
+    }
+
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        self.inner.content_encoding()
+    }
+
+    fn available_encodings(&self) -> Vec<&str> {
+        let mut encodings = self.inner.available_encodings();
+        if self.gzip().is_some() {
+            // the compressed data is preferred over the encodings of the inner file
+            encodings.insert(0, Gzip::ENCODING);
+        }
+        encodings
+    }
+
+    #[inline]
+    fn cache_busting(&self) -> &CacheBusting {
+        self.inner.cache_busting()
+    }
+
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.inner.last_modified()
+    }
+
+    #[inline]
+    fn cache_control(&self) -> CacheControl {
+        self.inner.cache_control()
+    }
+
+    // the compressed data is generated per response, so ranges over it are not offered
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.inner.data()
+    }
+
+    #[inline]
+    fn into_data(self) -> ByteData<'l> {
+        self.inner.into_data()
+    }
+
+    #[inline]
+    fn clone_data(&self) -> ByteData<'l> {
+        self.inner.clone_data()
+    }
+
+    #[inline]
+    fn data_range(&self, range: core::ops::Range<usize>) -> Option<ByteData<'l>> {
+        self.inner.data_range(range)
+    }
+}
+
+// `respond_guard` and `response_headers` describe the uncompressed representation,
+// while `respond` and `respond_borrowed` select the representation based on the request
+impl<'l, T: HttpFileResponse<'l>> HttpFileResponse<'l> for GzipOnDemand<T> {
+    fn respond<R: From<ByteData<'l>>>(
+        self,
+        request: &http::Request<()>,
+    ) -> Result<http::Response<R>, http::Error> {
+        if let Some(variant) = self.gzip_variant(request) {
+            return variant.respond_borrowed(request);
+        }
+        match self.respond_guard(request) {
+            Ok(response) => response.body(R::from(self.into_data())),
+            Err(res) => res,
+        }
+    }
+
+    fn respond_borrowed<R: From<ByteData<'l>>>(
+        &self,
+        request: &http::Request<()>,
+    ) -> Result<http::Response<R>, http::Error> {
+        if let Some(variant) = self.gzip_variant(request) {
+            return variant.respond_borrowed(request);
+        }
+        match self.respond_guard(request) {
+            Ok(response) => response.body(R::from(self.clone_data())),
+            Err(res) => res,
+        }
+    }
+
+    #[inline]
+    fn etag_header_value(&self) -> http::header::HeaderValue {
+        self.inner.etag_header_value()
+    }
+
+    #[inline]
+    fn vary_headers(&self) -> &[http::header::HeaderName] {
+        &self.vary
+    }
+
+    #[inline]
+    fn network_error_logging(&self) -> Option<&crate::NetworkErrorLogging> {
+        self.inner.network_error_logging()
+    }
+
+    #[inline]
+    fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+        self.inner.security_policy()
+    }
+
+    #[inline]
+    fn retry_after(&self) -> Option<u64> {
+        self.inner.retry_after()
+    }
+
+    #[inline]
+    fn sniff_protection(&self) -> bool {
+        self.inner.sniff_protection()
+    }
+
+    #[inline]
+    fn security_headers(&self, response: http::response::Builder) -> http::response::Builder {
+        self.inner.security_headers(response)
+    }
+
+    #[inline]
+    fn cachebust_uri<R: From<ByteData<'l>>>(
+        &self,
+        old_uri: &http::Uri,
+        query_key: &str,
+    ) -> Option<Result<http::Response<R>, http::Error>> {
+        self.inner.cachebust_uri(old_uri, query_key)
+    }
+
+    #[inline]
+    fn cachebust_suffix<R: From<ByteData<'l>>>(
+        &self,
+        old_uri: &http::Uri,
+        left_sep: Option<NonZeroU8>,
+        len: Option<NonZeroUsize>,
+    ) -> Option<Result<http::Response<R>, http::Error>> {
+        self.inner.cachebust_suffix(old_uri, left_sep, len)
+    }
+}
@@ -0,0 +1,104 @@
+use core::{
+    convert::Infallible,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use ::http_body_1::{Body, Frame, SizeHint};
+use bytedata::ByteData;
+
+/// A response body yielding the data of a file as a single frame, without copying the data.
+///
+/// This can be used as the body type of [`HttpFileResponse::respond`](crate::HttpFileResponse::respond) to get a body implementing the
+/// `Body` trait of `http-body` 1, as used by `hyper` 1 and `axum` 0.7.
+/// The responses of this crate are built with `http` 0.2 while those servers use `http` 1, so the status and headers of the response
+/// have to be copied into an `http` 1 response, which can then carry the body as is.
+/// The body reports its exact length as the size hint, so servers send a `Content-Length` header instead of chunked data.
+#[derive(Clone, Debug)]
+pub struct HttpFileBody<'a> {
+    data: Option<ByteData<'a>>,
+}
+
+impl<'a> HttpFileBody<'a> {
+    /// Create a new [`HttpFileBody`] from the data of a file.
+    pub const fn new(data: ByteData<'a>) -> Self {
+        HttpFileBody { data: Some(data) }
+    }
+
+    /// Number of bytes remaining in the body.
+    pub fn len(&self) -> usize {
+        self.data.as_ref().map_or(0, |data| data.len())
+    }
+
+    /// Checks if there are no bytes remaining in the body.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> From<ByteData<'a>> for HttpFileBody<'a> {
+    #[inline]
+    fn from(data: ByteData<'a>) -> Self {
+        HttpFileBody::new(data)
+    }
+}
+
+impl<'a> Body for HttpFileBody<'a> {
+    type Data = HttpFileChunk<'a>;
+    type Error = Infallible;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let data = self.get_mut().data.take();
+        Poll::Ready(
+            data.filter(|data| !data.is_empty())
+                .map(|data| Ok(Frame::data(HttpFileChunk { data }))),
+        )
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
+    // the exact length lets servers send a `Content-Length` header instead of using chunked transfer encoding,
+    // which also holds for the partial data of a range response
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.len() as u64)
+    }
+}
+
+/// A frame of a [`HttpFileBody`], which refers to the data of the file instead of copying it.
+#[derive(Clone, Debug)]
+pub struct HttpFileChunk<'a> {
+    data: ByteData<'a>,
+}
+
+impl<'a> HttpFileChunk<'a> {
+    /// Unwraps the remaining data.
+    pub fn into_data(self) -> ByteData<'a> {
+        self.data
+    }
+}
+
+impl bytes_1::Buf for HttpFileChunk<'_> {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        let len = self.data.len();
+        assert!(cnt <= len, "cannot advance past the end of the chunk");
+        let data = core::mem::replace(&mut self.data, ByteData::from_static(&[]));
+        self.data = data.sliced(cnt..len);
+    }
+}
@@ -0,0 +1,2 @@
+mod http_file_body;
+pub use http_file_body::*;
@@ -0,0 +1,161 @@
+const DAYS: [&[u8; 3]; 7] = [b"Sun", b"Mon", b"Tue", b"Wed", b"Thu", b"Fri", b"Sat"];
+const MONTHS: [&[u8; 3]; 12] = [
+    b"Jan", b"Feb", b"Mar", b"Apr", b"May", b"Jun", b"Jul", b"Aug", b"Sep", b"Oct", b"Nov", b"Dec",
+];
+
+/// Formats seconds since the Unix epoch as an HTTP date, such as `Sun, 06 Nov 1994 08:49:37 GMT`,
+/// as used in the `Last-Modified` header.
+///
+/// Example:
+/// ```
+/// # use static_http_file::format_http_date;
+/// const DATE: [u8; 29] = format_http_date(784111777);
+/// assert_eq!(&DATE, b"Sun, 06 Nov 1994 08:49:37 GMT");
+/// ```
+pub const fn format_http_date(seconds: u64) -> [u8; 29] {
+    let days = seconds / 86400;
+    let time = seconds % 86400;
+    let (year, month, day) = civil_from_days(days);
+    let mut date = *b"Thu, 01 Jan 1970 00:00:00 GMT";
+    let weekday = DAYS[((days + 4) % 7) as usize];
+    date[0] = weekday[0];
+    date[1] = weekday[1];
+    date[2] = weekday[2];
+    date = write_digits(date, 5, day, 2);
+    let month = MONTHS[(month - 1) as usize];
+    date[8] = month[0];
+    date[9] = month[1];
+    date[10] = month[2];
+    date = write_digits(date, 12, year, 4);
+    date = write_digits(date, 17, time / 3600, 2);
+    date = write_digits(date, 20, time / 60 % 60, 2);
+    date = write_digits(date, 23, time % 60, 2);
+    date
+}
+
+/// Parses an HTTP date in the preferred format, such as `Sun, 06 Nov 1994 08:49:37 GMT`, into seconds since the Unix epoch.
+///
+/// Returns `None` if the date is not in the preferred format, is invalid, or is before the Unix epoch.
+/// The obsolete RFC 850 and asctime formats are not supported.
+///
+/// Example:
+/// ```
+/// # use static_http_file::parse_http_date;
+/// const SECONDS: Option<u64> = parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT");
+/// assert_eq!(SECONDS, Some(784111777));
+/// assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+/// ```
+pub const fn parse_http_date(date: &str) -> Option<u64> {
+    let date = date.as_bytes();
+    if date.len() != 29
+        || date[3] != b','
+        || date[4] != b' '
+        || date[7] != b' '
+        || date[11] != b' '
+        || date[16] != b' '
+        || date[19] != b':'
+        || date[22] != b':'
+        || date[25] != b' '
+        || date[26] != b'G'
+        || date[27] != b'M'
+        || date[28] != b'T'
+    {
+        return None;
+    }
+    let mut month = 0;
+    while month < MONTHS.len() {
+        let name = MONTHS[month];
+        if date[8] == name[0] && date[9] == name[1] && date[10] == name[2] {
+            break;
+        }
+        month += 1;
+    }
+    if month == MONTHS.len() {
+        return None;
+    }
+    let (Some(day), Some(year), Some(hour), Some(minute), Some(second)) = (
+        read_digits(date, 5, 2),
+        read_digits(date, 12, 4),
+        read_digits(date, 17, 2),
+        read_digits(date, 20, 2),
+        read_digits(date, 23, 2),
+    ) else {
+        return None;
+    };
+    let month = month as u64 + 1;
+    if year < 1970 || day == 0 || day > days_in_month(year, month) || hour > 23 || minute > 59 {
+        return None;
+    }
+    // a leap second is treated as the last second of the minute
+    let second = if second > 60 {
+        return None;
+    } else if second == 60 {
+        59
+    } else {
+        second
+    };
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+const fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Counts the days since the Unix epoch of a date in the proleptic Gregorian calendar, which must not be before the epoch.
+const fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    // the year is shifted to start in March so that the leap day is the last day of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Converts days since the Unix epoch to a year, month and day, which is the inverse of [`days_from_civil`].
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+const fn write_digits(mut trg: [u8; 29], offset: usize, mut value: u64, len: usize) -> [u8; 29] {
+    let mut i = len;
+    while i > 0 {
+        i -= 1;
+        trg[offset + i] = b'0' + (value % 10) as u8;
+        value /= 10;
+    }
+    trg
+}
+
+const fn read_digits(src: &[u8], offset: usize, len: usize) -> Option<u64> {
+    let mut value = 0;
+    let mut i = 0;
+    while i < len {
+        let b = src[offset + i];
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        value = value * 10 + (b - b'0') as u64;
+        i += 1;
+    }
+    Some(value)
+}
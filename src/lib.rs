@@ -4,18 +4,62 @@ extern crate alloc;
 
 mod const_mime;
 pub use const_mime::*;
+mod mime_type;
+pub use mime_type::{MimeType, UnknownMimeType};
 mod traits;
 pub use traits::*;
 
 mod const_http_file;
 pub use const_http_file::ConstHttpFile;
 
+mod const_directory;
+pub use const_directory::ConstDirectory;
+
 mod cachebusted_http_file;
-pub use cachebusted_http_file::QueryCacheBustedHttpFile;
+pub use cachebusted_http_file::{QueryCacheBustedHttpFile, SuffixCacheBustedHttpFile};
+
+mod shared_http_file;
+pub use shared_http_file::SharedHttpFile;
+
+mod manifest;
+pub use manifest::{ManifestEntry, ManifestEntryTooLarge};
+
+mod reporting;
+pub use reporting::NetworkErrorLogging;
+
+mod cache_control;
+pub use cache_control::CacheControl;
+
+mod decision;
+pub use decision::{header_pairs, ResponseDecision, ALLOWED_METHODS};
+
+mod negotiation;
+pub use negotiation::{select_encoding, select_media_type};
+
+mod negotiated_http_file;
+pub use negotiated_http_file::NegotiatedHttpFile;
+
+mod range;
+pub use range::{parse_ranges, RangeParseResult};
+
+mod security_headers;
+pub use security_headers::SecurityHeaders;
+
+mod with_security_headers;
+pub use with_security_headers::WithSecurityHeaders;
+
+mod urldecode;
+pub use urldecode::*;
 
 mod const_etag;
 pub use const_etag::*;
 
+mod const_gzip;
+pub use const_gzip::{const_gzip, const_gzip_len};
+
+mod http_date;
+pub use http_date::{format_http_date, parse_http_date};
+
 mod const_b64;
 pub use const_b64::*;
 
@@ -34,5 +78,23 @@ mod expose;
 #[cfg(feature = "expose")]
 pub use self::expose::*;
 
+#[cfg(any(feature = "flate2", feature = "brotli"))]
+mod on_demand;
+
+#[cfg(feature = "flate2")]
+mod gzip_on_demand;
+#[cfg(feature = "flate2")]
+pub use gzip_on_demand::GzipOnDemand;
+
+#[cfg(feature = "brotli")]
+mod brotli_on_demand;
+#[cfg(feature = "brotli")]
+pub use brotli_on_demand::BrotliOnDemand;
+
+#[cfg(feature = "http_body_1")]
+mod http_body_1;
+#[cfg(feature = "http_body_1")]
+pub use self::http_body_1::*;
+
 #[cfg(test)]
 mod test;
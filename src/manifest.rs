@@ -0,0 +1,143 @@
+use alloc::vec::Vec;
+use bytedata::StringData;
+
+use crate::{HttpFile, SharedHttpFile};
+
+/// Metadata of a file as stored in a binary manifest, without the file contents.
+///
+/// Each entry is stored as a record prefixed by its length as a little-endian `u32`.
+/// Strings are prefixed by their length as a little-endian `u32`, the size is a little-endian `u64`,
+/// and the encodings are prefixed by their count as a `u8`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManifestEntry<'a> {
+    pub path: StringData<'a>,
+    pub mime: StringData<'a>,
+    pub etag: StringData<'a>,
+    pub size: u64,
+    pub encodings: Vec<StringData<'a>>,
+}
+
+impl<'a> ManifestEntry<'a> {
+    /// Appends the entry as a length-prefixed record to `out`.
+    ///
+    /// Returns [`ManifestEntryTooLarge`] and leaves `out` unchanged if the entry has more than 255 encodings,
+    /// or if a string or the whole record is longer than `u32::MAX` bytes.
+    pub fn to_manifest_bytes(&self, out: &mut Vec<u8>) -> Result<(), ManifestEntryTooLarge> {
+        let start = out.len();
+        let result = self.write_record(out, start);
+        if result.is_err() {
+            out.truncate(start);
+        }
+        result
+    }
+
+    fn write_record(&self, out: &mut Vec<u8>, start: usize) -> Result<(), ManifestEntryTooLarge> {
+        let count = u8::try_from(self.encodings.len()).map_err(|_| ManifestEntryTooLarge)?;
+        out.extend_from_slice(&[0; 4]);
+        write_str(out, self.path.as_str())?;
+        write_str(out, self.mime.as_str())?;
+        write_str(out, self.etag.as_str())?;
+        out.extend_from_slice(&self.size.to_le_bytes());
+        out.push(count);
+        for encoding in &self.encodings {
+            write_str(out, encoding.as_str())?;
+        }
+        let len = u32::try_from(out.len() - start - 4).map_err(|_| ManifestEntryTooLarge)?;
+        out[start..start + 4].copy_from_slice(&len.to_le_bytes());
+        Ok(())
+    }
+
+    /// Reads the first record of a manifest. The strings of the entry are borrowed from `input`.
+    ///
+    /// Returns the entry and the remaining input, or `None` if the record is malformed.
+    pub fn from_manifest_bytes(input: &'a [u8]) -> Option<(Self, &'a [u8])> {
+        let mut input = input;
+        let len = u32::from_le_bytes(take(&mut input, 4)?.try_into().ok()?) as usize;
+        let mut record = take(&mut input, len)?;
+        let path = take_str(&mut record)?;
+        let mime = take_str(&mut record)?;
+        let etag = take_str(&mut record)?;
+        let size = u64::from_le_bytes(take(&mut record, 8)?.try_into().ok()?);
+        let count = take(&mut record, 1)?[0] as usize;
+        let mut encodings = Vec::with_capacity(count);
+        for _ in 0..count {
+            encodings.push(StringData::from_borrowed(take_str(&mut record)?));
+        }
+        if !record.is_empty() {
+            return None;
+        }
+        let entry = ManifestEntry {
+            path: StringData::from_borrowed(path),
+            mime: StringData::from_borrowed(mime),
+            etag: StringData::from_borrowed(etag),
+            size,
+            encodings,
+        };
+        Some((entry, input))
+    }
+
+    /// Reads all records of a manifest, stopping at the first malformed record.
+    pub fn iter_manifest(input: &'a [u8]) -> impl Iterator<Item = ManifestEntry<'a>> + 'a {
+        let mut input = input;
+        core::iter::from_fn(move || {
+            let (entry, rest) = ManifestEntry::from_manifest_bytes(input)?;
+            input = rest;
+            Some(entry)
+        })
+    }
+}
+
+/// The error of a [`ManifestEntry`] which cannot be represented in the manifest format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ManifestEntryTooLarge;
+
+impl core::fmt::Display for ManifestEntryTooLarge {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("manifest entry is too large")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ManifestEntryTooLarge {}
+
+impl<'a> SharedHttpFile<'a> {
+    /// Appends the metadata of the file as a manifest record to `out`. The web path is taken from the `file` field.
+    ///
+    /// Returns [`ManifestEntryTooLarge`] and leaves `out` unchanged if a string is longer than `u32::MAX` bytes.
+    pub fn to_manifest_bytes(&self, out: &mut Vec<u8>) -> Result<(), ManifestEntryTooLarge> {
+        ManifestEntry::from(self).to_manifest_bytes(out)
+    }
+}
+
+impl<'s, 'a> From<&'s SharedHttpFile<'a>> for ManifestEntry<'s> {
+    fn from(file: &'s SharedHttpFile<'a>) -> Self {
+        ManifestEntry {
+            path: StringData::from_borrowed(file.file.as_ref().map_or("", |f| f.as_str())),
+            mime: StringData::from_borrowed(file.content_type()),
+            etag: StringData::from_borrowed(file.etag()),
+            size: file.data().len() as u64,
+            encodings: Vec::new(),
+        }
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) -> Result<(), ManifestEntryTooLarge> {
+    let len = u32::try_from(value.len()).map_err(|_| ManifestEntryTooLarge)?;
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if input.len() < len {
+        return None;
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Some(head)
+}
+
+fn take_str<'a>(input: &mut &'a [u8]) -> Option<&'a str> {
+    let len = u32::from_le_bytes(take(input, 4)?.try_into().ok()?) as usize;
+    core::str::from_utf8(take(input, len)?).ok()
+}
@@ -0,0 +1,214 @@
+/// Defines [`MimeType`] from a single table of variants and mime types,
+/// so the string representation and the lookup cannot drift apart.
+macro_rules! mime_types {
+    ($($variant:ident => $mime:literal,)*) => {
+        /// A mime type known by the detection functions of this crate.
+        ///
+        /// Use [`MimeType::as_str`] to get the string representation, or [`MimeType::from_static`] to parse one.
+        /// Types which are not part of the detection tables are represented as [`MimeType::Custom`].
+        ///
+        /// Example:
+        /// ```
+        /// # use static_http_file::{detect_mime_type_enum, MimeType};
+        /// match detect_mime_type_enum("image.png", &[]) {
+        ///     Some(MimeType::ImagePng) => {}
+        ///     _ => unreachable!(),
+        /// }
+        /// assert_eq!(MimeType::from_static("text/css"), MimeType::TextCss);
+        /// assert_eq!(MimeType::TextCss.as_str(), "text/css");
+        /// ```
+        #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+        #[non_exhaustive]
+        pub enum MimeType {
+            $(
+                #[doc = concat!("`", $mime, "`")]
+                $variant,
+            )*
+            /// A mime type not known by this crate.
+            Custom(&'static str),
+        }
+
+        impl MimeType {
+            /// Every known mime type, which excludes [`MimeType::Custom`].
+            const KNOWN: &'static [MimeType] = &[$(MimeType::$variant,)*];
+
+            /// The string representation of the mime type.
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $(MimeType::$variant => $mime,)*
+                    MimeType::Custom(mime) => mime,
+                }
+            }
+        }
+    };
+}
+
+mime_types! {
+    ApplicationAtomXml => "application/atom+xml",
+    ApplicationGzip => "application/gzip",
+    ApplicationJavaArchive => "application/java-archive",
+    ApplicationJavascript => "application/javascript",
+    ApplicationJson => "application/json",
+    ApplicationLdJson => "application/ld+json",
+    ApplicationManifestJson => "application/manifest+json",
+    ApplicationMsword => "application/msword",
+    ApplicationOctetStream => "application/octet-stream",
+    ApplicationOgg => "application/ogg",
+    ApplicationPdf => "application/pdf",
+    ApplicationRssXml => "application/rss+xml",
+    ApplicationRtf => "application/rtf",
+    ApplicationToml => "application/toml",
+    ApplicationVndAndroidPackageArchive => "application/vnd.android.package-archive",
+    ApplicationVndAppleInstallerXml => "application/vnd.apple.installer+xml",
+    ApplicationVndMsExcel => "application/vnd.ms-excel",
+    ApplicationVndMsFontobject => "application/vnd.ms-fontobject",
+    ApplicationVndMsPowerpoint => "application/vnd.ms-powerpoint",
+    ApplicationVndOasisOpendocumentPresentation => "application/vnd.oasis.opendocument.presentation",
+    ApplicationVndOasisOpendocumentSpreadsheet => "application/vnd.oasis.opendocument.spreadsheet",
+    ApplicationVndOasisOpendocumentText => "application/vnd.oasis.opendocument.text",
+    ApplicationVndOpenxmlformatsOfficedocumentPresentationmlPresentation => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ApplicationVndOpenxmlformatsOfficedocumentSpreadsheetmlSheet => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ApplicationVndOpenxmlformatsOfficedocumentWordprocessingmlDocument => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ApplicationVndRar => "application/vnd.rar",
+    ApplicationVndVisio => "application/vnd.visio",
+    ApplicationWasm => "application/wasm",
+    ApplicationX7zCompressed => "application/x-7z-compressed",
+    ApplicationXBat => "application/x-bat",
+    ApplicationXBzip2 => "application/x-bzip2",
+    ApplicationXCmd => "application/x-cmd",
+    ApplicationXGzip => "application/x-gzip",
+    ApplicationXMpegurl => "application/x-mpegURL",
+    ApplicationXSh => "application/x-sh",
+    ApplicationXTar => "application/x-tar",
+    ApplicationXXz => "application/x-xz",
+    ApplicationXYaml => "application/x-yaml",
+    ApplicationXhtmlXml => "application/xhtml+xml",
+    ApplicationXml => "application/xml",
+    ApplicationZip => "application/zip",
+    AudioAac => "audio/aac",
+    AudioAiff => "audio/aiff",
+    AudioFlac => "audio/flac",
+    AudioMidi => "audio/midi",
+    AudioMp3 => "audio/mp3",
+    AudioMp4 => "audio/mp4",
+    AudioMpeg => "audio/mpeg",
+    AudioOgg => "audio/ogg",
+    AudioOpus => "audio/opus",
+    AudioWav => "audio/wav",
+    AudioWebm => "audio/webm",
+    FontOtf => "font/otf",
+    FontTtf => "font/ttf",
+    FontWoff => "font/woff",
+    FontWoff2 => "font/woff2",
+    ImageApng => "image/apng",
+    ImageAvif => "image/avif",
+    ImageBmp => "image/bmp",
+    ImageGif => "image/gif",
+    ImageHeic => "image/heic",
+    ImageJpeg => "image/jpeg",
+    ImagePng => "image/png",
+    ImageSvgXml => "image/svg+xml",
+    ImageTiff => "image/tiff",
+    ImageVndMicrosoftIcon => "image/vnd.microsoft.icon",
+    ImageWebp => "image/webp",
+    ImageXIcns => "image/x-icns",
+    ImageXXcf => "image/x-xcf",
+    TextCalendar => "text/calendar",
+    TextCss => "text/css",
+    TextCsv => "text/csv",
+    TextHtml => "text/html",
+    TextMarkdown => "text/markdown",
+    TextPlain => "text/plain",
+    TextXml => "text/xml",
+    VideoMp4 => "video/mp4",
+    VideoMpeg => "video/mpeg",
+    VideoQuicktime => "video/quicktime",
+    VideoWebm => "video/webm",
+    VideoXMatroska => "video/x-matroska",
+    VideoXMsvideo => "video/x-msvideo",
+}
+
+impl MimeType {
+    /// Get the mime type matching a string, falling back to [`MimeType::Custom`] for unknown types.
+    pub const fn from_static(mime: &'static str) -> Self {
+        match MimeType::lookup(mime) {
+            MimeType::Custom(_) => MimeType::Custom(mime),
+            known => known,
+        }
+    }
+
+    /// Looks up a known mime type, where unknown types are returned as an empty [`MimeType::Custom`].
+    const fn lookup(mime: &str) -> Self {
+        let mut i = 0;
+        while i < MimeType::KNOWN.len() {
+            let known = MimeType::KNOWN[i];
+            if crate::const_mime::const_str_eq(known.as_str(), mime) {
+                return known;
+            }
+            i += 1;
+        }
+        MimeType::Custom("")
+    }
+}
+
+impl core::fmt::Display for MimeType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for MimeType {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for MimeType {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for MimeType {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// The error returned when parsing a mime type which is not known by this crate.
+///
+/// Unknown types can still be represented by [`MimeType::from_static`] if the string is static.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct UnknownMimeType;
+
+impl core::fmt::Display for UnknownMimeType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("unknown mime type")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownMimeType {}
+
+impl core::str::FromStr for MimeType {
+    type Err = UnknownMimeType;
+
+    /// Parses one of the known mime types.
+    /// As [`MimeType::Custom`] requires a static string, other types result in [`UnknownMimeType`].
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{MimeType, UnknownMimeType};
+    /// assert_eq!("image/png".parse(), Ok(MimeType::ImagePng));
+    /// assert_eq!("image/x-unknown".parse::<MimeType>(), Err(UnknownMimeType));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match MimeType::lookup(s) {
+            MimeType::Custom(_) => Err(UnknownMimeType),
+            known => Ok(known),
+        }
+    }
+}
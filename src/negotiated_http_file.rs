@@ -0,0 +1,323 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use bytedata::ByteData;
+
+use crate::{CacheBusting, CacheControl, HttpFile, HttpFileResponse};
+
+/// A file with multiple variants of different content types, such as an HTML and an XHTML version of a page,
+/// where the variant is selected by the `Accept` header of the request.
+///
+/// The variant with the highest quality according to the `Accept` header is used, where ties are resolved by the order in which
+/// the variants were added. If no variant is acceptable, or the request has no `Accept` header, the first variant is used.
+/// With [`NegotiatedHttpFile::with_save_data`], the smallest acceptable variant is used for requests with `Save-Data: on`.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_http_file, ConstHttpFile, HttpFileResponse, NegotiatedHttpFile};
+/// const HTML: ConstHttpFile = const_http_file!(b"<!DOCTYPE html>" as &[u8], "text/html");
+/// const XHTML: ConstHttpFile = const_http_file!(b"<?xml version=\"1.0\"?>" as &[u8], "application/xhtml+xml");
+/// let page = NegotiatedHttpFile::new(HTML).with_variant(XHTML);
+/// let request = http::Request::get("/page")
+///     .header("accept", "application/xhtml+xml, text/html;q=0.9")
+///     .body(())
+///     .unwrap();
+/// let response = page.respond_borrowed::<bytedata::ByteData>(&request).unwrap();
+/// assert_eq!(response.headers()["content-type"], "application/xhtml+xml");
+/// assert_eq!(response.headers()["vary"], "accept");
+/// ```
+pub struct NegotiatedHttpFile<T> {
+    variants: Vec<T>,
+    vary: Vec<http::header::HeaderName>,
+    save_data: bool,
+}
+
+/// The `Save-Data` client hint, which a client sends as `Save-Data: on` to ask for smaller responses.
+const SAVE_DATA: &str = "save-data";
+
+impl<'l, T: HttpFileResponse<'l>> NegotiatedHttpFile<T> {
+    /// Create a negotiated file with a first variant, which is used when no other variant is acceptable.
+    pub fn new(first: T) -> Self {
+        let mut file = NegotiatedHttpFile {
+            variants: Vec::new(),
+            vary: vec![http::header::ACCEPT],
+            save_data: false,
+        };
+        file.add_variant(first);
+        file
+    }
+
+    /// Add a variant, which is preferred less than the variants added before it.
+    pub fn with_variant(mut self, variant: T) -> Self {
+        self.add_variant(variant);
+        self
+    }
+
+    /// Prefer the smallest acceptable variant, such as a low quality image, for requests with `Save-Data: on`,
+    /// which adds `Save-Data` to the `Vary` header. Requests without the header select a variant as usual.
+    pub fn with_save_data(mut self, save_data: bool) -> Self {
+        self.save_data = save_data;
+        if !save_data {
+            self.vary.retain(|name| name != SAVE_DATA);
+        } else if !self.vary.iter().any(|name| name == SAVE_DATA) {
+            self.vary
+                .push(http::header::HeaderName::from_static(SAVE_DATA));
+        }
+        self
+    }
+
+    fn add_variant(&mut self, variant: T) {
+        for name in variant.vary_headers() {
+            if !self.vary.contains(name) {
+                self.vary.push(name.clone());
+            }
+        }
+        self.variants.push(variant);
+    }
+
+    /// Returns the variants in order of preference.
+    pub fn variants(&self) -> &[T] {
+        &self.variants
+    }
+
+    /// Unwraps the variants.
+    pub fn into_variants(self) -> Vec<T> {
+        self.variants
+    }
+
+    /// Selects the variant for the value of an `Accept` header, falling back to the first variant.
+    pub fn select(&self, accept: &str) -> &T {
+        &self.variants[self.select_for(Some(accept), false)]
+    }
+
+    /// Selects the index of the variant for the value of an `Accept` header, where every variant is acceptable without the header,
+    /// preferring the smallest acceptable variant if `save_data` is set.
+    fn select_for(&self, accept: Option<&str>, save_data: bool) -> usize {
+        let mut best: Option<(usize, u16)> = None;
+        for (i, variant) in self.variants.iter().enumerate() {
+            let q = accept.map_or(1000, |accept| {
+                crate::negotiation::media_type_quality(accept, variant.content_type())
+            });
+            let better = match best {
+                None => q > 0,
+                Some((best, _)) if save_data => {
+                    q > 0 && variant.data().len() < self.variants[best].data().len()
+                }
+                Some((_, best_q)) => q > best_q,
+            };
+            if better {
+                best = Some((i, q));
+            }
+        }
+        best.map_or(0, |(i, _)| i)
+    }
+
+    /// Selects the index of the variant for the `Accept` and `Save-Data` headers of a request.
+    fn select_request(&self, request: &http::Request<()>) -> usize {
+        let mut accept = String::new();
+        for value in request.headers().get_all(http::header::ACCEPT) {
+            if let Ok(value) = value.to_str() {
+                if !accept.is_empty() {
+                    accept.push(',');
+                }
+                accept.push_str(value);
+            }
+        }
+        let save_data = self.save_data
+            && request
+                .headers()
+                .get_all(SAVE_DATA)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .any(|value| value.trim().eq_ignore_ascii_case("on"));
+        let accept = if accept.is_empty() {
+            None
+        } else {
+            Some(accept.as_str())
+        };
+        self.select_for(accept, save_data)
+    }
+
+    /// Replaces the `Vary` header of the response of a variant, such that it also lists the headers used to select the variant.
+    fn add_vary(&self, headers: &mut http::HeaderMap) {
+        let mut value = String::new();
+        for name in self.vary.iter() {
+            if !value.is_empty() {
+                value.push_str(", ");
+            }
+            value.push_str(name.as_str());
+        }
+        for existing in headers.get_all(http::header::VARY) {
+            let Ok(existing) = existing.to_str() else {
+                continue;
+            };
+            for name in existing.split(',').map(str::trim) {
+                if name.is_empty()
+                    || value
+                        .split(", ")
+                        .any(|listed| listed.eq_ignore_ascii_case(name))
+                {
+                    continue;
+                }
+                value.push_str(", ");
+                value.push_str(name);
+            }
+        }
+        if let Ok(value) = http::header::HeaderValue::from_str(&value) {
+            headers.insert(http::header::VARY, value);
+        }
+    }
+}
+
+impl<'l, T: HttpFile<'l>> HttpFile<'l> for NegotiatedHttpFile<T> {
+    #[inline]
+    fn content_type(&self) -> &str {
+        self.variants[0].content_type()
+    }
+
+    #[inline]
+    fn etag(&self) -> &str {
+        self.variants[0].etag()
+    }
+
+    #[inline]
+    fn etag_str(&self) -> &str {
+        self.variants[0].etag_str()
+    }
+
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        self.variants[0].content_encoding()
+    }
+
+    /// Returns the encodings of all variants, in the order of the variants.
+    fn available_encodings(&self) -> Vec<&str> {
+        let mut encodings = Vec::new();
+        for variant in self.variants.iter() {
+            for encoding in variant.available_encodings() {
+                if !encodings.contains(&encoding) {
+                    encodings.push(encoding);
+                }
+            }
+        }
+        encodings
+    }
+
+    #[inline]
+    fn cache_busting(&self) -> &CacheBusting {
+        self.variants[0].cache_busting()
+    }
+
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.variants[0].last_modified()
+    }
+
+    #[inline]
+    fn cache_control(&self) -> CacheControl {
+        self.variants[0].cache_control()
+    }
+
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        self.variants[0].accepts_ranges()
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.variants[0].data()
+    }
+
+    #[inline]
+    fn into_data(self) -> ByteData<'l> {
+        self.variants.into_iter().next().unwrap().into_data()
+    }
+
+    #[inline]
+    fn clone_data(&self) -> ByteData<'l> {
+        self.variants[0].clone_data()
+    }
+
+    #[inline]
+    fn data_range(&self, range: core::ops::Range<usize>) -> Option<ByteData<'l>> {
+        self.variants[0].data_range(range)
+    }
+}
+
+// `response_headers` describes the first variant, while `respond_guard`, `respond` and `respond_borrowed`
+// select the variant based on the request and delegate to it
+impl<'l, T: HttpFileResponse<'l>> HttpFileResponse<'l> for NegotiatedHttpFile<T> {
+    /// Evaluates the request against the selected variant, so a caller sending the data of the guard must use the data of
+    /// the same variant, as selected by [`NegotiatedHttpFile::select`], or use [`HttpFileResponse::respond_borrowed`] instead.
+    fn respond_guard<R: From<ByteData<'l>>>(
+        &self,
+        request: &http::Request<()>,
+    ) -> Result<http::response::Builder, Result<http::Response<R>, http::Error>> {
+        match self.variants[self.select_request(request)].respond_guard(request) {
+            Ok(mut response) => {
+                if let Some(headers) = response.headers_mut() {
+                    self.add_vary(headers);
+                }
+                Ok(response)
+            }
+            Err(Ok(mut response)) => {
+                self.add_vary(response.headers_mut());
+                Err(Ok(response))
+            }
+            Err(Err(err)) => Err(Err(err)),
+        }
+    }
+
+    fn respond<R: From<ByteData<'l>>>(
+        mut self,
+        request: &http::Request<()>,
+    ) -> Result<http::Response<R>, http::Error> {
+        let index = self.select_request(request);
+        let mut response = self.variants.swap_remove(index).respond(request)?;
+        self.add_vary(response.headers_mut());
+        Ok(response)
+    }
+
+    fn respond_borrowed<R: From<ByteData<'l>>>(
+        &self,
+        request: &http::Request<()>,
+    ) -> Result<http::Response<R>, http::Error> {
+        let mut response = self.variants[self.select_request(request)].respond_borrowed(request)?;
+        self.add_vary(response.headers_mut());
+        Ok(response)
+    }
+
+    #[inline]
+    fn etag_header_value(&self) -> http::header::HeaderValue {
+        self.variants[0].etag_header_value()
+    }
+
+    #[inline]
+    fn vary_headers(&self) -> &[http::header::HeaderName] {
+        &self.vary
+    }
+
+    #[inline]
+    fn network_error_logging(&self) -> Option<&crate::NetworkErrorLogging> {
+        self.variants[0].network_error_logging()
+    }
+
+    #[inline]
+    fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+        self.variants[0].security_policy()
+    }
+
+    #[inline]
+    fn retry_after(&self) -> Option<u64> {
+        self.variants[0].retry_after()
+    }
+
+    #[inline]
+    fn sniff_protection(&self) -> bool {
+        self.variants[0].sniff_protection()
+    }
+
+    #[inline]
+    fn security_headers(&self, response: http::response::Builder) -> http::response::Builder {
+        self.variants[0].security_headers(response)
+    }
+}
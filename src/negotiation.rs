@@ -0,0 +1,152 @@
+/// Parses a quality value, such as `0.8`, into thousandths.
+/// Returns `None` if the value is malformed.
+fn parse_qvalue(value: &str) -> Option<u16> {
+    let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut q = match int {
+        "0" => 0,
+        "1" => 1000,
+        _ => return None,
+    };
+    for (i, digit) in frac.bytes().enumerate() {
+        q += u16::from(digit - b'0') * [100, 10, 1][i];
+    }
+    if q > 1000 {
+        return None;
+    }
+    Some(q)
+}
+
+/// Selects the content coding to respond with, based on the value of an `Accept-Encoding` header.
+///
+/// `available` lists the codings the response can be sent with in order of preference, such as `["br", "gzip", "identity"]`,
+/// where `identity` stands for the data without any coding and should be included if the uncompressed data can be sent.
+/// The coding with the highest quality value is selected, where ties are resolved by the order of `available`.
+///
+/// A coding not mentioned in the header gets the quality of `*` if present.
+/// Otherwise `identity` is always acceptable, unless it is excluded by `identity;q=0` or `*;q=0`, while other codings are not.
+/// Codings are compared case-insensitively and entries with malformed quality values are ignored.
+///
+/// Returns `None` if the client does not accept any of the available codings, which should be answered with `406 Not Acceptable`.
+///
+/// Example:
+/// ```
+/// # use static_http_file::select_encoding;
+/// let available = ["br", "gzip", "identity"];
+/// assert_eq!(select_encoding("gzip, br;q=0.9", &available), Some("gzip"));
+/// assert_eq!(select_encoding("gzip, br", &available), Some("br"));
+/// assert_eq!(select_encoding("deflate", &available), Some("identity"));
+/// assert_eq!(select_encoding("deflate, identity;q=0", &available), None);
+/// ```
+pub fn select_encoding<'e>(header: &str, available: &[&'e str]) -> Option<&'e str> {
+    let mut best: Option<(&'e str, u16)> = None;
+    for &coding in available {
+        let mut explicit = None;
+        let mut wildcard = None;
+        for entry in header.split(',') {
+            let mut params = entry.split(';');
+            let name = params.next().unwrap_or_default().trim();
+            if name.is_empty() {
+                continue;
+            }
+            let mut q = Some(1000);
+            for param in params {
+                if let Some((key, value)) = param.split_once('=') {
+                    if key.trim().eq_ignore_ascii_case("q") {
+                        q = parse_qvalue(value.trim());
+                    }
+                }
+            }
+            let Some(q) = q else {
+                continue;
+            };
+            if name.eq_ignore_ascii_case(coding) {
+                explicit = Some(explicit.map_or(q, |e: u16| e.max(q)));
+            } else if name == "*" {
+                wildcard = Some(q);
+            }
+        }
+        let q = match (explicit, wildcard) {
+            (Some(q), _) | (None, Some(q)) => q,
+            (None, None) if coding.eq_ignore_ascii_case("identity") => 1000,
+            (None, None) => 0,
+        };
+        if q > 0 && best.map_or(true, |(_, best_q)| q > best_q) {
+            best = Some((coding, q));
+        }
+    }
+    best.map(|(coding, _)| coding)
+}
+
+/// Returns the quality of a media type, such as `text/html; charset=utf-8`, according to the value of an `Accept` header,
+/// in thousandths. The quality is taken from the most specific matching media range, and is 0 if no range matches.
+pub(crate) fn media_type_quality(header: &str, mime: &str) -> u16 {
+    let essence = mime.split(';').next().unwrap_or_default().trim();
+    let Some((ty, subty)) = essence.split_once('/') else {
+        return 0;
+    };
+    // the specificity and quality of the best matching range
+    let mut best: Option<(u8, u16)> = None;
+    for entry in header.split(',') {
+        let mut params = entry.split(';');
+        let range = params.next().unwrap_or_default().trim();
+        let Some((range_ty, range_subty)) = range.split_once('/') else {
+            continue;
+        };
+        let specificity = if range_ty == "*" && range_subty == "*" {
+            0
+        } else if range_ty.eq_ignore_ascii_case(ty) && range_subty == "*" {
+            1
+        } else if range_ty.eq_ignore_ascii_case(ty) && range_subty.eq_ignore_ascii_case(subty) {
+            2
+        } else {
+            continue;
+        };
+        let mut q = Some(1000);
+        for param in params {
+            if let Some((key, value)) = param.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("q") {
+                    q = parse_qvalue(value.trim());
+                }
+            }
+        }
+        let Some(q) = q else {
+            continue;
+        };
+        if best.map_or(true, |(best_specificity, _)| specificity > best_specificity) {
+            best = Some((specificity, q));
+        }
+    }
+    best.map_or(0, |(_, q)| q)
+}
+
+/// Selects the media type to respond with, based on the value of an `Accept` header.
+///
+/// `available` lists the media types the response can be sent as in order of preference.
+/// The quality of each type is taken from the most specific matching media range, such as `text/html` before `text/*` and `*/*`.
+/// The type with the highest quality value is selected, where ties are resolved by the order of `available`.
+/// Parameters of the available types, such as the charset, are ignored when matching.
+///
+/// Returns `None` if the client does not accept any of the available types.
+///
+/// Example:
+/// ```
+/// # use static_http_file::select_media_type;
+/// let available = ["text/html; charset=utf-8", "application/xhtml+xml"];
+/// let accept = "text/html;q=0.9, application/xhtml+xml";
+/// assert_eq!(select_media_type(accept, &available), Some("application/xhtml+xml"));
+/// assert_eq!(select_media_type("*/*", &available), Some("text/html; charset=utf-8"));
+/// assert_eq!(select_media_type("image/*", &available), None);
+/// ```
+pub fn select_media_type<'m>(header: &str, available: &[&'m str]) -> Option<&'m str> {
+    let mut best: Option<(&'m str, u16)> = None;
+    for &mime in available {
+        let q = media_type_quality(header, mime);
+        if q > 0 && best.map_or(true, |(_, best_q)| q > best_q) {
+            best = Some((mime, q));
+        }
+    }
+    best.map(|(mime, _)| mime)
+}
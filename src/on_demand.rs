@@ -0,0 +1,209 @@
+use core::num::{NonZeroU8, NonZeroUsize};
+use std::sync::OnceLock;
+
+use bytedata::ByteData;
+
+use crate::{CacheBusting, CacheControl, HttpFile, HttpFileResponse};
+
+/// A content coding which is applied to the data of a file when the client accepts it.
+pub(crate) trait OnDemandEncoding {
+    /// The value of the `Content-Encoding` header, such as `gzip`.
+    const ENCODING: &'static str;
+    /// The names of the coding in an `Accept-Encoding` header.
+    const ACCEPTED: &'static [&'static str];
+    /// The suffix appended inside the quotes of the etag of the encoded data.
+    const ETAG_SUFFIX: &'static str;
+
+    /// Encodes all of the data.
+    fn encode(data: &[u8]) -> std::io::Result<Vec<u8>>;
+}
+
+/// The lazily encoded data and etag of a file, which can be shared between threads like the file itself.
+pub(crate) type EncodedCell = OnceLock<Option<(ByteData<'static>, String)>>;
+
+/// Returns the encoded data and its etag, encoding the data if it has not been done yet.
+/// Returns `None` if the file is already encoded or the encoded data is not smaller than the original data.
+pub(crate) fn encoded<'c, 'l, E: OnDemandEncoding, F: HttpFile<'l>>(
+    cell: &'c EncodedCell,
+    inner: &F,
+) -> Option<(&'c ByteData<'static>, &'c str)> {
+    cell.get_or_init(|| {
+        if inner.content_encoding().is_some() {
+            return None;
+        }
+        let data = inner.data();
+        let encoded = E::encode(data).ok()?;
+        if encoded.len() >= data.len() {
+            return None;
+        }
+        Some((
+            ByteData::from(encoded),
+            encoded_etag(inner.etag(), E::ETAG_SUFFIX),
+        ))
+    })
+    .as_ref()
+    .map(|(data, etag)| (data, etag.as_str()))
+}
+
+/// Returns the size of the encoded data relative to the original data if `encoding` names the coding `E`,
+/// encoding the data if it has not been done yet.
+pub(crate) fn compression_ratio<'l, E: OnDemandEncoding, F: HttpFile<'l>>(
+    cell: &EncodedCell,
+    inner: &F,
+    encoding: &str,
+) -> Option<f32> {
+    if !E::ACCEPTED
+        .iter()
+        .any(|coding| coding.eq_ignore_ascii_case(encoding))
+    {
+        return None;
+    }
+    // data is only encoded if it shrinks, so the original data is never empty here
+    let (data, _) = encoded::<E, F>(cell, inner)?;
+    Some(data.len() as f32 / inner.data().len() as f32)
+}
+
+/// Checks if the `Accept-Encoding` headers of a request allow the coding, either explicitly or through `*`, with a non-zero quality,
+/// and if the client does not prefer any of the `others` codings by giving it a higher quality.
+pub(crate) fn accepts<E: OnDemandEncoding>(request: &http::Request<()>, others: &[&str]) -> bool {
+    // several header fields are equivalent to a single field with the values separated by commas
+    let header = request
+        .headers()
+        .get_all(http::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut available = E::ACCEPTED.to_vec();
+    available.extend_from_slice(others);
+    matches!(crate::select_encoding(&header, &available), Some(coding) if E::ACCEPTED.contains(&coding))
+}
+
+/// Appends a suffix inside the quotes of the etag, which keeps weak etags weak.
+fn encoded_etag(etag: &str, suffix: &str) -> String {
+    match etag.strip_suffix('"') {
+        Some(tag) if !tag.is_empty() => format!("{}{}\"", tag, suffix),
+        _ => String::new(),
+    }
+}
+
+/// Returns the `Vary` names of a file with `Accept-Encoding` added.
+pub(crate) fn encoding_vary(inner: &[http::header::HeaderName]) -> Vec<http::header::HeaderName> {
+    let mut vary = inner.to_vec();
+    if !vary.contains(&http::header::ACCEPT_ENCODING) {
+        vary.push(http::header::ACCEPT_ENCODING);
+    }
+    vary
+}
+
+/// The encoded representation of a file.
+pub(crate) struct EncodedVariant<'g, T> {
+    pub(crate) inner: &'g T,
+    pub(crate) data: &'g ByteData<'static>,
+    pub(crate) etag: &'g str,
+    pub(crate) encoding: &'static str,
+}
+
+impl<'g, 'l, T: HttpFile<'l>> HttpFile<'l> for EncodedVariant<'g, T> {
+    #[inline]
+    fn content_type(&self) -> &str {
+        self.inner.content_type()
+    }
+
+    #[inline]
+    fn etag(&self) -> &str {
+        self.etag
+    }
+
+    // the cache busted location is shared by all representations, so it uses the etag of the inner file
+    #[inline]
+    fn etag_str(&self) -> &str {
+        self.inner.etag_str()
+    }
+
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        Some(self.encoding)
+    }
+
+    #[inline]
+    fn cache_busting(&self) -> &CacheBusting {
+        self.inner.cache_busting()
+    }
+
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.inner.last_modified()
+    }
+
+    #[inline]
+    fn cache_control(&self) -> CacheControl {
+        self.inner.cache_control()
+    }
+
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    #[inline]
+    fn into_data(self) -> ByteData<'l> {
+        self.data.clone()
+    }
+
+    #[inline]
+    fn clone_data(&self) -> ByteData<'l> {
+        self.data.clone()
+    }
+}
+
+impl<'g, 'l, T: HttpFileResponse<'l>> HttpFileResponse<'l> for EncodedVariant<'g, T> {
+    #[inline]
+    fn vary_headers(&self) -> &[http::header::HeaderName] {
+        self.inner.vary_headers()
+    }
+
+    #[inline]
+    fn network_error_logging(&self) -> Option<&crate::NetworkErrorLogging> {
+        self.inner.network_error_logging()
+    }
+
+    #[inline]
+    fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+        self.inner.security_policy()
+    }
+
+    #[inline]
+    fn sniff_protection(&self) -> bool {
+        self.inner.sniff_protection()
+    }
+
+    #[inline]
+    fn security_headers(&self, response: http::response::Builder) -> http::response::Builder {
+        self.inner.security_headers(response)
+    }
+
+    #[inline]
+    fn cachebust_uri<R: From<ByteData<'l>>>(
+        &self,
+        old_uri: &http::Uri,
+        query_key: &str,
+    ) -> Option<Result<http::Response<R>, http::Error>> {
+        self.inner.cachebust_uri(old_uri, query_key)
+    }
+
+    #[inline]
+    fn cachebust_suffix<R: From<ByteData<'l>>>(
+        &self,
+        old_uri: &http::Uri,
+        left_sep: Option<NonZeroU8>,
+        len: Option<NonZeroUsize>,
+    ) -> Option<Result<http::Response<R>, http::Error>> {
+        self.inner.cachebust_suffix(old_uri, left_sep, len)
+    }
+}
@@ -0,0 +1,95 @@
+use alloc::vec::Vec;
+
+/// The result of parsing the value of a `Range` header using [`parse_ranges`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RangeParseResult {
+    /// The satisfiable byte ranges, each given as inclusive `(first, last)` offsets.
+    Ranges(Vec<(u64, u64)>),
+    /// The header was valid but none of the ranges overlap the data, which should result in a `416 Range Not Satisfiable`.
+    Unsatisfiable,
+    /// The header is malformed or uses an unknown unit and should be ignored.
+    Ignore,
+}
+
+/// Parses the value of a `Range` header according to RFC 7233 for a resource of `total` bytes.
+///
+/// Open-ended ranges, such as `500-`, and suffix ranges, such as `-500`, are resolved against `total`.
+/// Ranges extending past the end of the data are truncated and ranges starting past the end are dropped.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{parse_ranges, RangeParseResult};
+/// assert_eq!(
+///     parse_ranges("bytes=0-9, -5", 100),
+///     RangeParseResult::Ranges(vec![(0, 9), (95, 99)])
+/// );
+/// assert_eq!(parse_ranges("bytes=100-", 100), RangeParseResult::Unsatisfiable);
+/// assert_eq!(parse_ranges("items=0-9", 100), RangeParseResult::Ignore);
+/// ```
+pub fn parse_ranges(header: &str, total: u64) -> RangeParseResult {
+    let header = header.trim_matches(|c| c == ' ' || c == '\t');
+    let Some((unit, set)) = header.split_once('=') else {
+        return RangeParseResult::Ignore;
+    };
+    if !unit.eq_ignore_ascii_case("bytes") {
+        return RangeParseResult::Ignore;
+    }
+    let mut ranges = Vec::new();
+    let mut any = false;
+    for spec in set.split(',') {
+        let spec = spec.trim_matches(|c| c == ' ' || c == '\t');
+        if spec.is_empty() {
+            continue;
+        }
+        any = true;
+        let Some((first, last)) = spec.split_once('-') else {
+            return RangeParseResult::Ignore;
+        };
+        if first.is_empty() {
+            // suffix range of the last `last` bytes
+            let Some(suffix) = parse_pos(last) else {
+                return RangeParseResult::Ignore;
+            };
+            if suffix != 0 && total != 0 {
+                ranges.push((total.saturating_sub(suffix), total - 1));
+            }
+            continue;
+        }
+        let Some(first) = parse_pos(first) else {
+            return RangeParseResult::Ignore;
+        };
+        let last = if last.is_empty() {
+            u64::MAX
+        } else {
+            match parse_pos(last) {
+                Some(last) if last >= first => last,
+                _ => return RangeParseResult::Ignore,
+            }
+        };
+        if first < total {
+            ranges.push((first, last.min(total - 1)));
+        }
+    }
+    if !any {
+        RangeParseResult::Ignore
+    } else if ranges.is_empty() {
+        RangeParseResult::Unsatisfiable
+    } else {
+        RangeParseResult::Ranges(ranges)
+    }
+}
+
+/// Parses a non-empty sequence of digits, saturating on overflow.
+fn parse_pos(value: &str) -> Option<u64> {
+    if value.is_empty() {
+        return None;
+    }
+    let mut pos = 0u64;
+    for b in value.bytes() {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        pos = pos.saturating_mul(10).saturating_add((b - b'0') as u64);
+    }
+    Some(pos)
+}
@@ -0,0 +1,98 @@
+use alloc::string::String;
+use http::header::{HeaderValue, InvalidHeaderValue};
+
+/// Configuration for the `Report-To` and `NEL` headers used by Network Error Logging.
+///
+/// The header values are validated when the configuration is created, so responses can always include them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NetworkErrorLogging {
+    report_to: HeaderValue,
+    nel: HeaderValue,
+}
+
+impl NetworkErrorLogging {
+    /// Create a new [`NetworkErrorLogging`] from pre-serialized JSON values of the `Report-To` and `NEL` headers.
+    ///
+    /// Returns an error if either value is not a valid header value, such as JSON containing a line break.
+    pub fn from_json(report_to: &str, nel: &str) -> Result<Self, InvalidHeaderValue> {
+        Ok(NetworkErrorLogging {
+            report_to: HeaderValue::from_str(report_to)?,
+            nel: HeaderValue::from_str(nel)?,
+        })
+    }
+
+    /// Create a new [`NetworkErrorLogging`] reporting failed requests to a single endpoint.
+    /// Both the reporting group and the policy are cached by the client for `max_age` seconds.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::NetworkErrorLogging;
+    /// let nel = NetworkErrorLogging::new("network-errors", "https://example.com/reports", 86400);
+    /// assert_eq!(
+    ///     nel.report_to(),
+    ///     r#"{"group":"network-errors","max_age":86400,"endpoints":[{"url":"https://example.com/reports"}]}"#
+    /// );
+    /// assert_eq!(nel.nel(), r#"{"report_to":"network-errors","max_age":86400}"#);
+    /// ```
+    pub fn new(group: &str, endpoint: &str, max_age: u64) -> Self {
+        use core::fmt::Write;
+        let mut report_to = String::with_capacity(64 + group.len() + endpoint.len());
+        report_to.push_str("{\"group\":");
+        push_json_str(&mut report_to, group);
+        write!(
+            report_to,
+            ",\"max_age\":{},\"endpoints\":[{{\"url\":",
+            max_age
+        )
+        .unwrap();
+        push_json_str(&mut report_to, endpoint);
+        report_to.push_str("}]}");
+
+        let mut nel = String::with_capacity(32 + group.len());
+        nel.push_str("{\"report_to\":");
+        push_json_str(&mut nel, group);
+        write!(nel, ",\"max_age\":{}}}", max_age).unwrap();
+
+        // the strings are escaped into printable ASCII, which is always a valid header value
+        NetworkErrorLogging {
+            report_to: HeaderValue::from_str(&report_to).expect("escaped JSON is printable ASCII"),
+            nel: HeaderValue::from_str(&nel).expect("escaped JSON is printable ASCII"),
+        }
+    }
+
+    /// The value of the `Report-To` header.
+    pub fn report_to(&self) -> &HeaderValue {
+        &self.report_to
+    }
+
+    /// The value of the `NEL` header.
+    pub fn nel(&self) -> &HeaderValue {
+        &self.nel
+    }
+}
+
+/// Pushes a JSON string literal, where control characters, `DEL` and non-ASCII characters are escaped as `\u` sequences.
+fn push_json_str(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c == ' ' || c.is_ascii_graphic() => out.push(c),
+            c => {
+                const HEX: &[u8; 16] = b"0123456789abcdef";
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units).iter() {
+                    out.push_str("\\u");
+                    for shift in [12, 8, 4, 0] {
+                        out.push(HEX[((unit >> shift) & 0xF) as usize] as char);
+                    }
+                }
+            }
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,124 @@
+/// Configuration for security related response headers.
+///
+/// Example:
+/// ```
+/// # use static_http_file::SecurityHeaders;
+/// const ISOLATED: SecurityHeaders = SecurityHeaders::new()
+///     .with_cross_origin_embedder_policy("require-corp")
+///     .with_cross_origin_opener_policy("same-origin");
+/// assert_eq!(ISOLATED.cross_origin_resource_policy(), None);
+/// assert_eq!(ISOLATED.cross_origin_opener_policy(), Some("same-origin"));
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SecurityHeaders {
+    cross_origin_resource_policy: Option<&'static str>,
+    cross_origin_embedder_policy: Option<&'static str>,
+    cross_origin_opener_policy: Option<&'static str>,
+}
+
+impl SecurityHeaders {
+    /// Create a new [`SecurityHeaders`] which does not emit any headers.
+    pub const fn new() -> Self {
+        SecurityHeaders {
+            cross_origin_resource_policy: None,
+            cross_origin_embedder_policy: None,
+            cross_origin_opener_policy: None,
+        }
+    }
+
+    /// Set the value of the `Cross-Origin-Resource-Policy` header, such as `same-origin` or `cross-origin`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value, which is a compile time error in constant contexts.
+    pub const fn with_cross_origin_resource_policy(mut self, value: &'static str) -> Self {
+        assert!(
+            is_header_value(value),
+            "the value must be a valid header value"
+        );
+        self.cross_origin_resource_policy = Some(value);
+        self
+    }
+
+    /// Set the value of the `Cross-Origin-Embedder-Policy` header, such as `require-corp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value, which is a compile time error in constant contexts.
+    pub const fn with_cross_origin_embedder_policy(mut self, value: &'static str) -> Self {
+        assert!(
+            is_header_value(value),
+            "the value must be a valid header value"
+        );
+        self.cross_origin_embedder_policy = Some(value);
+        self
+    }
+
+    /// Set the value of the `Cross-Origin-Opener-Policy` header, such as `same-origin`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value, which is a compile time error in constant contexts.
+    pub const fn with_cross_origin_opener_policy(mut self, value: &'static str) -> Self {
+        assert!(
+            is_header_value(value),
+            "the value must be a valid header value"
+        );
+        self.cross_origin_opener_policy = Some(value);
+        self
+    }
+
+    /// The value of the `Cross-Origin-Resource-Policy` header.
+    pub const fn cross_origin_resource_policy(&self) -> Option<&'static str> {
+        self.cross_origin_resource_policy
+    }
+
+    /// The value of the `Cross-Origin-Embedder-Policy` header.
+    pub const fn cross_origin_embedder_policy(&self) -> Option<&'static str> {
+        self.cross_origin_embedder_policy
+    }
+
+    /// The value of the `Cross-Origin-Opener-Policy` header.
+    pub const fn cross_origin_opener_policy(&self) -> Option<&'static str> {
+        self.cross_origin_opener_policy
+    }
+
+    /// Add the configured headers to a response.
+    /// The values were validated when they were set, so they are converted without checking them again.
+    pub fn apply(&self, mut response: http::response::Builder) -> http::response::Builder {
+        if let Some(value) = self.cross_origin_resource_policy {
+            response = response.header(
+                "cross-origin-resource-policy",
+                http::header::HeaderValue::from_static(value),
+            );
+        }
+        if let Some(value) = self.cross_origin_embedder_policy {
+            response = response.header(
+                "cross-origin-embedder-policy",
+                http::header::HeaderValue::from_static(value),
+            );
+        }
+        if let Some(value) = self.cross_origin_opener_policy {
+            response = response.header(
+                "cross-origin-opener-policy",
+                http::header::HeaderValue::from_static(value),
+            );
+        }
+        response
+    }
+}
+
+/// Checks if a value is accepted by [`HeaderValue::from_static`](http::header::HeaderValue::from_static),
+/// which only allows visible ASCII characters, spaces and tabs.
+const fn is_header_value(value: &str) -> bool {
+    let value = value.as_bytes();
+    let mut i = 0;
+    while i < value.len() {
+        let b = value[i];
+        if (b < 0x20 && b != b'\t') || b >= 0x7F {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
@@ -0,0 +1,234 @@
+use bytedata::{ByteData, StringData};
+
+use crate::{CacheBusting, ConstHttpFile, HttpFile, HttpFileResponse};
+
+/// A HTTP file backed by data that may be borrowed, static, or shared.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SharedHttpFile<'a> {
+    pub file: Option<StringData<'a>>,
+    pub data: ByteData<'a>,
+    pub mime: StringData<'a>,
+    pub etag: StringData<'a>,
+    pub encoding: Option<StringData<'a>>,
+    pub cbust: CacheBusting,
+    /// When the file was last modified as seconds since the Unix epoch, if known.
+    pub modified: Option<u64>,
+}
+
+impl<'a> SharedHttpFile<'a> {
+    /// Create a new [`SharedHttpFile`] with an explicit filename.
+    pub const fn new_named(
+        data: ByteData<'a>,
+        mime: StringData<'a>,
+        etag: StringData<'a>,
+        file: StringData<'a>,
+    ) -> Self {
+        SharedHttpFile {
+            file: Some(file),
+            data,
+            mime,
+            etag,
+            encoding: None,
+            cbust: CacheBusting::None,
+            modified: None,
+        }
+    }
+
+    /// Create a new [`SharedHttpFile`] without an explicit filename.
+    pub const fn new(data: ByteData<'a>, mime: StringData<'a>, etag: StringData<'a>) -> Self {
+        SharedHttpFile {
+            file: None,
+            data,
+            mime,
+            etag,
+            encoding: None,
+            cbust: CacheBusting::None,
+            modified: None,
+        }
+    }
+
+    /// Create a new [`SharedHttpFile`] where the mime type and etag are computed from the data.
+    ///
+    /// If `path` is provided the mime type is detected from the file extension before falling back to the file contents.
+    #[cfg(feature = "std")]
+    pub fn from_data(data: ByteData<'a>, path: Option<&str>) -> Self {
+        let etag = crate::compute_etag_nonconst(data.as_slice());
+        Self::from_data_etag(data, path, etag)
+    }
+
+    /// Create a new [`SharedHttpFile`] where the mime type is computed from the data like [`SharedHttpFile::from_data`], using an etag which has already been computed.
+    #[cfg(feature = "std")]
+    fn from_data_etag(data: ByteData<'a>, path: Option<&str>, etag: String) -> Self {
+        let mime = match path {
+            Some(path) => crate::detect_mime_type(path, data.as_slice()),
+            None => crate::detect_mime_type_magic(data.as_slice()),
+        }
+        .unwrap_or(crate::DEFAULT_MIME);
+        SharedHttpFile {
+            file: path.map(|path| StringData::from(path.to_owned())),
+            data,
+            mime: StringData::from_static(mime),
+            etag: StringData::from(etag),
+            encoding: None,
+            cbust: CacheBusting::None,
+            modified: None,
+        }
+    }
+}
+
+impl SharedHttpFile<'static> {
+    /// Create a new [`SharedHttpFile`] by reading all of the data of a reader, where the mime type and etag are computed from the data.
+    ///
+    /// If `path` is provided the mime type is detected from the file extension before falling back to the file contents.
+    /// The reader is read until its end, see [`SharedHttpFile::from_reader_with_max_size`] for readers which are not trusted.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::SharedHttpFile;
+    /// let file = SharedHttpFile::from_reader(&b"{}"[..], Some("data.json")).unwrap();
+    /// assert_eq!(file.mime.as_str(), "application/json");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read>(reader: R, path: Option<&str>) -> std::io::Result<Self> {
+        Self::from_reader_with_max_size(reader, path, u64::MAX)
+    }
+
+    /// Create a new [`SharedHttpFile`] by reading the data of a reader like [`SharedHttpFile::from_reader`], reading at most `max_bytes` bytes.
+    ///
+    /// Returns an error of kind [`InvalidData`](std::io::ErrorKind::InvalidData) if the reader has more than `max_bytes` bytes.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::SharedHttpFile;
+    /// let file = SharedHttpFile::from_reader_with_max_size(&b"{}"[..], Some("data.json"), 2).unwrap();
+    /// assert_eq!(file.data.as_slice(), b"{}");
+    /// let err = SharedHttpFile::from_reader_with_max_size(&b"[{}]"[..], None, 2).unwrap_err();
+    /// assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_reader_with_max_size<R: std::io::Read>(
+        reader: R,
+        path: Option<&str>,
+        max_bytes: u64,
+    ) -> std::io::Result<Self> {
+        use std::io::Read;
+        let mut builder = bytedata::SharedBytesBuilder::new();
+        let mut hasher = crate::EtagHasher::new();
+        // one byte past the limit is read to detect readers which are too large
+        let total = crate::std::read_into(
+            reader.take(max_bytes.saturating_add(1)),
+            &mut builder,
+            Some(&mut hasher),
+        )?;
+        if total > max_bytes {
+            return Err(crate::std::file_too_large(max_bytes));
+        }
+        // the etag is computed while reading, instead of in a second pass over the data
+        Ok(Self::from_data_etag(
+            ByteData::from_shared(builder.build()),
+            path,
+            hasher.finish(),
+        ))
+    }
+}
+
+impl SharedHttpFile<'_> {
+    /// Marks the etag as weak by prefixing it with `W/`, for files which are semantically equal even if their bytes differ,
+    /// such as generated files with embedded timestamps.
+    ///
+    /// Weak etags are only used for `If-None-Match`, while `If-Match` and `If-Range` require a strong etag.
+    pub fn with_weak_etag(mut self) -> Self {
+        if !self.etag.is_empty() && !self.etag.as_str().starts_with("W/") {
+            self.etag = StringData::from(alloc::format!("W/{}", self.etag.as_str()));
+        }
+        self
+    }
+
+    /// Sets the method used to redirect requests to a location containing the etag.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{CacheBusting, HttpFileResponse, SharedHttpFile};
+    /// # use bytedata::{ByteData, StringData};
+    /// let file = SharedHttpFile::new(
+    ///     ByteData::from_static(b"body {}"),
+    ///     StringData::from_static("text/css"),
+    ///     StringData::from_static("\"q25fZAd-fY\""),
+    /// )
+    /// .with_cache_busting(CacheBusting::Query(StringData::from_static("v")));
+    /// let request = http::Request::get("/style.css").body(()).unwrap();
+    /// let response = file.respond_borrowed::<ByteData>(&request).unwrap();
+    /// assert_eq!(response.status(), 307);
+    /// assert_eq!(response.headers()["location"], "/style.css?v=q25fZAd-fY");
+    /// ```
+    pub fn with_cache_busting(mut self, cbust: CacheBusting) -> Self {
+        self.cbust = cbust;
+        self
+    }
+}
+
+impl From<ConstHttpFile> for SharedHttpFile<'static> {
+    /// Converts a [`ConstHttpFile`] without copying any data, as all of its fields are static.
+    fn from(file: ConstHttpFile) -> Self {
+        SharedHttpFile {
+            file: file.file.map(StringData::from_static),
+            data: ByteData::from_static(file.data),
+            mime: StringData::from_static(file.mime),
+            etag: StringData::from_static(file.etag),
+            encoding: file.encoding.map(StringData::from_static),
+            cbust: file.cache_busting.clone(),
+            modified: None,
+        }
+    }
+}
+
+impl Default for SharedHttpFile<'_> {
+    fn default() -> Self {
+        SharedHttpFile {
+            file: None,
+            data: ByteData::from_static(&[]),
+            mime: StringData::from_static(crate::DEFAULT_MIME),
+            etag: StringData::from_static(""),
+            encoding: None,
+            cbust: CacheBusting::None,
+            modified: None,
+        }
+    }
+}
+
+impl<'a> HttpFile<'a> for SharedHttpFile<'a> {
+    fn content_type(&self) -> &str {
+        self.mime.as_str()
+    }
+
+    fn etag(&self) -> &str {
+        self.etag.as_str()
+    }
+
+    fn content_encoding(&self) -> Option<&str> {
+        self.encoding.as_ref().map(|encoding| encoding.as_str())
+    }
+
+    fn last_modified(&self) -> Option<u64> {
+        self.modified
+    }
+
+    fn cache_busting(&self) -> &CacheBusting {
+        &self.cbust
+    }
+
+    fn data(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    fn into_data(self) -> ByteData<'a> {
+        self.data
+    }
+
+    fn clone_data(&self) -> ByteData<'a> {
+        self.data.clone()
+    }
+}
+
+impl<'a> HttpFileResponse<'a> for SharedHttpFile<'a> {}
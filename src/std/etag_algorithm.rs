@@ -0,0 +1,87 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use super::EtagHasher;
+
+/// The method used to compute the etag of a file.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{compute_etag_nonconst, EtagAlgorithm};
+/// let sampled = EtagAlgorithm::Sampled { prefix: 4, suffix: 4 };
+/// assert_ne!(sampled.compute(b"0123456789"), sampled.compute(b"0123456789a"));
+/// assert_eq!(sampled.compute(b"0123456789"), sampled.compute(b"0123xx6789"));
+/// assert_eq!(EtagAlgorithm::Full.compute(b"foo"), compute_etag_nonconst(b"foo"));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum EtagAlgorithm {
+    /// Hash all of the data, which gives the same etag as [`compute_etag_nonconst`](crate::compute_etag_nonconst).
+    #[default]
+    Full,
+    /// Hash the size of the data together with the first `prefix` and the last `suffix` bytes.
+    ///
+    /// This avoids reading all of a huge file, but changes which keep the size and only touch the bytes between
+    /// the sampled regions are not detected, so clients may keep using a stale cached copy.
+    /// It should only be used for files which are replaced as a whole, such as disk images or archives.
+    /// The etags differ from those of [`EtagAlgorithm::Full`] even if the sampled regions cover all of the data.
+    Sampled { prefix: usize, suffix: usize },
+}
+
+impl EtagAlgorithm {
+    /// Compute the etag of the data.
+    pub fn compute(&self, data: &[u8]) -> String {
+        match *self {
+            EtagAlgorithm::Full => super::compute_etag_nonconst(data),
+            EtagAlgorithm::Sampled { prefix, suffix } => {
+                let (prefix_end, suffix_start) = sampled_regions(data.len() as u64, prefix, suffix);
+                let mut hasher = EtagHasher::new();
+                hasher.update(&(data.len() as u64).to_le_bytes());
+                hasher.update(&data[..prefix_end as usize]);
+                hasher.update(&data[suffix_start as usize..]);
+                hasher.finish()
+            }
+        }
+    }
+
+    /// Compute the etag of the data of a reader, which only reads the sampled regions when using [`EtagAlgorithm::Sampled`].
+    /// The reader is read from its start, and the result is identical to [`EtagAlgorithm::compute`] for the same data.
+    pub fn compute_seekable<R: Read + Seek>(&self, mut reader: R) -> std::io::Result<String> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        match *self {
+            EtagAlgorithm::Full => super::compute_etag_streaming(reader),
+            EtagAlgorithm::Sampled { prefix, suffix } => {
+                let (prefix_end, suffix_start) = sampled_regions(len, prefix, suffix);
+                let mut hasher = EtagHasher::new();
+                hasher.update(&len.to_le_bytes());
+                let mut buf = vec![0u8; 64 * 1024];
+                hash_exact(&mut reader, &mut hasher, &mut buf, prefix_end)?;
+                reader.seek(SeekFrom::Start(suffix_start))?;
+                hash_exact(&mut reader, &mut hasher, &mut buf, len - suffix_start)?;
+                Ok(hasher.finish())
+            }
+        }
+    }
+}
+
+/// Returns the end of the prefix and the start of the suffix, where the suffix never overlaps the prefix.
+fn sampled_regions(len: u64, prefix: usize, suffix: usize) -> (u64, u64) {
+    let prefix_end = len.min(prefix as u64);
+    let suffix_start = len.saturating_sub(suffix as u64).max(prefix_end);
+    (prefix_end, suffix_start)
+}
+
+/// Feeds exactly `len` bytes of the reader into the hasher.
+fn hash_exact<R: Read>(
+    reader: &mut R,
+    hasher: &mut EtagHasher,
+    buf: &mut [u8],
+    mut len: u64,
+) -> std::io::Result<()> {
+    while len > 0 {
+        let n = len.min(buf.len() as u64) as usize;
+        reader.read_exact(&mut buf[..n])?;
+        hasher.update(&buf[..n]);
+        len -= n as u64;
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::sync::OnceLock;
+
+use alloc::borrow::Cow;
+use bytedata::ByteData;
+
+use crate::{HttpFile, HttpFileResponse};
+
+/// A static HTTP file where the etag is only computed the first time it is needed.
+///
+/// This avoids hashing the data of files that are loaded but never served.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct LazyEtagHttpFile {
+    pub file: Cow<'static, str>,
+    pub data: ByteData<'static>,
+    pub mime: Cow<'static, str>,
+    /// The computed etag along with its header value, which are computed together.
+    etag: OnceLock<(String, http::header::HeaderValue)>,
+}
+
+impl LazyEtagHttpFile {
+    /// Create a new [`LazyEtagHttpFile`] with an explicit mime and data.
+    pub const fn new_with_mime_data(
+        file: Cow<'static, str>,
+        mime: Cow<'static, str>,
+        data: ByteData<'static>,
+    ) -> Self {
+        LazyEtagHttpFile {
+            file,
+            data,
+            mime,
+            etag: OnceLock::new(),
+        }
+    }
+
+    /// Create a new [`LazyEtagHttpFile`] from a path.
+    pub fn new(path: impl Into<Cow<'static, str>>) -> std::io::Result<Self> {
+        let path: Cow<'static, str> = path.into();
+        let data = super::std_http_file::read_file(path.as_ref().as_ref())?;
+        let mime = crate::detect_mime_type(path.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME);
+        Ok(Self::new_with_mime_data(
+            path,
+            Cow::Borrowed(mime),
+            ByteData::from_shared(data),
+        ))
+    }
+
+    /// Checks if the etag has already been computed.
+    pub fn is_etag_computed(&self) -> bool {
+        self.etag.get().is_some()
+    }
+
+    fn computed_etag(&self) -> &(String, http::header::HeaderValue) {
+        self.etag.get_or_init(|| {
+            let etag = super::compute_etag_nonconst(self.data.as_slice());
+            let value = crate::traits::etag_to_header_value(&etag);
+            (etag, value)
+        })
+    }
+}
+
+impl HttpFile<'static> for LazyEtagHttpFile {
+    fn content_type(&self) -> &str {
+        self.mime.as_ref()
+    }
+
+    fn etag(&self) -> &str {
+        self.computed_etag().0.as_str()
+    }
+
+    fn data(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    fn into_data(self) -> ByteData<'static> {
+        self.data
+    }
+
+    fn clone_data(&self) -> ByteData<'static> {
+        self.data.clone()
+    }
+}
+
+impl HttpFileResponse<'static> for LazyEtagHttpFile {
+    #[inline]
+    fn etag_header_value(&self) -> http::header::HeaderValue {
+        self.computed_etag().1.clone()
+    }
+}
@@ -1,6 +1,17 @@
 mod std_http_file;
 pub use std_http_file::*;
 
+mod lazy_etag_http_file;
+pub use lazy_etag_http_file::LazyEtagHttpFile;
+
+mod etag_algorithm;
+pub use etag_algorithm::EtagAlgorithm;
+
+#[cfg(feature = "nonce")]
+mod nonce_http_file;
+#[cfg(feature = "nonce")]
+pub use nonce_http_file::{generate_nonce, NonceHttpFile, DEFAULT_NONCE_PLACEHOLDER};
+
 /// Compute an etag from a byte slice. The returned etag is a base64url-encoded 64-bit xxhash3 hash of the data wrapped in quotes.
 ///
 /// Example:
@@ -10,13 +21,86 @@ pub use std_http_file::*;
 /// assert_eq!(&etag, "\"q25fZAd-fY\"");
 /// ```
 pub fn compute_etag_nonconst(data: &[u8]) -> String {
-    let h = xxhash_rust::xxh3::xxh3_64(data).to_be_bytes();
-    let (mut etag, _n) = crate::b64url_const(&h, [0; 12], 1);
-    #[cfg(debug_assertions)]
-    if _n != 12 {
-        panic!("Unexpected etag length");
-    }
-    etag[0] = b'"';
-    etag[11] = b'"';
+    let etag = crate::const_etag::etag_from_hash(xxhash_rust::xxh3::xxh3_64(data));
+    unsafe { String::from_utf8_unchecked(etag.to_vec()) }
+}
+
+/// Compute an etag from a byte slice using a seeded hash, such that deployments using different seeds produce different etags for the same data.
+/// The resulting etag is identical to the one produced by [`compute_etag_seeded_const`](crate::compute_etag_seeded_const).
+///
+/// Example:
+/// ```
+/// # use static_http_file::{compute_etag_nonconst, compute_etag_seeded};
+/// assert_ne!(compute_etag_seeded(b"foo", 0x5eed), compute_etag_nonconst(b"foo"));
+/// assert_eq!(compute_etag_seeded(b"foo", 0), compute_etag_nonconst(b"foo"));
+/// ```
+pub fn compute_etag_seeded(data: &[u8], seed: u64) -> String {
+    let etag = crate::const_etag::etag_from_hash(xxhash_rust::xxh3::xxh3_64_with_seed(data, seed));
     unsafe { String::from_utf8_unchecked(etag.to_vec()) }
 }
+
+/// Compute an etag from the data of a reader, which is read in chunks instead of being kept in memory.
+/// The resulting etag is identical to the one produced by [`compute_etag_nonconst`] for the same data.
+///
+/// Example:
+/// ```
+/// # use static_http_file::compute_etag_streaming;
+/// let etag = compute_etag_streaming(&b"foo"[..]).unwrap();
+/// assert_eq!(&etag, "\"q25fZAd-fY\"");
+/// ```
+pub fn compute_etag_streaming<R: std::io::Read>(mut reader: R) -> std::io::Result<String> {
+    let mut hasher = EtagHasher::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finish())
+}
+
+/// Incrementally computes an etag from data that is provided in chunks.
+/// The resulting etag is identical to the one produced by [`compute_etag_nonconst`] for the concatenated data.
+///
+/// Example:
+/// ```
+/// # use static_http_file::EtagHasher;
+/// let mut hasher = EtagHasher::new();
+/// hasher.update(b"f");
+/// hasher.update(b"oo");
+/// assert_eq!(&hasher.finish(), "\"q25fZAd-fY\"");
+/// ```
+#[derive(Clone)]
+pub struct EtagHasher {
+    state: xxhash_rust::xxh3::Xxh3,
+}
+
+impl EtagHasher {
+    /// Create a new [`EtagHasher`] without any data.
+    pub fn new() -> Self {
+        EtagHasher {
+            state: xxhash_rust::xxh3::Xxh3::new(),
+        }
+    }
+
+    /// Feed more data into the hasher.
+    pub fn update(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    /// Compute the etag of all data fed into the hasher so far.
+    pub fn finish(&self) -> String {
+        let etag = crate::const_etag::etag_from_hash(self.state.digest());
+        unsafe { String::from_utf8_unchecked(etag.to_vec()) }
+    }
+}
+
+impl Default for EtagHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
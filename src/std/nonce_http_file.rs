@@ -0,0 +1,378 @@
+use core::num::{NonZeroU8, NonZeroUsize};
+
+use bytedata::{ByteData, StringData};
+use http::header::{HeaderValue, InvalidHeaderValue};
+
+use crate::{CacheBusting, CacheControl, HttpFile, HttpFileResponse};
+
+/// The placeholder which is replaced by the nonce unless another one is configured.
+pub const DEFAULT_NONCE_PLACEHOLDER: &str = "{{CSP_NONCE}}";
+
+/// Generate a random base64url-encoded 128-bit nonce, suitable for a `Content-Security-Policy`.
+///
+/// The randomness comes from the random number generator of the operating system, as nonces must be unpredictable.
+///
+/// # Panics
+///
+/// Panics if the operating system fails to provide random bytes, as a predictable nonce would defeat the policy.
+pub fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("the operating system failed to provide random bytes");
+    let (nonce, len) = crate::b64url_const(&bytes, [0u8; 22], 0);
+    String::from_utf8(nonce[..len].to_vec()).unwrap()
+}
+
+/// A file, such as an HTML document with inline scripts, where a placeholder is replaced by a new nonce for each response.
+/// The same nonce is set in the `Content-Security-Policy` header.
+///
+/// As the data differs for every response, it is never stored by caches and ranges are not supported.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_http_file, ConstHttpFile, HttpFileResponse, NonceHttpFile};
+/// const PAGE: ConstHttpFile = const_http_file!(b"<script nonce=\"{{CSP_NONCE}}\">run()</script>" as &[u8], "text/html");
+/// let page = NonceHttpFile::new(PAGE);
+/// let request = http::Request::get("/").body(()).unwrap();
+/// let response = page.respond_borrowed::<bytedata::ByteData>(&request).unwrap();
+/// let csp = response.headers()["content-security-policy"].to_str().unwrap();
+/// assert!(csp.starts_with("script-src 'nonce-"));
+/// ```
+pub struct NonceHttpFile<T> {
+    inner: T,
+    placeholder: &'static str,
+    policy: Option<StringData<'static>>,
+}
+
+impl<T> NonceHttpFile<T> {
+    /// Wrap a file where [`DEFAULT_NONCE_PLACEHOLDER`] is replaced by the nonce,
+    /// and a `script-src` policy allowing the nonce is emitted.
+    pub const fn new(inner: T) -> Self {
+        NonceHttpFile {
+            inner,
+            placeholder: DEFAULT_NONCE_PLACEHOLDER,
+            policy: None,
+        }
+    }
+
+    /// Set the placeholder which is replaced by the nonce.
+    pub fn with_placeholder(mut self, placeholder: &'static str) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    /// Set the `Content-Security-Policy` header, where the placeholder is replaced by the nonce,
+    /// such as `script-src 'nonce-{{CSP_NONCE}}'; style-src 'nonce-{{CSP_NONCE}}'`.
+    ///
+    /// Returns an error if the policy is not a valid header value, such that no response fails because of it.
+    /// The nonce only contains base64url characters, so replacing the placeholder keeps the value valid.
+    pub fn with_policy(
+        mut self,
+        policy: impl Into<StringData<'static>>,
+    ) -> Result<Self, InvalidHeaderValue> {
+        let policy = policy.into();
+        HeaderValue::from_str(policy.as_str())?;
+        self.policy = Some(policy);
+        Ok(self)
+    }
+
+    /// The placeholder which is replaced by the nonce.
+    pub const fn placeholder(&self) -> &'static str {
+        self.placeholder
+    }
+
+    /// Returns a reference to the wrapped file.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwraps the file.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns the `Content-Security-Policy` header value for a nonce.
+    pub fn policy(&self, nonce: &str) -> String {
+        match &self.policy {
+            Some(policy) => policy.as_str().replace(self.placeholder, nonce),
+            None => format!("script-src 'nonce-{}'", nonce),
+        }
+    }
+}
+
+impl<'l, T: HttpFileResponse<'l>> NonceHttpFile<T> {
+    /// Returns the data with each placeholder replaced by the nonce.
+    pub fn render(&self, nonce: &str) -> Vec<u8> {
+        let data = self.inner.data();
+        let placeholder = self.placeholder.as_bytes();
+        let mut out = Vec::with_capacity(data.len());
+        let mut i = 0;
+        while i < data.len() {
+            if !placeholder.is_empty() && data[i..].starts_with(placeholder) {
+                out.extend_from_slice(nonce.as_bytes());
+                i += placeholder.len();
+            } else {
+                out.push(data[i]);
+                i += 1;
+            }
+        }
+        out
+    }
+
+    fn variant(&self) -> NonceVariant<'_, T> {
+        let nonce = generate_nonce();
+        let data = self.render(&nonce);
+        let etag = crate::compute_etag_nonconst(&data);
+        NonceVariant {
+            inner: &self.inner,
+            data: ByteData::from(data),
+            etag,
+            policy: self.policy(&nonce),
+        }
+    }
+}
+
+impl<'l, T: HttpFile<'l>> HttpFile<'l> for NonceHttpFile<T> {
+    #[inline]
+    fn content_type(&self) -> &str {
+        self.inner.content_type()
+    }
+
+    #[inline]
+    fn etag(&self) -> &str {
+        self.inner.etag()
+    }
+
+    #[inline]
+    fn etag_str(&self) -> &str {
+        self.inner.etag_str()
+    }
+
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        self.inner.content_encoding()
+    }
+
+    #[inline]
+    fn available_encodings(&self) -> Vec<&str> {
+        self.inner.available_encodings()
+    }
+
+    #[inline]
+    fn cache_busting(&self) -> &CacheBusting {
+        self.inner.cache_busting()
+    }
+
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.inner.last_modified()
+    }
+
+    #[inline]
+    fn cache_control(&self) -> CacheControl {
+        CacheControl::no_store()
+    }
+
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.inner.data()
+    }
+
+    #[inline]
+    fn into_data(self) -> ByteData<'l> {
+        self.inner.into_data()
+    }
+
+    #[inline]
+    fn clone_data(&self) -> ByteData<'l> {
+        self.inner.clone_data()
+    }
+}
+
+// `respond_guard` and `response_headers` describe the data with the placeholders,
+// while `respond` and `respond_borrowed` render a new nonce for each response
+impl<'l, T: HttpFileResponse<'l>> HttpFileResponse<'l> for NonceHttpFile<T> {
+    #[inline]
+    fn respond<R: From<ByteData<'l>>>(
+        self,
+        request: &http::Request<()>,
+    ) -> Result<http::Response<R>, http::Error> {
+        self.variant().respond(request)
+    }
+
+    #[inline]
+    fn respond_borrowed<R: From<ByteData<'l>>>(
+        &self,
+        request: &http::Request<()>,
+    ) -> Result<http::Response<R>, http::Error> {
+        self.variant().respond(request)
+    }
+
+    #[inline]
+    fn vary_headers(&self) -> &[http::header::HeaderName] {
+        self.inner.vary_headers()
+    }
+
+    #[inline]
+    fn network_error_logging(&self) -> Option<&crate::NetworkErrorLogging> {
+        self.inner.network_error_logging()
+    }
+
+    #[inline]
+    fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+        self.inner.security_policy()
+    }
+
+    #[inline]
+    fn sniff_protection(&self) -> bool {
+        self.inner.sniff_protection()
+    }
+
+    #[inline]
+    fn security_headers(&self, response: http::response::Builder) -> http::response::Builder {
+        self.inner.security_headers(response)
+    }
+
+    #[inline]
+    fn cachebust_uri<R: From<ByteData<'l>>>(
+        &self,
+        old_uri: &http::Uri,
+        query_key: &str,
+    ) -> Option<Result<http::Response<R>, http::Error>> {
+        self.inner.cachebust_uri(old_uri, query_key)
+    }
+
+    #[inline]
+    fn cachebust_suffix<R: From<ByteData<'l>>>(
+        &self,
+        old_uri: &http::Uri,
+        left_sep: Option<NonZeroU8>,
+        len: Option<NonZeroUsize>,
+    ) -> Option<Result<http::Response<R>, http::Error>> {
+        self.inner.cachebust_suffix(old_uri, left_sep, len)
+    }
+}
+
+/// The data of a [`NonceHttpFile`] rendered with a single nonce.
+struct NonceVariant<'g, T> {
+    inner: &'g T,
+    data: ByteData<'static>,
+    etag: String,
+    policy: String,
+}
+
+impl<'g, 'l, T: HttpFile<'l>> HttpFile<'l> for NonceVariant<'g, T> {
+    #[inline]
+    fn content_type(&self) -> &str {
+        self.inner.content_type()
+    }
+
+    #[inline]
+    fn etag(&self) -> &str {
+        &self.etag
+    }
+
+    // the etag differs for every response, so the cache busted location uses the etag of the inner file
+    #[inline]
+    fn etag_str(&self) -> &str {
+        self.inner.etag_str()
+    }
+
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        self.inner.content_encoding()
+    }
+
+    #[inline]
+    fn available_encodings(&self) -> Vec<&str> {
+        self.inner.available_encodings()
+    }
+
+    #[inline]
+    fn cache_busting(&self) -> &CacheBusting {
+        self.inner.cache_busting()
+    }
+
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.inner.last_modified()
+    }
+
+    #[inline]
+    fn cache_control(&self) -> CacheControl {
+        CacheControl::no_store()
+    }
+
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    #[inline]
+    fn into_data(self) -> ByteData<'l> {
+        self.data
+    }
+
+    #[inline]
+    fn clone_data(&self) -> ByteData<'l> {
+        self.data.clone()
+    }
+}
+
+impl<'g, 'l, T: HttpFileResponse<'l>> HttpFileResponse<'l> for NonceVariant<'g, T> {
+    #[inline]
+    fn vary_headers(&self) -> &[http::header::HeaderName] {
+        self.inner.vary_headers()
+    }
+
+    #[inline]
+    fn network_error_logging(&self) -> Option<&crate::NetworkErrorLogging> {
+        self.inner.network_error_logging()
+    }
+
+    #[inline]
+    fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+        self.inner.security_policy()
+    }
+
+    #[inline]
+    fn sniff_protection(&self) -> bool {
+        self.inner.sniff_protection()
+    }
+
+    fn security_headers(&self, response: http::response::Builder) -> http::response::Builder {
+        // the policy was validated by `with_policy` and the nonce only contains base64url characters
+        self.inner.security_headers(response).header(
+            http::header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_str(&self.policy).unwrap(),
+        )
+    }
+
+    #[inline]
+    fn cachebust_uri<R: From<ByteData<'l>>>(
+        &self,
+        old_uri: &http::Uri,
+        query_key: &str,
+    ) -> Option<Result<http::Response<R>, http::Error>> {
+        self.inner.cachebust_uri(old_uri, query_key)
+    }
+
+    #[inline]
+    fn cachebust_suffix<R: From<ByteData<'l>>>(
+        &self,
+        old_uri: &http::Uri,
+        left_sep: Option<NonZeroU8>,
+        len: Option<NonZeroUsize>,
+    ) -> Option<Result<http::Response<R>, http::Error>> {
+        self.inner.cachebust_suffix(old_uri, left_sep, len)
+    }
+}
@@ -1,9 +1,13 @@
-use std::{fs::File, path::Path};
+use std::{fs::File, path::Path, time::SystemTime};
 
 use alloc::borrow::Cow;
-use bytedata::ByteData;
+use bytedata::{ByteData, StringData};
 
-use crate::{HttpFile, HttpFileResponse};
+use super::{EtagAlgorithm, EtagHasher};
+use crate::{HttpFile, HttpFileResponse, SharedHttpFile};
+
+/// The maximum number of bytes read from a file unless another limit is given, which is 1 GiB.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1 << 30;
 
 /// A static HTTP file that can be computed at compile time or in other constant contexts.
 ///
@@ -15,6 +19,34 @@ pub struct StdHttpFile {
     pub data: ByteData<'static>,
     pub mime: Cow<'static, str>,
     pub etag: Cow<'static, str>,
+    pub encoding: Option<Cow<'static, str>>,
+    /// The modification time of the file when it was read, which is `None` if the data was not read from the file system.
+    pub modified: Option<SystemTime>,
+    /// How the file was read, which is repeated when it is reloaded.
+    pub(crate) options: ReadOptions,
+}
+
+/// The options a [`StdHttpFile`] was read with, such that [`StdHttpFile::reload`] reads it the same way.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct ReadOptions {
+    /// The maximum number of bytes read from the file.
+    pub(crate) max_bytes: u64,
+    /// The algorithm used to compute the etag.
+    pub(crate) etag_algorithm: EtagAlgorithm,
+    /// Whether the mime type was given explicitly instead of detected, in which case it is kept.
+    pub(crate) explicit_mime: bool,
+    /// Whether the etag was given explicitly instead of computed, in which case it is kept.
+    pub(crate) explicit_etag: bool,
+}
+
+impl ReadOptions {
+    /// Reads at most [`DEFAULT_MAX_FILE_SIZE`] bytes, detecting the mime type and hashing all of the data.
+    pub(crate) const DEFAULT: ReadOptions = ReadOptions {
+        max_bytes: DEFAULT_MAX_FILE_SIZE,
+        etag_algorithm: EtagAlgorithm::Full,
+        explicit_mime: false,
+        explicit_etag: false,
+    };
 }
 
 impl StdHttpFile {
@@ -30,6 +62,9 @@ impl StdHttpFile {
             data,
             mime,
             etag,
+            encoding: None,
+            modified: None,
+            options: ReadOptions::DEFAULT,
         }
     }
 
@@ -45,39 +80,244 @@ impl StdHttpFile {
             data,
             mime,
             etag: Cow::Owned(etag),
+            encoding: None,
+            modified: None,
+            options: ReadOptions::DEFAULT,
         }
     }
 
     /// Create a new [`StdHttpFile`] from a path.
     pub fn new(path: impl Into<Cow<'static, str>>) -> std::io::Result<Self> {
+        Self::new_with_overrides(path, &[])
+    }
+
+    /// Create a new [`StdHttpFile`] from a path, reading at most `max_bytes` bytes instead of [`DEFAULT_MAX_FILE_SIZE`].
+    ///
+    /// Returns an error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) if the path is not a regular file,
+    /// and of kind [`InvalidData`](std::io::ErrorKind::InvalidData) if the file is larger than `max_bytes`.
+    pub fn new_with_max_size(
+        path: impl Into<Cow<'static, str>>,
+        max_bytes: u64,
+    ) -> std::io::Result<Self> {
+        let path: Cow<'static, str> = path.into();
+        let (data, modified, etag) = read_file_hashed(path.as_ref().as_ref(), max_bytes)?;
+        let mime = crate::detect_mime_type(path.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME);
+        Ok(StdHttpFile {
+            file: path,
+            data: ByteData::from_shared(data),
+            mime: Cow::Borrowed(mime),
+            etag: Cow::Owned(etag),
+            encoding: None,
+            modified,
+            options: ReadOptions {
+                max_bytes,
+                ..ReadOptions::DEFAULT
+            },
+        })
+    }
+
+    /// Create a new [`StdHttpFile`] from a path, where the mime type is looked up in `overrides` before being detected.
+    /// The overrides are pairs of file extensions, without the leading dot, and mime types.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::StdHttpFile;
+    /// let file = StdHttpFile::new_with_overrides("Cargo.toml", &[("toml", "text/plain")]).unwrap();
+    /// assert_eq!(file.mime, "text/plain");
+    /// ```
+    pub fn new_with_overrides(
+        path: impl Into<Cow<'static, str>>,
+        overrides: &[(&str, &str)],
+    ) -> std::io::Result<Self> {
+        let path: Cow<'static, str> = path.into();
+        let (data, modified, etag) =
+            read_file_hashed(path.as_ref().as_ref(), DEFAULT_MAX_FILE_SIZE)?;
+        let ext = crate::file_ext(path.as_ref());
+        let overridden = overrides.iter().find(|(e, _)| Some(*e) == ext);
+        let mime = match overridden {
+            Some((_, mime)) => Cow::Owned(String::from(*mime)),
+            None => Cow::Borrowed(
+                crate::detect_mime_type(path.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME),
+            ),
+        };
+        Ok(StdHttpFile {
+            file: path,
+            data: ByteData::from_shared(data),
+            mime,
+            etag: Cow::Owned(etag),
+            encoding: None,
+            modified,
+            options: ReadOptions {
+                explicit_mime: overridden.is_some(),
+                ..ReadOptions::DEFAULT
+            },
+        })
+    }
+
+    /// Create a new [`StdHttpFile`] from a path, computing the etag with `algorithm` instead of hashing all of the data.
+    ///
+    /// [`EtagAlgorithm::Sampled`] only hashes the size and the sampled regions of the data, which is faster for huge files
+    /// at the cost of missing some changes, as described by [`EtagAlgorithm`]. The algorithm is also used by [`StdHttpFile::reload`].
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{EtagAlgorithm, StdHttpFile};
+    /// let algorithm = EtagAlgorithm::Sampled { prefix: 4096, suffix: 4096 };
+    /// let file = StdHttpFile::new_with_etag_algorithm("Cargo.toml", algorithm).unwrap();
+    /// assert_eq!(file.etag, algorithm.compute(file.data.as_slice()));
+    /// ```
+    pub fn new_with_etag_algorithm(
+        path: impl Into<Cow<'static, str>>,
+        algorithm: EtagAlgorithm,
+    ) -> std::io::Result<Self> {
         let path: Cow<'static, str> = path.into();
-        let data = read_file(path.as_ref().as_ref())?;
-        let mime =
-            crate::detect_mime_type(path.as_ref(), &data).unwrap_or("application/octet-data");
-        let etag = super::compute_etag_nonconst(&data);
+        let (data, modified, etag) =
+            read_file_etag(path.as_ref().as_ref(), DEFAULT_MAX_FILE_SIZE, algorithm)?;
+        let mime = crate::detect_mime_type(path.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME);
         Ok(StdHttpFile {
             file: path,
             data: ByteData::from_shared(data),
             mime: Cow::Borrowed(mime),
             etag: Cow::Owned(etag),
+            encoding: None,
+            modified,
+            options: ReadOptions {
+                etag_algorithm: algorithm,
+                ..ReadOptions::DEFAULT
+            },
         })
     }
 
+    /// Create a new [`StdHttpFile`] from a path with an explicit etag, such as a version tag, instead of one computed from the data.
+    /// The mime type is still detected from the path or data.
+    ///
+    /// Returns an error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) if the etag is not valid according to [`validate_etag`](crate::validate_etag).
+    pub fn new_with_etag(
+        path: impl Into<Cow<'static, str>>,
+        etag: impl Into<Cow<'static, str>>,
+    ) -> std::io::Result<Self> {
+        let etag = checked_etag(etag.into())?;
+        let path: Cow<'static, str> = path.into();
+        let (data, modified) = read_file_modified(path.as_ref().as_ref(), DEFAULT_MAX_FILE_SIZE)?;
+        let mime = crate::detect_mime_type(path.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME);
+        Ok(StdHttpFile {
+            file: path,
+            data: ByteData::from_shared(data),
+            mime: Cow::Borrowed(mime),
+            etag,
+            encoding: None,
+            modified,
+            options: ReadOptions {
+                explicit_etag: true,
+                ..ReadOptions::DEFAULT
+            },
+        })
+    }
+
+    /// Marks the etag as weak by prefixing it with `W/`, for files which are semantically equal even if their bytes differ,
+    /// such as generated files with embedded timestamps.
+    ///
+    /// Weak etags are only used for `If-None-Match`, while `If-Match` and `If-Range` require a strong etag.
+    pub fn with_weak_etag(mut self) -> Self {
+        if !self.etag.is_empty() && !self.etag.starts_with("W/") {
+            self.etag = Cow::Owned(format!("W/{}", self.etag));
+        }
+        self
+    }
+
     /// Create a new [`StdHttpFile`] from a file and explicit mime.
     pub fn new_with_mime(
         path: impl Into<Cow<'static, str>>,
         mime: impl Into<Cow<'static, str>>,
     ) -> std::io::Result<Self> {
         let path: Cow<'static, str> = path.into();
-        let data = read_file(path.as_ref().as_ref())?;
-        let etag = super::compute_etag_nonconst(&data);
+        let (data, modified, etag) =
+            read_file_hashed(path.as_ref().as_ref(), DEFAULT_MAX_FILE_SIZE)?;
         Ok(StdHttpFile {
             file: path,
             data: ByteData::from_shared(data),
             mime: mime.into(),
             etag: Cow::Owned(etag),
+            encoding: None,
+            modified,
+            options: ReadOptions {
+                explicit_mime: true,
+                ..ReadOptions::DEFAULT
+            },
         })
     }
+
+    /// Create a new [`StdHttpFile`] from a file with an explicit mime and charset, such as `text/html; charset=utf-8`.
+    ///
+    /// The charset is only added to textual mime types, as determined by [`is_text_mime`](crate::is_text_mime).
+    /// Binary types, such as `application/wasm` which must not have any parameters for `WebAssembly.instantiateStreaming`, are used as is.
+    pub fn new_with_mime_charset(
+        path: impl Into<Cow<'static, str>>,
+        mime: &str,
+        charset: &str,
+    ) -> std::io::Result<Self> {
+        if crate::is_text_mime(mime) {
+            Self::new_with_mime(path, format!("{}; charset={}", mime, charset))
+        } else {
+            Self::new_with_mime(path, String::from(mime))
+        }
+    }
+
+    /// Create a new [`StdHttpFile`] from a file that is already encoded using the content encoding `encoding`, such as `gzip`.
+    /// The mime should describe the decoded data.
+    pub fn new_encoded(
+        path: impl Into<Cow<'static, str>>,
+        mime: impl Into<Cow<'static, str>>,
+        encoding: impl Into<Cow<'static, str>>,
+    ) -> std::io::Result<Self> {
+        let mut file = Self::new_with_mime(path, mime)?;
+        file.encoding = Some(encoding.into());
+        Ok(file)
+    }
+
+    /// Checks if the file on disk has changed since it was read, by comparing both its size and modification time
+    /// without reading the data.
+    ///
+    /// If either modification time is unknown, such as for a file which was not read from the file system,
+    /// the file is reported as stale, as an unchanged size alone does not show that the data is unchanged.
+    pub fn is_stale(&self) -> std::io::Result<bool> {
+        let metadata = std::fs::metadata(&*self.file)?;
+        let modified = metadata.modified().ok();
+        Ok(metadata.len() != self.data.len() as u64
+            || modified.is_none()
+            || modified != self.modified)
+    }
+
+    /// Reads the file from disk again the same way it was created, using the same size limit and etag algorithm.
+    /// A mime type or etag which was given explicitly, such as to [`StdHttpFile::new_with_mime`] or [`StdHttpFile::new_with_etag`],
+    /// is kept, while a detected mime type is detected again and a computed etag is computed again.
+    /// A weak etag stays weak and the content encoding is kept.
+    ///
+    /// The file is left unchanged if reading fails.
+    pub fn reload(&mut self) -> std::io::Result<()> {
+        let options = self.options;
+        let path: &Path = self.file.as_ref().as_ref();
+        let (data, modified) = if options.explicit_etag {
+            read_file_modified(path, options.max_bytes)?
+        } else {
+            let (data, modified, etag) =
+                read_file_etag(path, options.max_bytes, options.etag_algorithm)?;
+            self.etag = if self.etag.starts_with("W/") {
+                Cow::Owned(format!("W/{}", etag))
+            } else {
+                Cow::Owned(etag)
+            };
+            (data, modified)
+        };
+        if !options.explicit_mime {
+            let mime =
+                crate::detect_mime_type(self.file.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME);
+            self.mime = Cow::Borrowed(mime);
+        }
+        self.data = ByteData::from_shared(data);
+        self.modified = modified;
+        Ok(())
+    }
 }
 
 impl HttpFile<'static> for StdHttpFile {
@@ -89,6 +329,15 @@ impl HttpFile<'static> for StdHttpFile {
         self.etag.as_ref()
     }
 
+    fn content_encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    fn last_modified(&self) -> Option<u64> {
+        let modified = self.modified?.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+        Some(modified.as_secs())
+    }
+
     fn data(&self) -> &[u8] {
         self.data.as_slice()
     }
@@ -104,24 +353,197 @@ impl HttpFile<'static> for StdHttpFile {
 
 impl HttpFileResponse<'static> for StdHttpFile {}
 
-fn read_file(path: &Path) -> std::io::Result<bytedata::SharedBytes> {
+/// Converts a string without copying it if it is static.
+fn cow_to_string_data(value: Cow<'static, str>) -> StringData<'static> {
+    match value {
+        Cow::Borrowed(value) => StringData::from_static(value),
+        Cow::Owned(value) => StringData::from(value),
+    }
+}
+
+impl From<StdHttpFile> for SharedHttpFile<'static> {
+    /// Converts a [`StdHttpFile`] without copying the data or any static strings.
+    fn from(file: StdHttpFile) -> Self {
+        let modified = file.last_modified();
+        let mut shared = SharedHttpFile::new_named(
+            file.data,
+            cow_to_string_data(file.mime),
+            cow_to_string_data(file.etag),
+            cow_to_string_data(file.file),
+        );
+        shared.encoding = file.encoding.map(cow_to_string_data);
+        shared.modified = modified;
+        shared
+    }
+}
+
+/// Converts a string without copying it if it is static, the reverse of [`cow_to_string_data`].
+fn string_data_to_cow(value: StringData<'static>) -> Cow<'static, str> {
+    match value.as_static() {
+        Some(value) => Cow::Borrowed(value),
+        None => Cow::Owned(String::from(value.as_str())),
+    }
+}
+
+impl From<SharedHttpFile<'static>> for StdHttpFile {
+    /// Converts a [`SharedHttpFile`] without copying the data or any static strings.
+    /// As the lifetime guarantees that nothing is borrowed for a shorter time, the conversion cannot fail.
+    /// A file without a name gets an empty name.
+    ///
+    /// A [`StdHttpFile`] has no cache busting, so the [`CacheBusting`](crate::CacheBusting) of the file is dropped.
+    /// Wrap the converted file in a [`QueryCacheBustedHttpFile`](crate::QueryCacheBustedHttpFile)
+    /// or [`SuffixCacheBustedHttpFile`](crate::SuffixCacheBustedHttpFile) to keep it.
+    fn from(file: SharedHttpFile<'static>) -> Self {
+        StdHttpFile {
+            file: file.file.map_or(Cow::Borrowed(""), string_data_to_cow),
+            data: file.data,
+            mime: string_data_to_cow(file.mime),
+            etag: string_data_to_cow(file.etag),
+            encoding: file.encoding.map(string_data_to_cow),
+            modified: file
+                .modified
+                .map(|secs| SystemTime::UNIX_EPOCH + core::time::Duration::from_secs(secs)),
+            options: ReadOptions::DEFAULT,
+        }
+    }
+}
+
+pub(crate) fn checked_etag(etag: Cow<'static, str>) -> std::io::Result<Cow<'static, str>> {
+    if crate::validate_etag(&etag) {
+        Ok(etag)
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "etag must be empty or a quoted string",
+        ))
+    }
+}
+
+pub(super) fn read_file(path: &Path) -> std::io::Result<bytedata::SharedBytes> {
+    read_file_limited(path, DEFAULT_MAX_FILE_SIZE)
+}
+
+pub(super) fn read_file_limited(
+    path: &Path,
+    max_bytes: u64,
+) -> std::io::Result<bytedata::SharedBytes> {
+    read_file_modified(path, max_bytes).map(|(data, _)| data)
+}
+
+/// Reads a file along with its modification time, if the platform supports it.
+fn read_file_modified(
+    path: &Path,
+    max_bytes: u64,
+) -> std::io::Result<(bytedata::SharedBytes, Option<SystemTime>)> {
     let mut builder = bytedata::SharedBytesBuilder::new();
-    read_file_into(path, &mut builder)?;
-    Ok(builder.build())
+    let modified = read_file_into(path, &mut builder, max_bytes, None)?;
+    Ok((builder.build(), modified))
 }
 
-fn read_file_into(path: &Path, builder: &mut bytedata::SharedBytesBuilder) -> std::io::Result<()> {
-    use bytes_1::BufMut;
+/// Reads a file along with its modification time like `read_file_modified`,
+/// and the etag of the data which is computed while reading instead of in a second pass over the data.
+pub(crate) fn read_file_hashed(
+    path: &Path,
+    max_bytes: u64,
+) -> std::io::Result<(bytedata::SharedBytes, Option<SystemTime>, String)> {
+    let mut builder = bytedata::SharedBytesBuilder::new();
+    let mut hasher = EtagHasher::new();
+    let modified = read_file_into(path, &mut builder, max_bytes, Some(&mut hasher))?;
+    Ok((builder.build(), modified, hasher.finish()))
+}
+
+/// Reads a file along with its modification time and the etag computed using `algorithm`,
+/// where the data is hashed while reading for [`EtagAlgorithm::Full`].
+fn read_file_etag(
+    path: &Path,
+    max_bytes: u64,
+    algorithm: EtagAlgorithm,
+) -> std::io::Result<(bytedata::SharedBytes, Option<SystemTime>, String)> {
+    match algorithm {
+        EtagAlgorithm::Full => read_file_hashed(path, max_bytes),
+        EtagAlgorithm::Sampled { .. } => {
+            let (data, modified) = read_file_modified(path, max_bytes)?;
+            let etag = algorithm.compute(&data);
+            Ok((data, modified, etag))
+        }
+    }
+}
+
+/// Rejects anything but regular files, as reading a FIFO or a device such as `/dev/zero` may never end,
+/// and files which are already known to exceed the limit.
+pub(crate) fn check_file_metadata(
+    metadata: &std::fs::Metadata,
+    max_bytes: u64,
+) -> std::io::Result<()> {
+    if !metadata.is_file() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "not a regular file",
+        ));
+    }
+    if metadata.len() > max_bytes {
+        return Err(file_too_large(max_bytes));
+    }
+    Ok(())
+}
+
+pub(crate) fn invalid_read_length() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "reader returned more bytes than the buffer holds",
+    )
+}
+
+pub(crate) fn file_too_large(max_bytes: u64) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("file is larger than the maximum of {} bytes", max_bytes),
+    )
+}
+
+fn read_file_into(
+    path: &Path,
+    builder: &mut bytedata::SharedBytesBuilder,
+    max_bytes: u64,
+    hasher: Option<&mut EtagHasher>,
+) -> std::io::Result<Option<SystemTime>> {
     use std::io::Read;
-    let mut file = File::open(path)?;
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    check_file_metadata(&metadata, max_bytes)?;
+    // the file may grow after the metadata was read, so one byte past the limit is read to detect it
+    let total = read_into(file.take(max_bytes.saturating_add(1)), builder, hasher)?;
+    if total > max_bytes {
+        return Err(file_too_large(max_bytes));
+    }
+    Ok(metadata.modified().ok())
+}
+
+/// Reads all of the data of a reader into the builder, returning the number of bytes read.
+/// If a hasher is given, each chunk is fed into it as it is read, like [`compute_etag_streaming`](super::compute_etag_streaming) does.
+///
+/// The reader may be provided by the user, so it is only ever given initialized memory to read into.
+pub(crate) fn read_into<R: std::io::Read>(
+    mut reader: R,
+    builder: &mut bytedata::SharedBytesBuilder,
+    mut hasher: Option<&mut EtagHasher>,
+) -> std::io::Result<u64> {
+    use bytes_1::BufMut;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut total = 0u64;
     loop {
-        let buf = builder.chunk_mut();
-        let n =
-            file.read(unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr(), buf.len()) })?;
-        if n == 0 {
-            break;
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) if n > buf.len() => return Err(invalid_read_length()),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if let Some(hasher) = hasher.as_deref_mut() {
+            hasher.update(&buf[..n]);
         }
-        unsafe { builder.advance_mut(n) };
+        builder.put_slice(&buf[..n]);
+        total += n as u64;
     }
-    Ok(())
+    Ok(total)
 }
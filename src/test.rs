@@ -1,3 +1,5 @@
+use crate::{HttpFile, HttpFileResponse};
+
 #[test]
 fn test_detect_mime_type_ext() {
     use crate::detect_mime_type_ext;
@@ -54,7 +56,7 @@ fn test_const_http_file() {
     use crate::const_http_file;
 
     let file = const_http_file!("../.gitignore");
-    assert_eq!(file.mime, "application/octet-data");
+    assert_eq!(file.mime, "application/octet-stream");
     assert_eq!(file.etag.len(), 12);
     assert_eq!(file.data.len(), 20);
 
@@ -65,3 +67,4536 @@ fn test_const_http_file() {
     assert_eq!(file1.etag, file.etag);
     assert_eq!(file1.data, file.data);
 }
+
+#[test]
+fn test_etag_seeded() {
+    use crate::{compute_etag, compute_etag_seeded_const, const_etag, const_etag_seeded};
+
+    const DEFAULT: &str = const_etag!(b"foo");
+    const SEEDED_A: &str = const_etag_seeded!(b"foo", 1);
+    const SEEDED_B: &str = const_etag_seeded!(b"foo", 2);
+    assert_eq!(DEFAULT, "\"q25fZAd-fY\"");
+    assert_eq!(const_etag_seeded!(b"foo", 0), DEFAULT);
+    assert_ne!(SEEDED_A, DEFAULT);
+    assert_ne!(SEEDED_A, SEEDED_B);
+    assert_eq!(SEEDED_A.len(), 12);
+    assert!(crate::validate_etag(SEEDED_A));
+    assert_eq!(compute_etag_seeded_const(b"bar", 0), compute_etag(b"bar"));
+
+    #[cfg(feature = "std")]
+    {
+        use crate::compute_etag_seeded;
+        assert_eq!(compute_etag_seeded(b"foo", 1), SEEDED_A);
+        assert_eq!(compute_etag_seeded(b"foo", 2), SEEDED_B);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_etag_algorithm_sampled() {
+    use crate::{compute_etag_nonconst, EtagAlgorithm};
+    use std::io::Cursor;
+
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let sampled = EtagAlgorithm::Sampled {
+        prefix: 1024,
+        suffix: 1024,
+    };
+    let etag = sampled.compute(&data);
+    assert_eq!(sampled.compute_seekable(Cursor::new(&data)).unwrap(), etag);
+    assert_ne!(etag, compute_etag_nonconst(&data));
+
+    // changes within the sampled regions or to the size change the etag
+    for i in [0, 1023, data.len() - 1024, data.len() - 1] {
+        let mut changed = data.clone();
+        changed[i] ^= 0xFF;
+        assert_ne!(sampled.compute(&changed), etag, "change at {}", i);
+        assert_eq!(
+            sampled.compute_seekable(Cursor::new(&changed)).unwrap(),
+            sampled.compute(&changed)
+        );
+    }
+    let mut longer = data.clone();
+    longer.push(0);
+    assert_ne!(sampled.compute(&longer), etag);
+
+    // changes between the sampled regions are not detected
+    let mut changed = data.clone();
+    changed[1024] ^= 0xFF;
+    changed[50_000] ^= 0xFF;
+    assert_eq!(sampled.compute(&changed), etag);
+
+    // overlapping regions cover the data once
+    let short = b"0123456789";
+    let overlapping = EtagAlgorithm::Sampled {
+        prefix: 8,
+        suffix: 8,
+    };
+    assert_eq!(
+        overlapping.compute_seekable(Cursor::new(short)).unwrap(),
+        overlapping.compute(short)
+    );
+    assert_ne!(
+        overlapping.compute(short),
+        overlapping.compute(b"0123456780")
+    );
+
+    assert_eq!(EtagAlgorithm::default(), EtagAlgorithm::Full);
+    assert_eq!(
+        EtagAlgorithm::Full
+            .compute_seekable(Cursor::new(&data))
+            .unwrap(),
+        compute_etag_nonconst(&data)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_compute_etag_streaming() {
+    use crate::{compute_etag_nonconst, compute_etag_streaming};
+
+    /// A reader returning the data in uneven chunks.
+    struct Chunked<'a>(&'a [u8], usize);
+
+    impl std::io::Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.1 = self.1 % 8191 + 1;
+            let n = self.1.min(buf.len()).min(self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    let data: Vec<u8> = (0..3 * 1024 * 1024 + 17u32)
+        .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+        .collect();
+    let expected = compute_etag_nonconst(&data);
+    assert_eq!(compute_etag_streaming(&data[..]).unwrap(), expected);
+    assert_eq!(compute_etag_streaming(Chunked(&data, 0)).unwrap(), expected);
+    assert_eq!(
+        compute_etag_streaming(std::io::empty()).unwrap(),
+        compute_etag_nonconst(b"")
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_lazy_etag_http_file() {
+    use crate::{HttpFile, LazyEtagHttpFile};
+    use bytedata::ByteData;
+
+    let file = LazyEtagHttpFile::new_with_mime_data(
+        "foo.txt".into(),
+        "text/plain".into(),
+        ByteData::from_static(b"foo"),
+    );
+    assert!(!file.is_etag_computed());
+    assert_eq!(file.content_type(), "text/plain");
+    assert_eq!(file.data(), b"foo");
+    assert!(!file.is_etag_computed());
+
+    let etag = file.etag();
+    assert_eq!(etag, "\"q25fZAd-fY\"");
+    assert!(file.is_etag_computed());
+    // the cached etag is reused instead of being computed again
+    assert_eq!(file.etag().as_ptr(), etag.as_ptr());
+    assert_eq!(file.etag_str(), "q25fZAd-fY");
+    assert_eq!(file.etag_header_value(), etag);
+
+    let file = LazyEtagHttpFile::new("Cargo.toml").unwrap();
+    assert!(!file.is_etag_computed());
+    assert_eq!(file.etag(), crate::compute_etag_nonconst(file.data()));
+}
+
+#[test]
+fn test_detect_mime_type_before() {
+    use crate::const_mime::detect_mime_type_xml;
+    use crate::detect_mime_type_magic;
+
+    // tiny buffers must neither panic nor match magics longer than the data
+    assert_eq!(detect_mime_type_xml(b""), None);
+    assert_eq!(detect_mime_type_xml(b"<"), None);
+    assert_eq!(detect_mime_type_xml(b"<s"), None);
+    assert_eq!(detect_mime_type_xml(b"<sv"), None);
+    assert_eq!(detect_mime_type_xml(b"svg"), None);
+
+    // a magic filling the whole buffer is tested exactly once at offset 0
+    assert_eq!(detect_mime_type_xml(b"<svg"), Some("image/svg+xml"));
+    assert_eq!(detect_mime_type_xml(b"<sva"), None);
+    assert_eq!(detect_mime_type_xml(b" <svg"), Some("image/svg+xml"));
+
+    // the last position where the magic fits is included
+    assert_eq!(detect_mime_type_magic(b"<?xml<svg"), Some("image/svg+xml"));
+    assert_eq!(detect_mime_type_magic(b"<?xml<sv"), Some("text/xml"));
+
+    // magics starting at or after the offset limit are ignored
+    let mut late = [b' '; 50];
+    late[..5].copy_from_slice(b"<?xml");
+    late[46..].copy_from_slice(b"<svg");
+    assert_eq!(detect_mime_type_magic(&late), Some("text/xml"));
+    late[45..49].copy_from_slice(b"<svg");
+    assert_eq!(detect_mime_type_magic(&late), Some("image/svg+xml"));
+}
+
+#[test]
+fn test_detect_mime_type_sfnt() {
+    use crate::detect_mime_type_magic;
+
+    // sfnt version followed by numTables and searchRange
+    assert_eq!(
+        detect_mime_type_magic(b"\0\x01\0\0\0\x0F\0\x80\0\x03\0\x70"),
+        Some("font/ttf")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"OTTO\0\x0C\0\x80\0\x03\0\x40"),
+        Some("font/otf")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"true\0\x0F\0\x80"),
+        Some("font/ttf")
+    );
+    assert_eq!(detect_mime_type_magic(b"\0\x01\0"), None);
+}
+
+#[test]
+fn test_detect_mime_type_riff() {
+    use crate::detect_mime_type_magic;
+
+    assert_eq!(
+        detect_mime_type_magic(b"RIFF\x1A\0\0\0WEBPVP8 \x0E\0\0\0"),
+        Some("image/webp")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"RIFF\x1A\0\0\0WEBPVP8L\x0E\0\0\0"),
+        Some("image/webp")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"RIFF\x1A\0\0\0WEBPVP8X\x0A\0\0\0"),
+        Some("image/webp")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"RIFF\x1A\0\0\0WAVEfmt "),
+        Some("audio/wav")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"RIFF\x1A\0\0\0AVI LIST"),
+        Some("video/x-msvideo")
+    );
+
+    // truncated headers must not read out of bounds
+    let webp = b"RIFF\x1A\0\0\0WEBPVP8L";
+    for len in 0..webp.len() {
+        let expected = if len >= 12 { Some("image/webp") } else { None };
+        assert_eq!(detect_mime_type_magic(&webp[..len]), expected);
+    }
+}
+
+#[test]
+fn test_detect_mime_type_bom() {
+    use crate::detect_mime_type_magic;
+
+    assert_eq!(
+        detect_mime_type_magic(b"\xEF\xBB\xBF<!DOCTYPE html><html></html>"),
+        Some("text/html")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"\xEF\xBB\xBF<?xml version=\"1.0\"?><root/>"),
+        Some("text/xml")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"\xEF\xBB\xBF<?xml version=\"1.0\"?><svg></svg>"),
+        Some("image/svg+xml")
+    );
+    assert_eq!(
+        detect_mime_type_magic(b"\xEF\xBB\xBF<svg></svg>"),
+        Some("image/svg+xml")
+    );
+    assert_eq!(detect_mime_type_magic(b"<html></html>"), Some("text/html"));
+
+    // binary formats are never preceded by a byte order mark
+    assert_eq!(detect_mime_type_magic(b"\xEF\xBB\xBF%PDF-1.7"), None);
+    assert_eq!(detect_mime_type_magic(b"\xEF\xBB\xBF"), None);
+    assert_eq!(detect_mime_type_magic(b"\xEF\xBB"), None);
+}
+
+#[test]
+fn test_is_text_mime() {
+    use crate::is_text_mime;
+
+    assert!(is_text_mime("text/plain"));
+    assert!(is_text_mime("text/html; charset=utf-8"));
+    assert!(is_text_mime("text/css ;charset=utf-8"));
+    assert!(is_text_mime("application/json"));
+    assert!(is_text_mime("application/javascript"));
+    assert!(is_text_mime("application/xml"));
+    assert!(is_text_mime("application/x-yaml"));
+    assert!(is_text_mime("application/toml"));
+    assert!(is_text_mime("application/manifest+json"));
+    assert!(is_text_mime("image/svg+xml"));
+
+    assert!(!is_text_mime(crate::DEFAULT_MIME));
+    assert!(!is_text_mime("image/png"));
+    assert!(!is_text_mime("application/wasm"));
+    assert!(!is_text_mime("application/zip"));
+    assert!(!is_text_mime("font/woff2"));
+    assert!(!is_text_mime("application/jsonx"));
+    assert!(!is_text_mime(""));
+}
+
+#[test]
+fn test_known_extensions() {
+    use crate::{detect_mime_type_ext, known_extensions, MimeType, KNOWN_EXTENSIONS};
+    use alloc::format;
+
+    for (i, (ext, mime)) in known_extensions().enumerate() {
+        assert!(
+            !KNOWN_EXTENSIONS[..i].iter().any(|(e, _)| e == &ext),
+            "duplicate extension {}",
+            ext
+        );
+        assert_eq!(detect_mime_type_ext(&format!("file.{}", ext)), Some(mime));
+        assert!(!matches!(MimeType::from_static(mime), MimeType::Custom(_)));
+    }
+    assert_eq!(known_extensions().count(), KNOWN_EXTENSIONS.len());
+    assert_eq!(detect_mime_type_ext("file.unknown"), None);
+    assert_eq!(detect_mime_type_ext("file"), None);
+}
+
+#[test]
+fn test_exts_for_mime() {
+    use crate::{ext_for_mime, exts_for_mime, known_extensions};
+    use alloc::vec::Vec;
+
+    assert_eq!(
+        exts_for_mime("image/jpeg").collect::<Vec<_>>(),
+        ["jpg", "jpeg"]
+    );
+    assert_eq!(
+        exts_for_mime("Audio/MIDI").collect::<Vec<_>>(),
+        ["mid", "midi"]
+    );
+    assert_eq!(
+        exts_for_mime("application/javascript; charset=utf-8").collect::<Vec<_>>(),
+        ["js", "mjs"]
+    );
+    assert_eq!(exts_for_mime("image/png").collect::<Vec<_>>(), ["png"]);
+    assert_eq!(exts_for_mime("image/x-unknown").next(), None);
+    assert_eq!(ext_for_mime("image/tiff"), Some("tiff"));
+    assert_eq!(ext_for_mime("image/x-unknown"), None);
+
+    for (ext, mime) in known_extensions() {
+        assert!(exts_for_mime(mime).any(|e| e == ext));
+    }
+}
+
+#[test]
+fn test_detect_mime_type_detailed() {
+    use crate::{detect_mime_type, detect_mime_type_detailed, MimeSource};
+
+    const PNG: &[u8] = b"%PNG\x0D\x0A\x1A\x0A";
+    assert_eq!(
+        detect_mime_type_detailed("image.png", PNG),
+        Some((MimeSource::Extension, "image/png"))
+    );
+    assert_eq!(
+        detect_mime_type_detailed("image", PNG),
+        Some((MimeSource::Magic, "image/png"))
+    );
+    // the extension takes precedence even if the data disagrees
+    assert_eq!(
+        detect_mime_type_detailed("page.txt", b"<!DOCTYPE html>"),
+        Some((MimeSource::Extension, "text/plain"))
+    );
+    assert_eq!(
+        detect_mime_type_detailed("page.unknown", b"<!DOCTYPE html>"),
+        Some((MimeSource::Magic, "text/html"))
+    );
+    assert_eq!(detect_mime_type_detailed("file", b"\0\0"), None);
+
+    for (path, data) in [("a.css", &b""[..]), ("a", PNG), ("a", b"")] {
+        assert_eq!(
+            detect_mime_type_detailed(path, data).map(|(_, mime)| mime),
+            detect_mime_type(path, data)
+        );
+    }
+}
+
+#[test]
+fn test_mime_type_enum() {
+    use crate::{detect_mime_type_enum, MimeType};
+
+    const PNG: Option<MimeType> = detect_mime_type_enum("image.png", b"");
+    assert_eq!(PNG, Some(MimeType::ImagePng));
+    assert_eq!(
+        detect_mime_type_enum("unknown", b"%PDF-1.7"),
+        Some(MimeType::ApplicationPdf)
+    );
+    assert_eq!(
+        detect_mime_type_enum("unknown", b"PK\x03\x04"),
+        Some(MimeType::ApplicationZip)
+    );
+    assert_eq!(detect_mime_type_enum("unknown", b""), None);
+    assert_eq!(MimeType::ImagePng.as_str(), "image/png");
+    // the string tables are derived from the enum
+    for (ext, mime) in crate::KNOWN_EXTENSIONS {
+        let path = alloc::format!("file.{}", ext);
+        assert_eq!(
+            detect_mime_type_enum(&path, b"").map(|m| m.as_str()),
+            Some(*mime)
+        );
+    }
+    assert_eq!(
+        MimeType::from_static(crate::DEFAULT_MIME),
+        MimeType::ApplicationOctetStream
+    );
+    assert_eq!(
+        MimeType::from_static("text/x-custom"),
+        MimeType::Custom("text/x-custom")
+    );
+    assert_eq!(MimeType::Custom("text/x-custom").as_str(), "text/x-custom");
+}
+
+#[test]
+fn test_mime_type_conversions() {
+    use crate::{detect_mime_type_enum, MimeType, UnknownMimeType};
+    use alloc::string::ToString;
+
+    assert_eq!(MimeType::ImageSvgXml.to_string(), "image/svg+xml");
+    assert_eq!(
+        MimeType::Custom("text/x-custom").to_string(),
+        "text/x-custom"
+    );
+    assert_eq!(AsRef::<str>::as_ref(&MimeType::TextCss), "text/css");
+    assert!(detect_mime_type_enum("image.png", b"").unwrap() == "image/png");
+    assert!(MimeType::TextCss != "text/plain");
+
+    assert_eq!("application/wasm".parse(), Ok(MimeType::ApplicationWasm));
+    assert_eq!("".parse::<MimeType>(), Err(UnknownMimeType));
+    assert_eq!("text/x-custom".parse::<MimeType>(), Err(UnknownMimeType));
+    assert_eq!(MimeType::from_static(""), MimeType::Custom(""));
+}
+
+#[test]
+fn test_const_http_file_basename() {
+    use crate::{const_http_file, file_basename, ConstHttpFile};
+
+    const FILE: ConstHttpFile = const_http_file!("../.gitignore");
+    const BASENAME: Option<&str> = FILE.basename();
+    assert_eq!(FILE.file, Some("../.gitignore"));
+    assert_eq!(BASENAME, Some(".gitignore"));
+    assert_eq!(
+        const_http_file!(b"foo" as &[u8], "text/plain").basename(),
+        None
+    );
+
+    assert_eq!(file_basename("app.js"), Some("app.js"));
+    assert_eq!(file_basename("/static/app.js"), Some("app.js"));
+    assert_eq!(file_basename("static\\app.js"), Some("app.js"));
+    assert_eq!(file_basename("/"), None);
+    assert_eq!(file_basename(""), None);
+}
+
+#[test]
+fn test_is_dev_artifact() {
+    use crate::is_dev_artifact;
+
+    assert!(is_dev_artifact("app.js.map"));
+    assert!(is_dev_artifact("/static/app.css.map"));
+    assert!(is_dev_artifact("src/main.ts"));
+    assert!(is_dev_artifact("types/index.d.ts"));
+    assert!(is_dev_artifact("components/App.tsx"));
+    assert!(is_dev_artifact("styles/main.scss"));
+    assert!(is_dev_artifact("styles/theme.less"));
+    assert!(is_dev_artifact("app.test.js"));
+    assert!(is_dev_artifact("src\\utils.test.tsx"));
+
+    assert!(!is_dev_artifact("app.js"));
+    assert!(!is_dev_artifact("styles/main.css"));
+    assert!(!is_dev_artifact("index.html"));
+    assert!(!is_dev_artifact("sitemap.xml"));
+    assert!(!is_dev_artifact("latest.js"));
+    assert!(!is_dev_artifact("test/app.js"));
+    assert!(!is_dev_artifact("app.test"));
+    assert!(!is_dev_artifact("maps/"));
+    assert!(!is_dev_artifact(""));
+}
+
+#[test]
+fn test_default_mime() {
+    use crate::{ConstHttpFile, SharedHttpFile};
+
+    assert_eq!(crate::DEFAULT_MIME, "application/octet-stream");
+    assert_eq!(ConstHttpFile::default().mime, "application/octet-stream");
+    assert_eq!(
+        SharedHttpFile::default().mime.as_str(),
+        "application/octet-stream"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_shared_http_file_from_data() {
+    use crate::{HttpFile, SharedHttpFile};
+    use bytedata::ByteData;
+
+    let file = SharedHttpFile::from_data(ByteData::from_static(b"foo"), Some("foo.txt"));
+    assert_eq!(file.content_type(), "text/plain");
+    assert_eq!(file.etag(), "\"q25fZAd-fY\"");
+    assert_eq!(file.etag_str(), "q25fZAd-fY");
+    assert_eq!(file.file.as_ref().map(|f| f.as_str()), Some("foo.txt"));
+
+    let file = SharedHttpFile::from_data(ByteData::from_static(b"<html></html>"), None);
+    assert_eq!(file.content_type(), "text/html");
+    assert!(file.file.is_none());
+
+    let file = SharedHttpFile::from_data(ByteData::from_static(b"foo"), None);
+    assert_eq!(file.content_type(), "application/octet-stream");
+}
+
+fn zip_with_entries(names: &[&str]) -> alloc::vec::Vec<u8> {
+    let mut zip = alloc::vec::Vec::new();
+    // a stub local file header, enough to be recognized as a zip file
+    zip.extend_from_slice(b"PK\x03\x04");
+    zip.extend_from_slice(&[0; 26]);
+    let cd_offset = zip.len();
+    for name in names {
+        zip.extend_from_slice(b"PK\x01\x02");
+        zip.extend_from_slice(&[0; 24]);
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0; 16]);
+        zip.extend_from_slice(name.as_bytes());
+    }
+    let cd_size = zip.len() - cd_offset;
+    zip.extend_from_slice(b"PK\x05\x06");
+    zip.extend_from_slice(&[0; 4]);
+    zip.extend_from_slice(&(names.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(names.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(cd_size as u32).to_le_bytes());
+    zip.extend_from_slice(&(cd_offset as u32).to_le_bytes());
+    zip.extend_from_slice(&[0; 2]);
+    zip
+}
+
+#[test]
+fn test_detect_mime_type_magic_zip() {
+    use crate::detect_mime_type_magic;
+
+    let zip = zip_with_entries(&["index.html", "style.css"]);
+    assert_eq!(detect_mime_type_magic(&zip), Some("application/zip"));
+
+    let apk = zip_with_entries(&[
+        "AndroidManifest.xml",
+        "META-INF/MANIFEST.MF",
+        "classes.dex",
+        "resources.arsc",
+    ]);
+    assert_eq!(
+        detect_mime_type_magic(&apk),
+        Some("application/vnd.android.package-archive")
+    );
+
+    let jar = zip_with_entries(&["META-INF/", "META-INF/MANIFEST.MF", "Main.class"]);
+    assert_eq!(
+        detect_mime_type_magic(&jar),
+        Some("application/java-archive")
+    );
+
+    // entry names must match exactly
+    let not_jar = zip_with_entries(&["META-INF/MANIFEST.MF.bak"]);
+    assert_eq!(detect_mime_type_magic(&not_jar), Some("application/zip"));
+
+    // a central directory offset past the end of the data is not followed
+    let mut bad_offset = zip_with_entries(&["META-INF/MANIFEST.MF"]);
+    let len = bad_offset.len();
+    bad_offset[len - 6..len - 2].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(detect_mime_type_magic(&bad_offset), Some("application/zip"));
+
+    // a truncated archive without a central directory is still a zip
+    assert_eq!(
+        detect_mime_type_magic(b"PK\x03\x04"),
+        Some("application/zip")
+    );
+}
+
+#[test]
+fn test_parse_ranges() {
+    use crate::{parse_ranges, RangeParseResult};
+    use alloc::vec;
+
+    let ranges = |ranges: &[(u64, u64)]| RangeParseResult::Ranges(ranges.to_vec());
+
+    // bounded ranges
+    assert_eq!(parse_ranges("bytes=0-9", 100), ranges(&[(0, 9)]));
+    assert_eq!(parse_ranges("bytes=10-10", 100), ranges(&[(10, 10)]));
+    assert_eq!(parse_ranges("bytes=90-199", 100), ranges(&[(90, 99)]));
+    assert_eq!(parse_ranges("Bytes=0-0", 1), ranges(&[(0, 0)]));
+
+    // open-ended ranges
+    assert_eq!(parse_ranges("bytes=0-", 100), ranges(&[(0, 99)]));
+    assert_eq!(parse_ranges("bytes=50-", 100), ranges(&[(50, 99)]));
+    assert_eq!(parse_ranges("bytes=99-", 100), ranges(&[(99, 99)]));
+
+    // suffix ranges
+    assert_eq!(parse_ranges("bytes=-10", 100), ranges(&[(90, 99)]));
+    assert_eq!(parse_ranges("bytes=-500", 100), ranges(&[(0, 99)]));
+    assert_eq!(
+        parse_ranges("bytes=-0", 100),
+        RangeParseResult::Unsatisfiable
+    );
+    assert_eq!(
+        parse_ranges("bytes=-10", 0),
+        RangeParseResult::Unsatisfiable
+    );
+
+    // multiple ranges, including whitespace and empty list elements
+    assert_eq!(
+        parse_ranges("bytes=0-9, 20-29,-5", 100),
+        RangeParseResult::Ranges(vec![(0, 9), (20, 29), (95, 99)])
+    );
+    assert_eq!(parse_ranges("bytes= 0-9 ,, \t200-", 100), ranges(&[(0, 9)]));
+
+    // unsatisfiable ranges
+    assert_eq!(
+        parse_ranges("bytes=100-", 100),
+        RangeParseResult::Unsatisfiable
+    );
+    assert_eq!(
+        parse_ranges("bytes=100-200", 100),
+        RangeParseResult::Unsatisfiable
+    );
+    assert_eq!(parse_ranges("bytes=0-", 0), RangeParseResult::Unsatisfiable);
+    assert_eq!(
+        parse_ranges("bytes=18446744073709551616-", 100),
+        RangeParseResult::Unsatisfiable
+    );
+
+    // malformed headers
+    assert_eq!(parse_ranges("", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes=", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes=,", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("items=0-9", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes=9-0", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes=-", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes=5", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes=a-9", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes=0-9,x", 100), RangeParseResult::Ignore);
+    assert_eq!(parse_ranges("bytes=+1-9", 100), RangeParseResult::Ignore);
+}
+
+#[test]
+fn test_weak_etag() {
+    use crate::{
+        const_etag_matches, const_etag_matches_weak, HttpFile, HttpFileResponse, SharedHttpFile,
+    };
+    use bytedata::{ByteData, StringData};
+
+    const STRONG: &str = "\"q25fZAd-fY\"";
+    const WEAK: &str = "W/\"q25fZAd-fY\"";
+    assert!(const_etag_matches(STRONG, STRONG));
+    assert!(!const_etag_matches(STRONG, WEAK));
+    assert!(!const_etag_matches(WEAK, STRONG));
+    assert!(!const_etag_matches(WEAK, WEAK));
+    assert!(const_etag_matches(WEAK, "*"));
+    assert!(const_etag_matches_weak(STRONG, WEAK));
+    assert!(const_etag_matches_weak(WEAK, STRONG));
+    assert!(const_etag_matches_weak(WEAK, "\"other\", W/\"q25fZAd-fY\""));
+    assert!(!const_etag_matches_weak(WEAK, "W/\"other\""));
+    assert!(!const_etag_matches("", ""));
+    assert!(!const_etag_matches_weak("", ""));
+    assert!(!const_etag_matches_weak("", "\"other\", "));
+
+    let file = SharedHttpFile::new(
+        ByteData::from_static(b"foo"),
+        StringData::from_static("text/plain"),
+        StringData::from_static(STRONG),
+    )
+    .with_weak_etag();
+    assert_eq!(file.etag(), WEAK);
+    assert_eq!(file.clone().with_weak_etag().etag(), WEAK);
+
+    let request = |name: http::header::HeaderName, value: &str, range: bool| {
+        let mut builder = http::Request::builder().uri("/foo.txt").header(name, value);
+        if range {
+            builder = builder.header(http::header::RANGE, "bytes=1-");
+        }
+        builder.body(()).unwrap()
+    };
+    let status = |file: &SharedHttpFile, request: http::Request<()>| {
+        file.respond_borrowed::<ByteData>(&request)
+            .unwrap()
+            .status()
+    };
+
+    // If-None-Match uses the weak comparison
+    let res = file
+        .respond_borrowed::<ByteData>(&request(http::header::IF_NONE_MATCH, STRONG, false))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers()[http::header::ETAG], WEAK);
+    assert_eq!(
+        status(&file, request(http::header::IF_NONE_MATCH, WEAK, false)),
+        http::StatusCode::NOT_MODIFIED
+    );
+    assert_eq!(
+        status(
+            &file,
+            request(http::header::IF_NONE_MATCH, "W/\"other\"", false)
+        ),
+        http::StatusCode::OK
+    );
+
+    // If-Match uses the strong comparison
+    assert_eq!(
+        status(&file, request(http::header::IF_MATCH, WEAK, false)),
+        http::StatusCode::PRECONDITION_FAILED
+    );
+    assert_eq!(
+        status(&file, request(http::header::IF_MATCH, STRONG, false)),
+        http::StatusCode::PRECONDITION_FAILED
+    );
+    assert_eq!(
+        status(&file, request(http::header::IF_MATCH, "*", false)),
+        http::StatusCode::OK
+    );
+
+    // If-Range never matches a weak etag, so the full data is sent
+    let res = file
+        .respond_borrowed::<ByteData>(&request(http::header::IF_RANGE, WEAK, true))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.body().as_slice(), b"foo");
+    let res = file
+        .respond_borrowed::<ByteData>(&request(http::header::IF_MATCH, "*", true))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.body().as_slice(), b"oo");
+
+    let strong = SharedHttpFile::new(
+        ByteData::from_static(b"foo"),
+        StringData::from_static("text/plain"),
+        StringData::from_static(STRONG),
+    );
+    assert_eq!(
+        status(&strong, request(http::header::IF_NONE_MATCH, WEAK, false)),
+        http::StatusCode::NOT_MODIFIED
+    );
+    assert_eq!(
+        status(&strong, request(http::header::IF_RANGE, STRONG, true)),
+        http::StatusCode::PARTIAL_CONTENT
+    );
+}
+
+#[test]
+fn test_data_range() {
+    use crate::{const_http_file, ConstHttpFile, HttpFile, SharedHttpFile};
+    use bytedata::{ByteData, StringData};
+
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+    assert_eq!(FILE.data_range(0..10).unwrap().as_slice(), b"0123456789");
+    assert_eq!(FILE.data_range(2..5).unwrap().as_slice(), b"234");
+    assert!(FILE.data_range(4..4).unwrap().is_empty());
+    assert!(FILE.data_range(10..10).unwrap().is_empty());
+    assert!(FILE.data_range(0..11).is_none());
+    assert!(FILE.data_range(11..12).is_none());
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = FILE.data_range(5..2);
+    assert!(reversed.is_none());
+
+    let shared = SharedHttpFile::new(
+        ByteData::from(b"0123456789".to_vec()),
+        StringData::from_static("text/plain"),
+        StringData::from_static(FILE.etag),
+    );
+    assert_eq!(shared.data_range(7..10).unwrap().as_slice(), b"789");
+    assert!(shared.data_range(7..11).is_none());
+}
+
+#[test]
+fn test_range_response() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+    let request = |method: http::Method, range: &str, if_range: Option<&str>| {
+        let mut builder = http::Request::builder()
+            .method(method)
+            .uri("/digits.txt")
+            .header(http::header::RANGE, range);
+        if let Some(if_range) = if_range {
+            builder = builder.header(http::header::IF_RANGE, if_range);
+        }
+        builder.body(()).unwrap()
+    };
+
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "bytes=2-4", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        res.headers().get(http::header::CONTENT_RANGE).unwrap(),
+        "bytes 2-4/10"
+    );
+    assert_eq!(
+        res.headers().get(http::header::ACCEPT_RANGES).unwrap(),
+        "bytes"
+    );
+    assert_eq!(res.body().as_slice(), b"234");
+
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "bytes=-3", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        res.headers().get(http::header::CONTENT_RANGE).unwrap(),
+        "bytes 7-9/10"
+    );
+    assert_eq!(res.body().as_slice(), b"789");
+
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "bytes=10-", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+        res.headers().get(http::header::CONTENT_RANGE).unwrap(),
+        "bytes */10"
+    );
+
+    // a range spanning the whole data is still answered as partial content
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "bytes=0-", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        res.headers().get(http::header::CONTENT_RANGE).unwrap(),
+        "bytes 0-9/10"
+    );
+    assert_eq!(res.body().as_slice(), b"0123456789");
+
+    // malformed and multiple ranges fall back to the full data
+    for range in ["bytes=4-2", "bytes=0-1, 4-5"] {
+        let res = FILE
+            .respond::<ByteData>(&request(http::Method::GET, range, None))
+            .unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.body().as_slice(), b"0123456789");
+    }
+
+    // ranges are only applied to GET requests
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::HEAD, "bytes=2-4", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert!(res.body().is_empty());
+
+    let etag = FILE.etag;
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "bytes=2-4", Some(etag)))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.body().as_slice(), b"234");
+
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "bytes=2-4", Some("\"other\"")))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.body().as_slice(), b"0123456789");
+
+    // a weak validator never satisfies If-Range, even if the opaque tag matches
+    let weak = alloc::format!("W/{}", etag);
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "bytes=2-4", Some(&weak)))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert!(res.headers().get(http::header::CONTENT_RANGE).is_none());
+    assert_eq!(res.body().as_slice(), b"0123456789");
+}
+
+/// A file for tests which delegates to a [`ConstHttpFile`](crate::ConstHttpFile) and returns the configured values from the optional hooks.
+///
+/// Use struct update syntax to configure it, such as `TestFile { accepts_ranges: false, ..TestFile::new(FILE) }`.
+struct TestFile {
+    file: crate::ConstHttpFile,
+    accepts_ranges: bool,
+    slices_data: bool,
+    cache_busting: crate::CacheBusting,
+    cache_control: Option<crate::CacheControl>,
+    last_modified: Option<u64>,
+    vary: &'static [http::header::HeaderName],
+    nel: Option<crate::NetworkErrorLogging>,
+    security: Option<crate::SecurityHeaders>,
+    retry_after: Option<u64>,
+    sniff_protection: bool,
+}
+
+impl TestFile {
+    fn new(file: crate::ConstHttpFile) -> Self {
+        TestFile {
+            file,
+            accepts_ranges: true,
+            slices_data: true,
+            cache_busting: crate::CacheBusting::None,
+            cache_control: None,
+            last_modified: None,
+            vary: &[],
+            nel: None,
+            security: None,
+            retry_after: None,
+            sniff_protection: true,
+        }
+    }
+}
+
+impl HttpFile<'static> for TestFile {
+    fn content_type(&self) -> &str {
+        self.file.content_type()
+    }
+    fn data(&self) -> &[u8] {
+        self.file.data()
+    }
+    fn etag(&self) -> &str {
+        self.file.etag()
+    }
+    fn last_modified(&self) -> Option<u64> {
+        self.last_modified
+    }
+    fn cache_busting(&self) -> &crate::CacheBusting {
+        &self.cache_busting
+    }
+    fn cache_control(&self) -> crate::CacheControl {
+        match self.cache_control {
+            Some(cache_control) => cache_control,
+            None => self.file.cache_control(),
+        }
+    }
+    fn accepts_ranges(&self) -> bool {
+        self.accepts_ranges
+    }
+    fn into_data(self) -> bytedata::ByteData<'static> {
+        self.file.into_data()
+    }
+    fn clone_data(&self) -> bytedata::ByteData<'static> {
+        self.file.clone_data()
+    }
+    fn data_range(&self, range: core::ops::Range<usize>) -> Option<bytedata::ByteData<'static>> {
+        if self.slices_data {
+            self.file.data_range(range)
+        } else {
+            None
+        }
+    }
+}
+
+impl HttpFileResponse<'static> for TestFile {
+    fn vary_headers(&self) -> &[http::header::HeaderName] {
+        self.vary
+    }
+    fn network_error_logging(&self) -> Option<&crate::NetworkErrorLogging> {
+        self.nel.as_ref()
+    }
+    fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+        self.security.as_ref()
+    }
+    fn retry_after(&self) -> Option<u64> {
+        self.retry_after
+    }
+    fn sniff_protection(&self) -> bool {
+        self.sniff_protection
+    }
+}
+
+#[test]
+fn test_accepts_ranges() {
+    use crate::{const_http_file, ConstHttpFile, HttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+    assert!(FILE.accepts_ranges());
+
+    for range in ["bytes=2-4", "bytes=10-"] {
+        let request = http::Request::get("/digits.txt")
+            .header(http::header::RANGE, range)
+            .body(())
+            .unwrap();
+        let res = TestFile {
+            accepts_ranges: false,
+            ..TestFile::new(FILE)
+        }
+        .respond::<ByteData>(&request)
+        .unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert!(res.headers().get(http::header::ACCEPT_RANGES).is_none());
+        assert!(res.headers().get(http::header::CONTENT_RANGE).is_none());
+        assert_eq!(res.body().as_slice(), b"0123456789");
+    }
+
+    // a file which cannot slice its data answers a range request with all of it
+    let request = http::Request::get("/digits.txt")
+        .header(http::header::RANGE, "bytes=2-4")
+        .body(())
+        .unwrap();
+    let unsliced = TestFile {
+        slices_data: false,
+        ..TestFile::new(FILE)
+    };
+    for res in [
+        unsliced.respond_borrowed::<ByteData>(&request).unwrap(),
+        unsliced.respond::<ByteData>(&request).unwrap(),
+    ] {
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.headers()[http::header::ETAG], FILE.etag);
+        assert_eq!(res.headers()[http::header::CONTENT_TYPE], "text/plain");
+        assert!(res.headers().get(http::header::CONTENT_RANGE).is_none());
+        assert_eq!(res.body().as_slice(), b"0123456789");
+    }
+}
+
+#[test]
+fn test_retry_after() {
+    use crate::{const_http_file, ConstHttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+    let request = |range: &str| {
+        http::Request::get("/log.txt")
+            .header(http::header::RANGE, range)
+            .body(())
+            .unwrap()
+    };
+
+    let res = FILE.respond::<ByteData>(&request("bytes=20-")).unwrap();
+    assert_eq!(res.status(), http::StatusCode::RANGE_NOT_SATISFIABLE);
+    assert!(res.headers().get(http::header::RETRY_AFTER).is_none());
+
+    // a file which is still being generated, where only the first part is available
+    let generating = || TestFile {
+        retry_after: Some(5),
+        ..TestFile::new(FILE)
+    };
+
+    let res = generating()
+        .respond::<ByteData>(&request("bytes=20-"))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(res.headers()[http::header::RETRY_AFTER], "5");
+    assert_eq!(res.headers()[http::header::CONTENT_RANGE], "bytes */10");
+
+    // satisfiable ranges are not affected
+    let res = generating()
+        .respond::<ByteData>(&request("bytes=0-3"))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert!(res.headers().get(http::header::RETRY_AFTER).is_none());
+}
+
+#[test]
+fn test_if_match() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let request = |method: http::Method, if_match: &str, if_none_match: Option<&str>| {
+        let mut builder = http::Request::builder()
+            .method(method)
+            .uri("/foo.txt")
+            .header(http::header::IF_MATCH, if_match);
+        if let Some(if_none_match) = if_none_match {
+            builder = builder.header(http::header::IF_NONE_MATCH, if_none_match);
+        }
+        builder.body(()).unwrap()
+    };
+
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "\"q25fZAd-fY\"", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.body().as_slice(), b"foo");
+
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "\"other\", *", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "\"other\"", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PRECONDITION_FAILED);
+    assert!(res.body().is_empty());
+
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::HEAD, "\"other\"", None))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PRECONDITION_FAILED);
+
+    // If-Match is evaluated before If-None-Match
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "\"other\"", Some("*")))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::PRECONDITION_FAILED);
+    let res = FILE
+        .respond::<ByteData>(&request(http::Method::GET, "*", Some("*")))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::NOT_MODIFIED);
+}
+
+#[test]
+fn test_etag_from_hash_endianness() {
+    // the hash is always encoded as big-endian bytes, regardless of the target
+    let etag = crate::const_etag::etag_from_hash(0x0123_4567_89AB_CDEF);
+    assert_eq!(&etag, b"\"ASNFZ4mrze\"");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_etag_variants_match() {
+    use crate::{compute_etag, compute_etag_nonconst, EtagHasher};
+
+    let inputs: [&[u8]; 4] = [
+        b"",
+        b"foo",
+        include_bytes!("../.gitignore"),
+        include_bytes!("../Cargo.toml"),
+    ];
+    for input in inputs {
+        let etag = compute_etag(input);
+        let etag_nonconst = compute_etag_nonconst(input);
+        assert_eq!(etag_nonconst.as_bytes(), &etag);
+
+        let mut hasher = EtagHasher::new();
+        for chunk in input.chunks(7) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.finish(), etag_nonconst);
+    }
+}
+
+#[test]
+fn test_vary_headers() {
+    use crate::{const_http_file, ConstHttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    const VARY: &[http::header::HeaderName] = &[
+        http::header::ACCEPT_ENCODING,
+        http::header::ORIGIN,
+        http::header::ACCEPT_ENCODING,
+    ];
+    let res = FILE.into_response::<ByteData>().unwrap();
+    assert!(res.headers().get(http::header::VARY).is_none());
+
+    let res = TestFile {
+        vary: VARY,
+        ..TestFile::new(FILE)
+    }
+    .into_response::<ByteData>()
+    .unwrap();
+    assert_eq!(
+        res.headers().get(http::header::VARY).unwrap(),
+        "accept-encoding, origin"
+    );
+}
+
+#[cfg(feature = "http_body_1")]
+#[test]
+fn test_http_file_body() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileBody, HttpFileResponse};
+    use ::http_body_1::Body;
+
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+    let request = http::Request::builder().uri("/").body(()).unwrap();
+    let res = FILE.respond::<HttpFileBody>(&request).unwrap();
+    assert_eq!(res.body().size_hint().exact(), Some(10));
+    assert!(!res.body().is_end_stream());
+
+    let empty = HttpFileBody::new(bytedata::ByteData::from_static(&[]));
+    assert_eq!(empty.size_hint().exact(), Some(0));
+    assert!(empty.is_end_stream());
+
+    // a range response hints the length of the range
+    let request = http::Request::get("/")
+        .header(http::header::RANGE, "bytes=2-5")
+        .body(())
+        .unwrap();
+    let res = FILE.respond::<HttpFileBody>(&request).unwrap();
+    assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.body().size_hint().exact(), Some(4));
+    let request = http::Request::get("/")
+        .header(http::header::RANGE, "bytes=-3")
+        .body(())
+        .unwrap();
+    let res = FILE.respond::<HttpFileBody>(&request).unwrap();
+    assert_eq!(res.body().size_hint().exact(), Some(3));
+
+    // the frame refers to the data of the file instead of a copy
+    let mut body = FILE.respond::<HttpFileBody>(&request).unwrap().into_body();
+    let waker = noop_waker();
+    let mut cx = core::task::Context::from_waker(&waker);
+    let frame = match core::pin::Pin::new(&mut body).poll_frame(&mut cx) {
+        core::task::Poll::Ready(Some(Ok(frame))) => frame,
+        _ => panic!("expected a data frame"),
+    };
+    let chunk = frame.into_data().unwrap();
+    assert_eq!(bytes_1::Buf::chunk(&chunk), b"789");
+    assert_eq!(
+        bytes_1::Buf::chunk(&chunk).as_ptr(),
+        FILE.data[7..].as_ptr()
+    );
+    assert!(body.is_end_stream());
+}
+
+/// A waker which does nothing, for polling bodies which are always ready.
+#[cfg(feature = "http_body_1")]
+fn noop_waker() -> core::task::Waker {
+    use core::task::{RawWaker, RawWakerVTable, Waker};
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(core::ptr::null(), &VTABLE),
+        |_| {},
+        |_| {},
+        |_| {},
+    );
+    unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) }
+}
+
+#[cfg(feature = "http_body_1")]
+#[test]
+fn test_http_file_body_served_by_hyper() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileBody, HttpFileResponse};
+    use tokio_1::io::{AsyncReadExt, AsyncWriteExt};
+
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+
+    // the request and response are converted between the `http` 1 types of hyper and the `http` 0.2 types of this crate
+    async fn serve(
+        request: http_1::Request<hyper_1::body::Incoming>,
+    ) -> Result<http_1::Response<HttpFileBody<'static>>, core::convert::Infallible> {
+        let mut builder = http::Request::get(request.uri().to_string());
+        for (name, value) in request.headers() {
+            builder = builder.header(name.as_str(), value.as_bytes());
+        }
+        let (parts, body) = FILE
+            .respond::<HttpFileBody>(&builder.body(()).unwrap())
+            .unwrap()
+            .into_parts();
+        let mut response = http_1::Response::new(body);
+        *response.status_mut() = http_1::StatusCode::from_u16(parts.status.as_u16()).unwrap();
+        for (name, value) in &parts.headers {
+            response.headers_mut().append(
+                http_1::HeaderName::from_bytes(name.as_str().as_bytes()).unwrap(),
+                http_1::HeaderValue::from_bytes(value.as_bytes()).unwrap(),
+            );
+        }
+        Ok(response)
+    }
+
+    let runtime = tokio_1::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async move {
+        let listener = tokio_1::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        for (range, length, body) in [
+            ("", "10", "0123456789"),
+            ("range: bytes=2-4\r\n", "3", "234"),
+        ] {
+            let client = tokio_1::spawn(async move {
+                let mut stream = tokio_1::net::TcpStream::connect(addr).await.unwrap();
+                let request = format!(
+                    "GET /digits.txt HTTP/1.1\r\nhost: localhost\r\n{}connection: close\r\n\r\n",
+                    range
+                );
+                stream.write_all(request.as_bytes()).await.unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).await.unwrap();
+                response
+            });
+            // axum serves its responses through hyper, which decides between `Content-Length` and chunked transfer encoding
+            let (stream, _) = listener.accept().await.unwrap();
+            hyper_1::server::conn::http1::Builder::new()
+                .serve_connection(
+                    hyper_util::rt::TokioIo::new(stream),
+                    hyper_1::service::service_fn(serve),
+                )
+                .await
+                .unwrap();
+            let response = client.await.unwrap().to_ascii_lowercase();
+            assert!(response.contains(&format!("\r\ncontent-length: {}\r\n", length)));
+            assert!(!response.contains("transfer-encoding"));
+            assert!(response.ends_with(&format!("\r\n\r\n{}", body)));
+        }
+    });
+}
+
+#[cfg(feature = "nonce")]
+#[test]
+fn test_nonce_http_file() {
+    use crate::{const_http_file, generate_nonce, ConstHttpFile, HttpFileResponse, NonceHttpFile};
+    use bytedata::ByteData;
+
+    const PAGE: ConstHttpFile = const_http_file!(
+        b"<script nonce=\"{{CSP_NONCE}}\">a()</script><style nonce=\"{{CSP_NONCE}}\"></style>"
+            as &[u8],
+        "text/html; charset=utf-8"
+    );
+
+    let nonce = generate_nonce();
+    assert_eq!(nonce.len(), 22);
+    assert_ne!(nonce, generate_nonce());
+
+    let page = NonceHttpFile::new(PAGE);
+    let request = http::Request::get("/").body(()).unwrap();
+    let res = page.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.headers()[http::header::CACHE_CONTROL], "no-store");
+    assert!(res.headers().get(http::header::ACCEPT_RANGES).is_none());
+    let csp = res.headers()[http::header::CONTENT_SECURITY_POLICY]
+        .to_str()
+        .unwrap();
+    let nonce = csp
+        .strip_prefix("script-src 'nonce-")
+        .and_then(|n| n.strip_suffix('\''))
+        .unwrap();
+    let body = core::str::from_utf8(res.body().as_slice()).unwrap();
+    assert_eq!(
+        body,
+        format!(
+            "<script nonce=\"{0}\">a()</script><style nonce=\"{0}\"></style>",
+            nonce
+        )
+    );
+
+    let second = page.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_ne!(second.headers()[http::header::CONTENT_SECURITY_POLICY], csp);
+    assert_ne!(
+        second.headers()[http::header::ETAG],
+        res.headers()[http::header::ETAG]
+    );
+
+    let page = NonceHttpFile::new(PAGE)
+        .with_placeholder("{{CSP_NONCE}}")
+        .with_policy("script-src 'nonce-{{CSP_NONCE}}'; style-src 'nonce-{{CSP_NONCE}}'")
+        .unwrap();
+    assert_eq!(
+        page.policy("abc"),
+        "script-src 'nonce-abc'; style-src 'nonce-abc'"
+    );
+    let res = page.respond::<ByteData>(&request).unwrap();
+    let csp = res.headers()[http::header::CONTENT_SECURITY_POLICY]
+        .to_str()
+        .unwrap();
+    let nonce = &csp["script-src 'nonce-".len().."script-src 'nonce-".len() + 22];
+    assert!(csp.ends_with(&format!("style-src 'nonce-{}'", nonce)));
+    let body = core::str::from_utf8(res.body().as_slice()).unwrap();
+    assert_eq!(body.matches(nonce).count(), 2);
+
+    // a policy which is not a valid header value is rejected instead of failing every response
+    assert!(NonceHttpFile::new(PAGE)
+        .with_policy("script-src 'nonce-{{CSP_NONCE}}'\n")
+        .is_err());
+
+    // a cache busted page is served at its location instead of redirecting to a new nonce every time
+    let busted = NonceHttpFile::new(crate::QueryCacheBustedHttpFile::new("/", "v", PAGE));
+    let location = format!("/?v={}", PAGE.etag_str());
+    let request = http::Request::get(location.as_str()).body(()).unwrap();
+    for _ in 0..2 {
+        let res = busted.respond_borrowed::<ByteData>(&request).unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+    }
+    let request = http::Request::get("/").body(()).unwrap();
+    let res = busted.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(res.status(), http::StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(res.headers()[http::header::LOCATION], location.as_str());
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn test_gzip_on_demand() {
+    use crate::{const_http_file, ConstHttpFile, GzipOnDemand, HttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+    use std::io::Read;
+
+    const TEXT: &[u8] =
+        b"body { margin: 0; padding: 0; } body { margin: 0; padding: 0; } body { margin: 0; }";
+    const FILE: ConstHttpFile = const_http_file!(TEXT, "text/css");
+    let file = GzipOnDemand::new(FILE);
+    let request = |accept: Option<&str>| {
+        let mut req = http::Request::get("/style.css");
+        if let Some(accept) = accept {
+            req = req.header(http::header::ACCEPT_ENCODING, accept);
+        }
+        req.body(()).unwrap()
+    };
+
+    let res = file.respond_borrowed::<ByteData>(&request(None)).unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert!(res.headers().get(http::header::CONTENT_ENCODING).is_none());
+    assert_eq!(res.headers()[http::header::ETAG], FILE.etag);
+    assert_eq!(res.headers()[http::header::VARY], "accept-encoding");
+    assert_eq!(res.body().as_slice(), TEXT);
+
+    for accept in ["gzip;q=0", "br", "identity, *;q=0"] {
+        let res = file
+            .respond_borrowed::<ByteData>(&request(Some(accept)))
+            .unwrap();
+        assert!(res.headers().get(http::header::CONTENT_ENCODING).is_none());
+    }
+
+    let res = file
+        .respond_borrowed::<ByteData>(&request(Some("br;q=1.0, GZIP;q=0.5")))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "gzip");
+    assert_eq!(res.headers()[http::header::VARY], "accept-encoding");
+    let gz_etag = res.headers()[http::header::ETAG]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    assert_eq!(gz_etag, format!("\"{}-gz\"", FILE.etag_str()));
+    assert!(res.body().len() < TEXT.len());
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(res.body().as_slice())
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, TEXT);
+
+    let req = http::Request::get("/style.css")
+        .header(http::header::ACCEPT_ENCODING, "gzip")
+        .header(http::header::IF_NONE_MATCH, gz_etag.as_str())
+        .body(())
+        .unwrap();
+    let res = file.respond::<ByteData>(&req).unwrap();
+    assert_eq!(res.status(), http::StatusCode::NOT_MODIFIED);
+
+    // the compressed representation is served at the cache busted location of the file instead of redirecting
+    let busted = GzipOnDemand::new(crate::QueryCacheBustedHttpFile::new(
+        "/style.css",
+        "v",
+        FILE,
+    ));
+    let location = format!("/style.css?v={}", FILE.etag_str());
+    let req = http::Request::get(location.as_str())
+        .header(http::header::ACCEPT_ENCODING, "gzip")
+        .body(())
+        .unwrap();
+    let res = busted.respond_borrowed::<ByteData>(&req).unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "gzip");
+    let res = busted
+        .respond_borrowed::<ByteData>(&request(Some("gzip")))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(res.headers()[http::header::LOCATION], location.as_str());
+
+    // a lazily compressed file can be shared between threads
+    fn assert_sync<T: Sync + Send>(_: &T) {}
+    assert_sync(&file);
+
+    // data which does not shrink is never compressed
+    const TINY: ConstHttpFile = const_http_file!(b"a" as &[u8], "text/plain");
+    let tiny = GzipOnDemand::new(TINY);
+    assert!(tiny.gzip().is_none());
+    let res = tiny.respond::<ByteData>(&request(Some("gzip"))).unwrap();
+    assert!(res.headers().get(http::header::CONTENT_ENCODING).is_none());
+    assert_eq!(tiny.compression_ratio("gzip"), None);
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn test_compression_ratio() {
+    use crate::{const_http_file, ConstHttpFile, GzipOnDemand};
+
+    const TEXT: &[u8] = &[b'a'; 4096];
+    const FILE: ConstHttpFile = const_http_file!(TEXT, "text/plain");
+    let file = GzipOnDemand::new(FILE);
+    let ratio = file.compression_ratio("gzip").unwrap();
+    assert!(ratio > 0.0 && ratio < 0.05, "ratio {}", ratio);
+    assert_eq!(
+        ratio,
+        file.gzip().unwrap().0.len() as f32 / TEXT.len() as f32
+    );
+    assert_eq!(file.compression_ratio("X-GZIP"), Some(ratio));
+    assert_eq!(file.compression_ratio("br"), None);
+    assert_eq!(file.compression_ratio("identity"), None);
+
+    #[cfg(feature = "brotli")]
+    {
+        let file = crate::BrotliOnDemand::new(GzipOnDemand::new(FILE));
+        let ratio = file.compression_ratio("br").unwrap();
+        assert!(ratio > 0.0 && ratio < 0.05, "ratio {}", ratio);
+        assert_eq!(file.compression_ratio("gzip"), None);
+        assert!(file.inner().compression_ratio("gzip").is_some());
+    }
+}
+
+#[test]
+fn test_const_http_file_gz() {
+    use crate::{const_etag, const_http_file_gz, ConstHttpFile, HttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const JSON: &[u8] = b"[{\"id\": 1, \"name\": \"a\"}, {\"id\": 2, \"name\": \"a\"}, {\"id\": 3, \"name\": \"a\"}]";
+    const FILE: ConstHttpFile = const_http_file_gz!(JSON, "application/json");
+    assert_eq!(FILE.content_encoding(), Some("gzip"));
+    assert_eq!(FILE.mime, "application/json");
+    assert_eq!(FILE.file, None);
+    assert!(FILE.data.len() < JSON.len());
+    // the etag is computed from the compressed data
+    assert_eq!(FILE.etag, const_etag!(FILE.data));
+    assert_ne!(FILE.etag, const_etag!(JSON));
+
+    // the gzip header is followed by the checksum and length of the uncompressed data
+    assert_eq!(&FILE.data[..4], b"\x1f\x8b\x08\x00");
+    let trailer = &FILE.data[FILE.data.len() - 8..];
+    assert_eq!(&trailer[4..], (JSON.len() as u32).to_le_bytes());
+
+    const DETECTED: ConstHttpFile = const_http_file_gz!(b"<!DOCTYPE html><html></html>" as &[u8]);
+    assert_eq!(DETECTED.mime, "text/html");
+
+    let request = http::Request::get("/data.json").body(()).unwrap();
+    let res = FILE.respond::<ByteData>(&request).unwrap();
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "gzip");
+    assert_eq!(res.body().as_slice(), FILE.data);
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn test_const_gzip_roundtrip() {
+    use std::io::Read;
+
+    let decode = |data: &[u8]| {
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(data)
+            .read_to_end(&mut decoded)
+            .unwrap();
+        decoded
+    };
+
+    let mut inputs: Vec<Vec<u8>> = vec![
+        Vec::new(),
+        b"a".to_vec(),
+        b"abcabcabcabcabcabcabcabcabc".to_vec(),
+        vec![b'a'; 1000],
+    ];
+    // matches at a long distance, and bytes which use the 9 bit literal codes
+    let block: Vec<u8> = (0..20_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    let mut far = block.clone();
+    far.extend_from_slice(&block);
+    inputs.push(far);
+    inputs.push(
+        (0..50_000u32)
+            .map(|i| b"lorem ipsum dolor sit amet "[(i % 27) as usize] ^ (i / 4096) as u8)
+            .collect(),
+    );
+    for input in inputs {
+        let (out, len) = crate::const_gzip::gzip_into::<{ 1 << 17 }>(&input);
+        assert_eq!(len, crate::const_gzip_len(&input));
+        assert_eq!(decode(&out[..len]), input);
+    }
+
+    const DATA: &[u8] = b"const const const const const const";
+    const GZIP: [u8; crate::const_gzip_len(DATA)] = crate::const_gzip(DATA);
+    assert_eq!(decode(&GZIP), DATA);
+}
+
+#[test]
+fn test_const_http_file_pinned_etag() {
+    use crate::{const_http_file, ConstHttpFile};
+
+    const FILE: ConstHttpFile =
+        const_http_file!("../.gitignore", "text/plain", etag = "\"0123456789\"");
+    assert_eq!(FILE.etag, "\"0123456789\"");
+    assert_eq!(FILE.const_etag_str(), "0123456789");
+    assert_eq!(FILE.file, Some("../.gitignore"));
+
+    const UNTAGGED: ConstHttpFile = const_http_file!("../.gitignore", "text/plain", etag = "");
+    assert_eq!(UNTAGGED.etag, "");
+
+    const VERSIONED: ConstHttpFile =
+        const_http_file!("../.gitignore", "text/plain", etag = "\"v1.2.3\"");
+    assert_eq!(VERSIONED.const_etag_str(), "v1.2.3");
+}
+
+#[test]
+fn test_validate_etag() {
+    use crate::validate_etag;
+
+    assert!(validate_etag(""));
+    assert!(validate_etag("\"\""));
+    assert!(validate_etag("\"q25fZAd-fY\""));
+    assert!(validate_etag("\"a-much-longer-etag-than-usual\""));
+    assert!(validate_etag("W/\"q25fZAd-fY\""));
+    assert!(validate_etag("W/\"\""));
+    assert!(validate_etag("\"caf\u{e9}\""));
+
+    assert!(!validate_etag("q25fZAd-fY"));
+    assert!(!validate_etag("\""));
+    assert!(!validate_etag("\"q25fZAd-fY"));
+    assert!(!validate_etag("q25fZAd-fY\""));
+    assert!(!validate_etag("W/"));
+    assert!(!validate_etag("W/q25fZAd-fY"));
+    assert!(!validate_etag("w/\"q25fZAd-fY\""));
+    assert!(!validate_etag("\"q25f\"ZAd-fY\""));
+    assert!(!validate_etag("\"q25f ZAd-fY\""));
+    assert!(!validate_etag("\"q25fZAd-fY\"\n"));
+}
+
+#[test]
+fn test_manifest_round_trip() {
+    use crate::{ManifestEntry, ManifestEntryTooLarge, SharedHttpFile};
+    use bytedata::{ByteData, StringData};
+
+    let file = SharedHttpFile::new_named(
+        ByteData::from_static(b"foo"),
+        StringData::from_static("text/plain"),
+        StringData::from_static("\"q25fZAd-fY\""),
+        StringData::from_static("/foo.txt"),
+    );
+    let entry = ManifestEntry {
+        path: StringData::from_static("/app.js"),
+        mime: StringData::from_static("application/javascript"),
+        etag: StringData::from_static("\"0123456789\""),
+        size: 1 << 40,
+        encodings: alloc::vec![
+            StringData::from_static("gzip"),
+            StringData::from_static("br")
+        ],
+    };
+
+    let mut manifest = alloc::vec::Vec::new();
+    file.to_manifest_bytes(&mut manifest).unwrap();
+    entry.to_manifest_bytes(&mut manifest).unwrap();
+
+    let (read, rest) = ManifestEntry::from_manifest_bytes(&manifest).unwrap();
+    assert_eq!(read.path.as_str(), "/foo.txt");
+    assert_eq!(read.mime.as_str(), "text/plain");
+    assert_eq!(read.etag.as_str(), "\"q25fZAd-fY\"");
+    assert_eq!(read.size, 3);
+    assert!(read.encodings.is_empty());
+
+    let (read, rest) = ManifestEntry::from_manifest_bytes(rest).unwrap();
+    assert_eq!(read, entry);
+    assert!(rest.is_empty());
+
+    assert_eq!(ManifestEntry::iter_manifest(&manifest).count(), 2);
+    assert!(ManifestEntry::from_manifest_bytes(&manifest[..manifest.len() - 1]).is_none());
+
+    // the encoding count is a single byte, so more encodings are rejected instead of being dropped
+    let len = manifest.len();
+    let too_many = ManifestEntry {
+        encodings: alloc::vec![StringData::from_static("gzip"); 256],
+        ..entry
+    };
+    assert_eq!(
+        too_many.to_manifest_bytes(&mut manifest),
+        Err(ManifestEntryTooLarge)
+    );
+    assert_eq!(manifest.len(), len);
+}
+
+#[test]
+fn test_content_encoding() {
+    use crate::{const_etag, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const GZ: &[u8] = b"\x1F\x8B\x08\0\0\0\0\0\0\x03K\xCB\xCF\x07\0!es\x8C\x03\0\0\0";
+    const FILE: ConstHttpFile =
+        ConstHttpFile::new_encoded(GZ, "text/plain", const_etag!(GZ), "gzip");
+    let res = FILE.into_response::<ByteData>().unwrap();
+    assert_eq!(
+        res.headers().get(http::header::CONTENT_ENCODING).unwrap(),
+        "gzip"
+    );
+    assert_eq!(
+        res.headers().get(http::header::CONTENT_TYPE).unwrap(),
+        "text/plain"
+    );
+    assert_eq!(
+        res.headers().get(http::header::VARY).unwrap(),
+        "accept-encoding"
+    );
+    assert_eq!(res.body().as_slice(), GZ);
+
+    let res = ConstHttpFile::default()
+        .into_response::<ByteData>()
+        .unwrap();
+    assert!(res.headers().get(http::header::CONTENT_ENCODING).is_none());
+    assert!(res.headers().get(http::header::VARY).is_none());
+}
+
+#[test]
+fn test_etag_header_value() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse, SharedHttpFile};
+    use bytedata::{ByteData, StringData};
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    assert_eq!(FILE.etag_header_value(), "\"q25fZAd-fY\"");
+    let res = FILE.into_response::<ByteData>().unwrap();
+    assert_eq!(res.headers()[http::header::ETAG], FILE.etag_header_value());
+
+    let shared = SharedHttpFile::new(
+        ByteData::from_static(b"foo"),
+        StringData::from_static("text/plain"),
+        StringData::from_static(FILE.etag),
+    );
+    assert_eq!(shared.etag_header_value(), FILE.etag_header_value());
+
+    // an invalid etag is not emitted instead of failing the response
+    let invalid = SharedHttpFile {
+        etag: StringData::from_static("unquoted"),
+        ..shared.clone()
+    };
+    assert!(invalid.etag_header_value().is_empty());
+    let res = invalid.into_response::<ByteData>().unwrap();
+    assert!(res.headers().get(http::header::ETAG).is_none());
+
+    // `obs-text` is valid in an etag but cannot be referenced statically
+    const OBS_TEXT: ConstHttpFile = ConstHttpFile::new(b"foo", "text/plain", "\"caf\u{e9}\"");
+    assert_eq!(
+        OBS_TEXT.etag_header_value().as_bytes(),
+        "\"caf\u{e9}\"".as_bytes()
+    );
+}
+
+#[test]
+#[should_panic(expected = "etag must be empty or a quoted string")]
+fn test_const_http_file_invalid_etag() {
+    let _ = crate::ConstHttpFile::new(b"foo", "text/plain", "unquoted");
+}
+
+#[test]
+fn test_cache_control() {
+    use crate::{const_http_file, CacheControl, ConstHttpFile, HttpFile, HttpFileResponse};
+    use alloc::string::ToString;
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    assert_eq!(FILE.cache_control(), CacheControl::REVALIDATE);
+    assert!(FILE.is_publicly_cacheable());
+    assert_eq!(
+        CacheControl::REVALIDATE.to_string(),
+        "public, max-age=0, must-revalidate"
+    );
+    assert_eq!(
+        CacheControl::IMMUTABLE.to_string(),
+        "public, max-age=31536000, immutable"
+    );
+    assert_eq!(CacheControl::new().to_string(), "");
+
+    let public = TestFile {
+        cache_control: Some(CacheControl::public().with_max_age(600)),
+        ..TestFile::new(FILE)
+    };
+    assert!(public.is_publicly_cacheable());
+    let res = public.into_response::<ByteData>().unwrap();
+    assert_eq!(
+        res.headers()[http::header::CACHE_CONTROL],
+        "public, max-age=600"
+    );
+
+    let private = TestFile {
+        cache_control: Some(CacheControl::private().with_max_age(600)),
+        ..TestFile::new(FILE)
+    };
+    assert!(!private.is_publicly_cacheable());
+    let res = private.into_response::<ByteData>().unwrap();
+    assert_eq!(
+        res.headers()[http::header::CACHE_CONTROL],
+        "private, max-age=600"
+    );
+
+    let no_store = TestFile {
+        cache_control: Some(CacheControl::no_store()),
+        ..TestFile::new(FILE)
+    };
+    assert!(!no_store.is_publicly_cacheable());
+    let res = no_store.into_response::<ByteData>().unwrap();
+    assert_eq!(res.headers()[http::header::CACHE_CONTROL], "no-store");
+
+    let no_cache = TestFile {
+        cache_control: Some(CacheControl::new().with_no_cache()),
+        ..TestFile::new(FILE)
+    };
+    assert!(no_cache.is_publicly_cacheable());
+    let res = no_cache.into_response::<ByteData>().unwrap();
+    assert_eq!(res.headers()[http::header::CACHE_CONTROL], "no-cache");
+}
+
+#[test]
+fn test_cache_control_duration() {
+    use crate::CacheControl;
+    use alloc::string::ToString;
+    use core::time::Duration;
+
+    const HOUR: CacheControl = CacheControl::public().max_age(Duration::from_secs(60 * 60));
+    assert_eq!(HOUR.max_age_seconds(), Some(3600));
+    assert_eq!(HOUR.to_string(), "public, max-age=3600");
+
+    let cc = CacheControl::private().max_age(Duration::from_millis(3_600_999));
+    assert_eq!(cc.to_string(), "private, max-age=3600");
+    let cc = CacheControl::new().max_age(Duration::MAX);
+    assert_eq!(cc.max_age_seconds(), Some(u64::MAX));
+}
+
+#[test]
+fn test_network_error_logging() {
+    use crate::{const_http_file, ConstHttpFileResponse, NetworkErrorLogging};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let res = FILE.into_response::<ByteData>().unwrap();
+    assert!(res.headers().get("report-to").is_none());
+    assert!(res.headers().get("nel").is_none());
+
+    let nel = NetworkErrorLogging::new("nel", "https://example.com/\"report\"", 60);
+    let res = TestFile {
+        nel: Some(nel),
+        ..TestFile::new(FILE)
+    }
+    .into_response::<ByteData>()
+    .unwrap();
+    assert_eq!(
+        res.headers().get("report-to").unwrap(),
+        r#"{"group":"nel","max_age":60,"endpoints":[{"url":"https://example.com/\"report\""}]}"#
+    );
+    assert_eq!(
+        res.headers().get("nel").unwrap(),
+        r#"{"report_to":"nel","max_age":60}"#
+    );
+
+    // characters which are not printable ASCII are escaped, so any configuration is a valid header
+    let nel = NetworkErrorLogging::new("n\x7f\u{e9}\u{1f600}", "https://example.com/", 60);
+    assert_eq!(
+        nel.nel(),
+        r#"{"report_to":"n\u007f\u00e9\ud83d\ude00","max_age":60}"#
+    );
+
+    let nel =
+        NetworkErrorLogging::from_json(r#"{"group":"nel"}"#, r#"{"report_to":"nel"}"#).unwrap();
+    assert_eq!(nel.report_to(), r#"{"group":"nel"}"#);
+    assert!(NetworkErrorLogging::from_json("{}\n", "{}").is_err());
+    assert!(NetworkErrorLogging::from_json("{}", "{\"a\":\"\x7f\"}").is_err());
+}
+
+#[test]
+fn test_security_headers() {
+    use crate::{const_http_file, ConstHttpFileResponse, SecurityHeaders};
+    use bytedata::ByteData;
+
+    const CORP: &str = "cross-origin-resource-policy";
+    const COEP: &str = "cross-origin-embedder-policy";
+    const COOP: &str = "cross-origin-opener-policy";
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+
+    let res = FILE.into_response::<ByteData>().unwrap();
+    assert!(res.headers().get(CORP).is_none());
+    assert!(res.headers().get(COEP).is_none());
+    assert!(res.headers().get(COOP).is_none());
+
+    let res = TestFile {
+        security: Some(SecurityHeaders::new()),
+        ..TestFile::new(FILE)
+    }
+    .into_response::<ByteData>()
+    .unwrap();
+    assert!(res.headers().get(CORP).is_none());
+    assert!(res.headers().get(COEP).is_none());
+    assert!(res.headers().get(COOP).is_none());
+
+    let policy = SecurityHeaders::new().with_cross_origin_resource_policy("same-origin");
+    let res = TestFile {
+        security: Some(policy),
+        ..TestFile::new(FILE)
+    }
+    .into_response::<ByteData>()
+    .unwrap();
+    assert_eq!(res.headers().get(CORP).unwrap(), "same-origin");
+    assert!(res.headers().get(COEP).is_none());
+    assert!(res.headers().get(COOP).is_none());
+
+    let policy = SecurityHeaders::new().with_cross_origin_embedder_policy("require-corp");
+    let res = TestFile {
+        security: Some(policy),
+        ..TestFile::new(FILE)
+    }
+    .into_response::<ByteData>()
+    .unwrap();
+    assert!(res.headers().get(CORP).is_none());
+    assert_eq!(res.headers().get(COEP).unwrap(), "require-corp");
+    assert!(res.headers().get(COOP).is_none());
+
+    let policy = SecurityHeaders::new().with_cross_origin_opener_policy("same-origin");
+    let res = TestFile {
+        security: Some(policy),
+        ..TestFile::new(FILE)
+    }
+    .into_response::<ByteData>()
+    .unwrap();
+    assert!(res.headers().get(CORP).is_none());
+    assert!(res.headers().get(COEP).is_none());
+    assert_eq!(res.headers().get(COOP).unwrap(), "same-origin");
+}
+
+#[test]
+#[should_panic(expected = "the value must be a valid header value")]
+fn test_security_headers_invalid_value() {
+    let _ = crate::SecurityHeaders::new().with_cross_origin_opener_policy("same-origin\r\n");
+}
+
+#[test]
+fn test_with_security_headers() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse, WithSecurityHeaders};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile =
+        const_http_file!(b"<!DOCTYPE html>" as &[u8], "text/html; charset=utf-8");
+
+    let res = FILE.into_response::<ByteData>().unwrap();
+    assert!(res.headers().get("x-frame-options").is_none());
+    assert!(res.headers().get("content-security-policy").is_none());
+
+    let res = WithSecurityHeaders::new(FILE)
+        .into_response::<ByteData>()
+        .unwrap();
+    assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+    assert_eq!(res.headers()["x-frame-options"], "DENY");
+    assert!(res.headers().get("content-security-policy").is_none());
+    assert_eq!(res.headers()["etag"], FILE.etag);
+
+    let file = WithSecurityHeaders::new(FILE)
+        .with_frame_options(None)
+        .unwrap()
+        .with_content_security_policy("default-src 'self'; script-src 'nonce-r4nd0m'")
+        .unwrap();
+    let req = http::Request::get("/").body(()).unwrap();
+    let res = file.respond_borrowed::<ByteData>(&req).unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert!(res.headers().get("x-frame-options").is_none());
+    assert_eq!(
+        res.headers()["content-security-policy"],
+        "default-src 'self'; script-src 'nonce-r4nd0m'"
+    );
+
+    // invalid values are rejected when they are set instead of failing each response
+    assert!(WithSecurityHeaders::new(FILE)
+        .with_content_security_policy("default-src 'self'\r\nx-injected: 1")
+        .is_err());
+    assert!(WithSecurityHeaders::new(FILE)
+        .with_frame_options(Some("DENY\n"))
+        .is_err());
+    let file = WithSecurityHeaders::new(FILE)
+        .with_frame_options(Some("SAMEORIGIN"))
+        .unwrap();
+    assert_eq!(file.frame_options().unwrap(), "SAMEORIGIN");
+    assert!(file.content_security_policy().is_none());
+
+    const CONST_FILE: WithSecurityHeaders<ConstHttpFile> =
+        WithSecurityHeaders::new_const(FILE, None, Some("default-src 'none'"));
+    let res = CONST_FILE.into_response::<ByteData>().unwrap();
+    assert!(res.headers().get("x-frame-options").is_none());
+    assert_eq!(
+        res.headers()["content-security-policy"],
+        "default-src 'none'"
+    );
+}
+
+#[test]
+fn test_sniff_protection() {
+    use crate::{const_http_file, ConstHttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"<svg></svg>" as &[u8], "image/svg+xml");
+
+    for method in [http::Method::GET, http::Method::HEAD] {
+        let req = http::Request::builder().method(method).body(()).unwrap();
+        let res = FILE.respond_borrowed::<ByteData>(&req).unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+    }
+
+    let req = http::Request::get("/")
+        .header(http::header::IF_NONE_MATCH, FILE.etag)
+        .body(())
+        .unwrap();
+    let res = FILE.respond_borrowed::<ByteData>(&req).unwrap();
+    assert_eq!(res.status(), http::StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+
+    let req = http::Request::get("/").body(()).unwrap();
+    let res = TestFile {
+        sniff_protection: false,
+        ..TestFile::new(FILE)
+    }
+    .respond::<ByteData>(&req)
+    .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert!(res.headers().get("x-content-type-options").is_none());
+}
+
+#[test]
+fn test_cachebust_suffix_len() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use alloc::borrow::ToOwned;
+    use bytedata::ByteData;
+    use core::num::{NonZeroU8, NonZeroUsize};
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let sep = NonZeroU8::new(b'~');
+    let location = |path: &str, len: usize| {
+        let uri = http::Uri::try_from(path).unwrap();
+        FILE.cachebust_suffix::<ByteData>(&uri, sep, NonZeroUsize::new(len))
+            .map(|res| {
+                let res = res.unwrap();
+                assert_eq!(res.status(), http::StatusCode::TEMPORARY_REDIRECT);
+                res.headers()[http::header::LOCATION]
+                    .to_str()
+                    .unwrap()
+                    .to_owned()
+            })
+    };
+
+    assert_eq!(location("/app.js", 8).as_deref(), Some("/app~q25fZAd-.js"));
+    assert_eq!(location("/app~q25fZAd-.js", 8), None);
+    assert_eq!(
+        location("/app~q25fZAd-fY.js", 8).as_deref(),
+        Some("/app~q25fZAd-.js")
+    );
+    assert_eq!(location("/app", 4).as_deref(), Some("/app~q25f"));
+    assert_eq!(location("/app~q25f", 4), None);
+
+    // a length longer than the etag uses the whole etag
+    assert_eq!(
+        location("/app.js", 100).as_deref(),
+        Some("/app~q25fZAd-fY.js")
+    );
+    assert_eq!(location("/app~q25fZAd-fY.js", 100), None);
+    assert_eq!(location("/app~q25fZAd-fY.js", 0), None);
+}
+
+#[test]
+fn test_ext_matches_magic() {
+    use crate::ext_matches_magic;
+
+    const JPEG: &[u8] = b"\xFF\xD8\xFF\xE0\0\x10JFIF";
+    const _: () = assert!(ext_matches_magic("photo.jpg", JPEG));
+    const _: () = assert!(ext_matches_magic("photo.jpeg", JPEG));
+    const _: () = assert!(!ext_matches_magic("photo.png", JPEG));
+    const _: () = assert!(!ext_matches_magic("index.html", b"%PDF-1.7"));
+
+    // nothing to compare against
+    const _: () = assert!(ext_matches_magic("photo", JPEG));
+    const _: () = assert!(ext_matches_magic("photo.png", b""));
+
+    // containers match their specializations
+    const _: () = assert!(ext_matches_magic(
+        "feed.atom",
+        b"<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed></feed>"
+    ));
+    const _: () = assert!(ext_matches_magic("module.wasm", b"\0asm\x01\0\0\0"));
+    const _: () = assert!(ext_matches_magic("archive.gz", b"\x1F\x8B\x08\0"));
+
+    let docx = zip_with_entries(&["[Content_Types].xml", "word/document.xml"]);
+    assert!(ext_matches_magic("report.docx", &docx));
+    assert!(!ext_matches_magic("report.pdf", &docx));
+}
+
+#[test]
+fn test_cachebust_suffix_percent_encoded() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+    use core::num::NonZeroU8;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let sep = NonZeroU8::new(b'~');
+    let cachebust = |path: &str| {
+        let uri = http::Uri::try_from(path).unwrap();
+        FILE.cachebust_suffix::<ByteData>(&uri, sep, None)
+    };
+
+    assert!(cachebust("/app~q25fZAd-fY.js").is_none());
+    assert!(cachebust("/app%7Eq25fZAd-fY.js").is_none());
+    assert!(cachebust("/app%7eq25fZAd-fY").is_none());
+    // only the last segment is decoded
+    assert!(cachebust("/a%7Eb/app%7Eq25fZAd-fY.js").is_none());
+
+    let res = cachebust("/app%7Eold.js").unwrap().unwrap();
+    assert_eq!(
+        res.headers().get(http::header::LOCATION).unwrap(),
+        "/app~q25fZAd-fY.js"
+    );
+
+    // the location keeps other percent-encoded characters, so the target and query are not changed
+    let locations = [
+        ("/a%2Fb.js", "/a%2Fb~q25fZAd-fY.js"),
+        ("/my%20app~old.js", "/my%20app~q25fZAd-fY.js"),
+        ("/x%3Fy=1.js", "/x%3Fy=1~q25fZAd-fY.js"),
+        ("/x%3Fy%7eold", "/x%3Fy~q25fZAd-fY"),
+    ];
+    for (path, location) in locations {
+        let res = cachebust(path).unwrap().unwrap();
+        assert_eq!(res.headers()[http::header::LOCATION], location, "{}", path);
+    }
+}
+
+#[test]
+fn test_urldecode() {
+    use crate::{urldecode, urldecode_into};
+
+    assert_eq!(urldecode("plain").unwrap().as_ref(), b"plain");
+    assert_eq!(urldecode("a%20b%2fc").unwrap().as_ref(), b"a b/c");
+    assert_eq!(urldecode("%FF%FE").unwrap().as_ref(), b"\xFF\xFE");
+    assert_eq!(urldecode("a%2"), Err(1));
+    assert_eq!(urldecode("a%zz"), Err(1));
+
+    let mut out = alloc::vec::Vec::new();
+    assert_eq!(urldecode_into(b"ok%21%", &mut out), Err(5));
+    assert_eq!(out, b"ok!");
+}
+
+#[test]
+fn test_urldecode_truncated_escapes() {
+    use crate::{urldecode, urldecode_into, urldecode_iter_fn};
+    use alloc::vec::Vec;
+
+    for input in ["abc%", "abc%A", "abc%AG", "abc%G0"] {
+        assert_eq!(urldecode(input), Err(3), "{}", input);
+
+        let mut out = Vec::new();
+        assert_eq!(urldecode_into(input.as_bytes(), &mut out), Err(3));
+        assert_eq!(out, b"abc");
+
+        let mut chunks = Vec::new();
+        let res = urldecode_iter_fn(input.as_bytes(), |chunk| {
+            chunk.iter().for_each(|b| chunks.push(*b))
+        });
+        assert_eq!(res, Err(3));
+        assert_eq!(chunks, b"abc");
+    }
+    assert_eq!(urldecode("%"), Err(0));
+    assert_eq!(urldecode("%4"), Err(0));
+    assert_eq!(urldecode("%41%"), Err(3));
+    assert_eq!(urldecode("%41").unwrap().as_ref(), b"A");
+}
+
+#[test]
+fn test_parse_query_string() {
+    use crate::{parse_query_string_iter_fn, parse_query_string_utf8};
+    use alloc::{borrow::Cow, vec::Vec};
+
+    let mut pairs = Vec::new();
+    let res = parse_query_string_iter_fn("a=1&&b&c=%FF%FE&d=x%3Dy", |k, v| {
+        pairs.push((k.into_owned(), v.into_owned()))
+    });
+    assert_eq!(res, Ok(()));
+    assert_eq!(
+        pairs,
+        [
+            (b"a".to_vec(), b"1".to_vec()),
+            (b"b".to_vec(), b"".to_vec()),
+            (b"c".to_vec(), b"\xFF\xFE".to_vec()),
+            (b"d".to_vec(), b"x=y".to_vec()),
+        ]
+    );
+    assert_eq!(parse_query_string_iter_fn("a=1&b=%2", |_, _| {}), Err(6));
+
+    let mut pairs = Vec::new();
+    let res = parse_query_string_utf8("v=q25fZAd-fY&n%C3%A4me=%E2%9C%93", |k, v| {
+        pairs.push((k, v))
+    });
+    assert_eq!(res, Ok(()));
+    assert!(matches!(
+        pairs[0],
+        (Cow::Borrowed("v"), Cow::Borrowed("q25fZAd-fY"))
+    ));
+    assert_eq!(pairs[1], ("n\u{e4}me".into(), "\u{2713}".into()));
+
+    let mut keys = Vec::new();
+    let res = parse_query_string_utf8("a=1&b=%FF%FE&c=3", |k, _| keys.push(k));
+    assert_eq!(res, Err(6));
+    assert_eq!(keys, ["a"]);
+    assert_eq!(parse_query_string_utf8("%C3=1", |_, _| {}), Err(0));
+    assert_eq!(parse_query_string_utf8("a=%ZZ", |_, _| {}), Err(2));
+}
+
+#[test]
+fn test_query_string_separators() {
+    use crate::{parse_query_string_iter_fn, parse_query_string_utf8, QueryStringIterator};
+    use alloc::vec::Vec;
+
+    let pairs: Vec<_> = QueryStringIterator::new_with_separators("a=1;b=2&c=3", b"&;").collect();
+    assert_eq!(pairs, [("a", "1"), ("b", "2"), ("c", "3")]);
+    let pairs: Vec<_> = QueryStringIterator::new("a=1;b=2&c=3").collect();
+    assert_eq!(pairs, [("a", "1;b=2"), ("c", "3")]);
+    let pairs: Vec<_> = QueryStringIterator::new_with_separators(";;a&;b=;", b"&;").collect();
+    assert_eq!(pairs, [("a", ""), ("b", "")]);
+    assert_eq!(QueryStringIterator::new("").count(), 0);
+
+    let mut pairs = Vec::new();
+    let query = QueryStringIterator::new_with_separators("a=1;b=%32&c=3", b"&;");
+    parse_query_string_utf8(query, |k, v| pairs.push((k.into_owned(), v.into_owned()))).unwrap();
+    assert_eq!(pairs.len(), 3);
+    assert_eq!((pairs[1].0.as_str(), pairs[1].1.as_str()), ("b", "2"));
+
+    let query = QueryStringIterator::new_with_separators("a=1;b=%3", b"&;");
+    assert_eq!(parse_query_string_iter_fn(query, |_, _| {}), Err(6));
+}
+
+#[test]
+fn test_cachebust_uri_keeps_prefixed_keys() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let location = |uri: &str| {
+        let uri = http::Uri::try_from(uri).unwrap();
+        let res = FILE
+            .cachebust_uri::<ByteData>(&uri, "v_et")
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.status(), http::StatusCode::TEMPORARY_REDIRECT);
+        res.headers()[http::header::LOCATION].clone()
+    };
+
+    assert_eq!(
+        location("/foo.txt?v_et=old&v_etags=keepme"),
+        "/foo.txt?v_et=q25fZAd-fY&v_etags=keepme"
+    );
+    assert_eq!(
+        location("/foo.txt?v_etags=keepme&v_et=old&v_et"),
+        "/foo.txt?v_et=q25fZAd-fY&v_etags=keepme"
+    );
+    assert_eq!(
+        location("/foo.txt?v_etags=keepme"),
+        "/foo.txt?v_et=q25fZAd-fY&v_etags=keepme"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_std_http_file_mime_charset() {
+    use crate::{HttpFileResponse, StdHttpFile};
+    use bytedata::ByteData;
+
+    let file =
+        StdHttpFile::new_with_mime_charset("Cargo.toml", "application/toml", "utf-8").unwrap();
+    assert_eq!(file.mime, "application/toml; charset=utf-8");
+    let res = file.into_response::<ByteData>().unwrap();
+    assert_eq!(
+        res.headers().get(http::header::CONTENT_TYPE).unwrap(),
+        "application/toml; charset=utf-8"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_std_http_file_with_etag() {
+    use crate::{HttpFile, HttpFileResponse, StdHttpFile};
+    use bytedata::ByteData;
+
+    let file = StdHttpFile::new_with_etag("Cargo.toml", "\"v1.2.3\"").unwrap();
+    assert_eq!(file.mime, "application/toml");
+    assert_eq!(file.etag(), "\"v1.2.3\"");
+    assert_eq!(file.etag_str(), "v1.2.3");
+    let res = file.into_response::<ByteData>().unwrap();
+    assert_eq!(res.headers()[http::header::ETAG], "\"v1.2.3\"");
+
+    let file = StdHttpFile::new_with_etag("Cargo.toml", String::from("W/\"1\"")).unwrap();
+    assert_eq!(file.etag(), "W/\"1\"");
+
+    let err = StdHttpFile::new_with_etag("Cargo.toml", "v1.2.3").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_wasm_content_type() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse, StdHttpFile};
+    use bytedata::ByteData;
+
+    const WASM: ConstHttpFile = const_http_file!(b"\0asm\x01\0\0\0" as &[u8]);
+    let res = WASM.into_response::<ByteData>().unwrap();
+    assert_eq!(
+        res.headers()[http::header::CONTENT_TYPE],
+        "application/wasm"
+    );
+
+    let path = std::env::temp_dir().join(format!("static-http-file-{}.wasm", std::process::id()));
+    std::fs::write(&path, b"\0asm\x01\0\0\0").unwrap();
+    let path_str = path.to_str().unwrap().to_owned();
+
+    let file = StdHttpFile::new(path_str.clone()).unwrap();
+    assert_eq!(file.mime, "application/wasm");
+
+    let file = StdHttpFile::new_with_mime_charset(path_str, "application/wasm", "utf-8").unwrap();
+    let res = file.into_response::<ByteData>().unwrap();
+    assert_eq!(
+        res.headers()[http::header::CONTENT_TYPE],
+        "application/wasm"
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_cachebust_redirect() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse, QueryCacheBustedHttpFile};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let file = QueryCacheBustedHttpFile::new("/foo.txt", "v", FILE);
+    assert_eq!(file.url().as_str(), "/foo.txt?v=q25fZAd-fY");
+
+    for method in [http::Method::GET, http::Method::HEAD] {
+        let request = http::Request::builder()
+            .method(method)
+            .uri("/foo.txt?v=old")
+            .body(())
+            .unwrap();
+        let res = file.respond_borrowed::<ByteData>(&request).unwrap();
+        assert_eq!(res.status(), http::StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            res.headers().get(http::header::LOCATION).unwrap(),
+            "/foo.txt?v=q25fZAd-fY"
+        );
+        assert_eq!(
+            res.headers().get(http::header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+        assert!(res.body().is_empty());
+    }
+
+    let request = http::Request::builder()
+        .uri(file.url().as_str())
+        .body(())
+        .unwrap();
+    let res = file.respond::<ByteData>(&request).unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(
+        res.headers().get(http::header::CACHE_CONTROL).unwrap(),
+        "public, max-age=31536000, immutable"
+    );
+}
+
+#[test]
+fn test_const_etag_matches() {
+    use crate::const_etag_matches;
+
+    const ETAG: &str = "\"q25fZAd-fY\"";
+    const _: () = assert!(const_etag_matches(ETAG, ETAG));
+    const _: () = assert!(const_etag_matches(ETAG, "*"));
+    const _: () = assert!(const_etag_matches(ETAG, "\"a\", *"));
+    const _: () = assert!(const_etag_matches(ETAG, "\"a\",\"q25fZAd-fY\""));
+    const _: () = assert!(const_etag_matches(ETAG, " \"a\" ,\t\"q25fZAd-fY\" "));
+    const _: () = assert!(!const_etag_matches(ETAG, ""));
+    const _: () = assert!(!const_etag_matches(ETAG, "\"a\", \"b\""));
+    const _: () = assert!(!const_etag_matches(ETAG, "q25fZAd-fY"));
+    const _: () = assert!(!const_etag_matches(ETAG, "**"));
+    const _: () = assert!(!const_etag_matches(ETAG, "\"q25fZAd-fY\"\""));
+    const _: () = assert!(const_etag_matches("", ""));
+}
+
+#[cfg(feature = "expose")]
+#[test]
+fn test_exposed_directory_trailing_slash() {
+    use crate::{DirWarmup, ExposedDirectory};
+    use bytedata::ByteData;
+
+    let root = std::env::temp_dir().join(format!(
+        "static-http-file-trailing-slash-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(root.join("docs")).unwrap();
+    std::fs::write(root.join("docs/index.html"), "<html></html>").unwrap();
+    std::fs::write(root.join("app.js"), "console.log(1);").unwrap();
+    let root_str = root.to_str().unwrap().to_owned();
+
+    let get = |dir: &ExposedDirectory, uri: &str| {
+        let request = http::Request::builder().uri(uri).body(()).unwrap();
+        dir.respond::<ByteData>(&request).map(|res| res.unwrap())
+    };
+
+    for warmup in [DirWarmup::Warm, DirWarmup::Cold] {
+        let dir =
+            ExposedDirectory::new_blocking(warmup, "/", root_str.clone(), |_: &str, _: &str| true)
+                .unwrap()
+                .with_trailing_slash_redirect(true);
+
+        let res = get(&dir, "/docs?lang=en").unwrap();
+        assert_eq!(res.status(), http::StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(res.headers()[http::header::LOCATION], "/docs/?lang=en");
+
+        let res = get(&dir, "/docs/?lang=en").unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.body().as_slice(), b"<html></html>");
+
+        let res = get(&dir, "/app.js").unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert!(get(&dir, "/app.js/").is_none());
+        assert!(get(&dir, "/missing").is_none());
+        assert!(get(&dir, "/docs/../app.js").is_none());
+
+        let dir = dir.with_trailing_slash_redirect(false);
+        let res = get(&dir, "/docs").unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.body().as_slice(), b"<html></html>");
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(feature = "expose")]
+#[test]
+fn test_exposed_directory_listing() {
+    use crate::{DirWarmup, ExposedDirectory, HttpFile};
+    use bytedata::ByteData;
+
+    let root =
+        std::env::temp_dir().join(format!("static-http-file-listing-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("docs")).unwrap();
+    std::fs::create_dir_all(root.join("assets")).unwrap();
+    std::fs::write(root.join("docs/index.html"), "<html></html>").unwrap();
+    std::fs::write(root.join("assets/app.js"), "console.log(1);").unwrap();
+    std::fs::write(root.join("assets/<b>&.txt"), "foo").unwrap();
+    std::fs::create_dir_all(root.join("my assets")).unwrap();
+    std::fs::write(root.join("my assets/a.txt"), "foo").unwrap();
+    let root_str = root.to_str().unwrap().to_owned();
+
+    for warmup in [DirWarmup::Warm, DirWarmup::Cold] {
+        let dir = ExposedDirectory::new_blocking(
+            warmup,
+            "/files",
+            root_str.clone(),
+            |_: &str, _: &str| true,
+        )
+        .unwrap();
+        assert!(dir.listing("/files/assets/").is_none());
+
+        let dir = dir.with_directory_listing(true);
+        // directories with an index are served as usual
+        assert!(dir.listing("/files/docs/").is_none());
+        assert!(dir.listing("/files/assets/app.js").is_none());
+
+        let listing = dir.listing("/files/assets/").unwrap();
+        assert_eq!(listing.content_type(), "text/html; charset=utf-8");
+        let html = core::str::from_utf8(listing.data()).unwrap();
+        assert!(html.contains("<title>Index of /files/assets/</title>"));
+        // the files are not cache-busted, so the links do not carry the etag
+        assert!(html.contains("<a href=\"/files/assets/app.js\">app.js</a>"));
+        assert!(html.contains("<a href=\"/files/assets/%3Cb%3E%26.txt\">&lt;b&gt;&amp;.txt</a>"));
+        assert!(!html.contains("<b>"));
+
+        // the segments of the web path are percent-encoded as well
+        let spaced = dir.listing("/files/my%20assets/").unwrap();
+        let spaced = core::str::from_utf8(spaced.data()).unwrap().to_owned();
+        assert!(spaced.contains("<a href=\"/files/my%20assets/a.txt\">a.txt</a>"));
+        assert!(spaced.contains("<title>Index of /files/my assets/</title>"));
+
+        let html = core::str::from_utf8(dir.listing("/files").unwrap().data())
+            .unwrap()
+            .to_owned();
+        let assets = html.find("<a href=\"/files/assets/\">assets/</a>").unwrap();
+        let docs = html.find("<a href=\"/files/docs/\">docs/</a>").unwrap();
+        assert!(assets < docs);
+        assert!(html.contains("<a href=\"/files/my%20assets/\">my assets/</a>"));
+
+        let request = http::Request::builder()
+            .uri("/files/assets")
+            .body(())
+            .unwrap();
+        let res = dir.respond::<ByteData>(&request).unwrap().unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(
+            res.headers()[http::header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(res.body().as_slice(), listing.data());
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_exposed_directory_parallel() {
+    use crate::{DirWarmup, ExposeError, ExposedDirectory};
+
+    let root =
+        std::env::temp_dir().join(format!("static-http-file-parallel-{}", std::process::id()));
+    for d in 0..8 {
+        std::fs::create_dir_all(root.join(format!("dir{}/nested", d))).unwrap();
+        for f in 0..16 {
+            std::fs::write(
+                root.join(format!("dir{}/file{}.txt", d, f)),
+                format!("{} {}", d, f),
+            )
+            .unwrap();
+            std::fs::write(
+                root.join(format!("dir{}/nested/file{}.txt", d, f)),
+                format!("{}", f),
+            )
+            .unwrap();
+        }
+    }
+    let root_str = root.to_str().unwrap().to_owned();
+
+    let filter = |_: &str, _: &str| true;
+    let serial =
+        ExposedDirectory::new_blocking(DirWarmup::Warm, "/", root_str.clone(), filter).unwrap();
+    let parallel =
+        ExposedDirectory::new_blocking_parallel(DirWarmup::Warm, "/", root_str, filter).unwrap();
+    for d in 0..8 {
+        for f in 0..16 {
+            for path in [
+                format!("/dir{}/file{}.txt", d, f),
+                format!("/dir{}/nested/file{}.txt", d, f),
+            ] {
+                let expected = serial.get(&path).unwrap();
+                let file = parallel.get(&path).unwrap();
+                assert_eq!(file.data, expected.data);
+                assert_eq!(file.etag, expected.etag);
+            }
+        }
+    }
+    assert!(matches!(
+        parallel.get("/dir8/file0.txt"),
+        Err(ExposeError::NotFound)
+    ));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+/// Compares the warmup time of a 10k-file tree read serially and in parallel.
+///
+/// Run with `cargo test --release --features rayon -- --ignored --nocapture bench_exposed_directory_parallel`.
+#[cfg(feature = "rayon")]
+#[test]
+#[ignore]
+fn bench_exposed_directory_parallel() {
+    use crate::{DirWarmup, ExposedDirectory};
+    use std::time::Instant;
+
+    let root = std::env::temp_dir().join(format!(
+        "static-http-file-parallel-bench-{}",
+        std::process::id()
+    ));
+    let data = vec![b'x'; 16 * 1024];
+    for d in 0..100 {
+        std::fs::create_dir_all(root.join(format!("dir{}", d))).unwrap();
+        for f in 0..100 {
+            std::fs::write(root.join(format!("dir{}/file{}.txt", d, f)), &data).unwrap();
+        }
+    }
+    let root_str = root.to_str().unwrap().to_owned();
+    let filter = |_: &str, _: &str| true;
+
+    // the first read warms the page cache, so both measurements read from memory
+    ExposedDirectory::new_blocking(DirWarmup::Warm, "/", root_str.clone(), filter).unwrap();
+    let start = Instant::now();
+    ExposedDirectory::new_blocking(DirWarmup::Warm, "/", root_str.clone(), filter).unwrap();
+    let serial = start.elapsed();
+    let start = Instant::now();
+    ExposedDirectory::new_blocking_parallel(DirWarmup::Warm, "/", root_str, filter).unwrap();
+    let parallel = start.elapsed();
+    println!(
+        "10k files: serial {:?}, parallel {:?} on {} threads, {:.1}x speedup",
+        serial,
+        parallel,
+        rayon::current_num_threads(),
+        serial.as_secs_f64() / parallel.as_secs_f64()
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(feature = "expose")]
+#[test]
+fn test_directory_router() {
+    use crate::{DirWarmup, DirectoryRouter, ExposeError, ExposedDirectory};
+
+    let root = std::env::temp_dir().join(format!("static-http-file-router-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("static/media")).unwrap();
+    std::fs::create_dir_all(root.join("media")).unwrap();
+    std::fs::write(root.join("static/app.js"), "console.log(1);").unwrap();
+    std::fs::write(root.join("static/media/nested.txt"), "static").unwrap();
+    std::fs::write(root.join("media/nested.txt"), "media").unwrap();
+    std::fs::write(root.join("media/index.html"), "<html></html>").unwrap();
+    let dir = |web_path: &'static str, path: &str| {
+        let path = root.join(path).to_str().unwrap().to_owned();
+        ExposedDirectory::new_blocking(DirWarmup::Warm, web_path, path, |_: &str, _: &str| true)
+            .unwrap()
+    };
+    let data =
+        |router: &DirectoryRouter, path: &str| router.get(path).map(|file| file.data.clone());
+
+    let mut router = DirectoryRouter::new();
+    router
+        .add("/static", dir("/static", "static"))
+        .add("/media/", dir("", "media"));
+
+    // non-overlapping mounts
+    assert_eq!(
+        data(&router, "/static/app.js").unwrap().as_slice(),
+        b"console.log(1);"
+    );
+    assert_eq!(
+        data(&router, "/static/media/nested.txt")
+            .unwrap()
+            .as_slice(),
+        b"static"
+    );
+    assert_eq!(
+        data(&router, "/media/nested.txt").unwrap().as_slice(),
+        b"media"
+    );
+    assert_eq!(
+        data(&router, "/media").unwrap().as_slice(),
+        b"<html></html>"
+    );
+    assert!(matches!(
+        router.get("/staticx/app.js"),
+        Err(ExposeError::NotFound)
+    ));
+    assert!(matches!(router.get("/app.js"), Err(ExposeError::NotFound)));
+
+    // overlapping mounts use the longest matching prefix
+    router.add("/static/media", dir("/assets", "media"));
+    assert_eq!(
+        data(&router, "/static/media/nested.txt")
+            .unwrap()
+            .as_slice(),
+        b"media"
+    );
+    assert_eq!(
+        data(&router, "/static/app.js").unwrap().as_slice(),
+        b"console.log(1);"
+    );
+    assert!(matches!(
+        router.get("/static/media/app.js"),
+        Err(ExposeError::NotFound)
+    ));
+
+    // mounting at the same prefix replaces the directory
+    router.add("/static/media/", dir("", "static/media"));
+    assert_eq!(
+        data(&router, "/static/media/nested.txt")
+            .unwrap()
+            .as_slice(),
+        b"static"
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_options_accept_ranges() {
+    use crate::{const_http_file, ConstHttpFile};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+    let request = || {
+        http::Request::builder()
+            .method(http::Method::OPTIONS)
+            .uri("/digits.txt")
+            .body(())
+            .unwrap()
+    };
+
+    let res = FILE.respond::<ByteData>(&request()).unwrap();
+    assert_eq!(res.status(), http::StatusCode::NO_CONTENT);
+    assert_eq!(res.headers()[http::header::ALLOW], "GET, HEAD, OPTIONS");
+    assert_eq!(res.headers()[http::header::ACCEPT_RANGES], "bytes");
+
+    let unranged = TestFile {
+        accepts_ranges: false,
+        ..TestFile::new(FILE)
+    };
+    let res = unranged.respond::<ByteData>(&request()).unwrap();
+    assert_eq!(res.status(), http::StatusCode::NO_CONTENT);
+    assert!(res.headers().get(http::header::ACCEPT_RANGES).is_none());
+}
+
+#[test]
+fn test_options_ignores_conditionals() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let options = |name: http::header::HeaderName, value: &str| {
+        let request = http::Request::builder()
+            .method(http::Method::OPTIONS)
+            .uri("/foo.txt")
+            .header(name, value)
+            .body(())
+            .unwrap();
+        FILE.respond::<ByteData>(&request).unwrap()
+    };
+
+    let conditionals = [
+        (http::header::IF_NONE_MATCH, FILE.etag),
+        (http::header::IF_NONE_MATCH, "*"),
+        (http::header::IF_MATCH, "\"other\""),
+        (http::header::IF_MATCH, FILE.etag),
+        (http::header::RANGE, "bytes=0-1"),
+        (http::header::RANGE, "bytes=100-"),
+    ];
+    for (name, value) in conditionals {
+        let res = options(name, value);
+        assert_eq!(res.status(), http::StatusCode::NO_CONTENT);
+        assert_eq!(res.headers()[http::header::ALLOW], "GET, HEAD, OPTIONS");
+        assert!(res.body().is_empty());
+    }
+}
+
+#[test]
+fn test_options_asterisk() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let options = |uri: &str| {
+        let request = http::Request::builder()
+            .method(http::Method::OPTIONS)
+            .uri(uri)
+            .body(())
+            .unwrap();
+        FILE.respond::<ByteData>(&request).unwrap()
+    };
+
+    let res = options("*");
+    assert_eq!(res.status(), http::StatusCode::NO_CONTENT);
+    assert_eq!(res.headers()[http::header::ALLOW], "GET, HEAD, OPTIONS");
+    assert!(res.headers().get(http::header::ETAG).is_none());
+    assert!(res.headers().get(http::header::CONTENT_TYPE).is_none());
+
+    let res = options("/foo.txt");
+    assert_eq!(res.status(), http::StatusCode::NO_CONTENT);
+    assert_eq!(res.headers()[http::header::ETAG], "\"q25fZAd-fY\"");
+}
+
+#[test]
+fn test_short_version() {
+    use crate::short_version;
+
+    const FOO: [u8; 8] = short_version(b"foo");
+    const FOO_AGAIN: [u8; 8] = short_version(b"foo");
+    const BAR: [u8; 8] = short_version(b"bar");
+    assert_eq!(&FOO, b"vrhf5xu2");
+    assert_eq!(FOO, FOO_AGAIN);
+    assert_ne!(FOO, BAR);
+    assert_eq!(short_version(b"foo"), FOO);
+    assert!(
+        BAR[0] == b'v'
+            && BAR[1..]
+                .iter()
+                .all(|b| b.is_ascii_digit() || b.is_ascii_lowercase())
+    );
+}
+
+#[test]
+fn test_cachebust_is_fresh() {
+    use crate::{const_http_file, CacheBusting, ConstHttpFileResponse, QueryCacheBustedHttpFile};
+    use bytedata::ByteData;
+    use core::num::NonZeroU8;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let uri = |uri: &str| http::Uri::try_from(uri).unwrap();
+
+    assert!(FILE.cachebust_is_fresh(&uri("/foo.txt")));
+
+    let query = QueryCacheBustedHttpFile::new("/foo.txt", "v", FILE);
+    assert!(query.cachebust_is_fresh(&uri("/foo.txt?v=q25fZAd-fY")));
+    assert!(query.cachebust_is_fresh(&uri("/foo.txt?a=1&v=q25fZAd-fY")));
+    assert!(!query.cachebust_is_fresh(&uri("/foo.txt?v=old")));
+    assert!(!query.cachebust_is_fresh(&uri("/foo.txt?va=q25fZAd-fY")));
+    assert!(!query.cachebust_is_fresh(&uri("/foo.txt")));
+
+    let suffix = TestFile {
+        cache_busting: CacheBusting::Suffix(NonZeroU8::new(b'~'), None),
+        ..TestFile::new(FILE)
+    };
+    assert!(suffix.cachebust_is_fresh(&uri("/foo~q25fZAd-fY.txt")));
+    assert!(suffix.cachebust_is_fresh(&uri("/foo~q25fZAd-fY")));
+    assert!(suffix.cachebust_is_fresh(&uri("/foo%7Eq25fZAd-fY.txt")));
+    assert!(!suffix.cachebust_is_fresh(&uri("/foo~old.txt")));
+    assert!(!suffix.cachebust_is_fresh(&uri("/fooq25fZAd-fY.txt")));
+    assert!(!suffix.cachebust_is_fresh(&uri("/foo.txt")));
+
+    // the decision agrees with the redirect logic
+    for path in ["/foo~q25fZAd-fY.txt", "/foo~old.txt", "/foo.txt"] {
+        let uri = uri(path);
+        assert_eq!(
+            suffix.cachebust_is_fresh(&uri),
+            suffix
+                .cachebust_suffix::<ByteData>(&uri, NonZeroU8::new(b'~'), None)
+                .is_none()
+        );
+    }
+}
+
+#[test]
+fn test_const_http_file_cache_busting() {
+    use crate::{const_http_file, CacheBusting, ConstHttpFile, HttpFile, HttpFileResponse};
+    use bytedata::{ByteData, StringData};
+    use core::num::NonZeroU8;
+
+    const SUFFIX: &CacheBusting = &CacheBusting::Suffix(NonZeroU8::new(b'~'), None);
+    const QUERY: &CacheBusting = &CacheBusting::Query(StringData::from_static("v"));
+    const PLAIN: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    const BUSTED: ConstHttpFile = PLAIN.with_cache_busting(SUFFIX);
+
+    assert_eq!(PLAIN.cache_busting(), &CacheBusting::None);
+    assert!(!PLAIN.cache_control().is_immutable());
+    assert_eq!(BUSTED.cache_busting(), SUFFIX);
+    assert!(BUSTED.cache_control().is_immutable());
+
+    let request = |uri: &str| http::Request::get(uri).body(()).unwrap();
+    let res = BUSTED
+        .respond::<ByteData>(&request("/foo~q25fZAd-fY.txt"))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    let res = BUSTED
+        .respond::<ByteData>(&request("/foo~old.txt"))
+        .unwrap();
+    assert!(res.status().is_redirection());
+
+    let query = PLAIN.with_cache_busting(QUERY);
+    let res = query
+        .respond::<ByteData>(&request("/foo.txt?v=q25fZAd-fY"))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    let res = query.respond::<ByteData>(&request("/foo.txt")).unwrap();
+    assert!(res.status().is_redirection());
+}
+
+#[test]
+fn test_suffix_cache_busted_http_file() {
+    use crate::{
+        const_http_file, static_http_file_suffixcache, CacheBusting, ConstHttpFile, HttpFile,
+        HttpFileResponse, SuffixCacheBustedHttpFile,
+    };
+    use alloc::format;
+    use bytedata::ByteData;
+    use core::num::NonZeroU8;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "application/javascript");
+    let file = SuffixCacheBustedHttpFile::new("/js/app.js", NonZeroU8::new(b'~'), FILE);
+    assert_eq!(file.url().as_str(), "/js/app~q25fZAd-fY.js");
+    assert_eq!(
+        file.cache_busting(),
+        &CacheBusting::Suffix(NonZeroU8::new(b'~'), None)
+    );
+    assert!(file.cache_control().is_immutable());
+    let file = SuffixCacheBustedHttpFile::new("/LICENSE", NonZeroU8::new(b'.'), FILE);
+    assert_eq!(file.url().as_str(), "/LICENSE.q25fZAd-fY");
+
+    let request = |uri: &str| http::Request::get(uri).body(()).unwrap();
+    let file = SuffixCacheBustedHttpFile::new("/js/app.js", NonZeroU8::new(b'~'), FILE);
+    let res = file
+        .respond_borrowed::<ByteData>(&request(file.url().as_str()))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+    let res = file
+        .respond_borrowed::<ByteData>(&request("/js/app~old.js"))
+        .unwrap();
+    assert!(res.status().is_redirection());
+    assert_eq!(res.headers()[http::header::LOCATION], file.url().as_str());
+
+    static STATIC: SuffixCacheBustedHttpFile<ConstHttpFile> =
+        static_http_file_suffixcache!("-", "../Cargo.toml", "application/toml");
+    assert_eq!(
+        STATIC.url().as_str(),
+        format!("../Cargo-{}.toml", STATIC.const_etag_str())
+    );
+    assert_eq!(STATIC.const_content_type(), "application/toml");
+    assert_eq!(
+        STATIC.const_cache_busting(),
+        &CacheBusting::Suffix(NonZeroU8::new(b'-'), None)
+    );
+}
+
+#[test]
+fn test_shared_http_file_from_const() {
+    use crate::{const_http_file, ConstHttpFile, HttpFile, SharedHttpFile};
+
+    const FILE: ConstHttpFile = const_http_file!("../.gitignore", "text/plain");
+    let shared = SharedHttpFile::from(FILE);
+    assert_eq!(shared.data.as_slice().as_ptr(), FILE.data.as_ptr());
+    assert_eq!(shared.data.len(), FILE.data.len());
+    assert_eq!(
+        shared.file.as_ref().map(|f| f.as_str()),
+        Some("../.gitignore")
+    );
+    assert_eq!(shared.content_type(), "text/plain");
+    assert_eq!(shared.etag(), FILE.etag);
+    assert_eq!(shared.content_encoding(), None);
+
+    const ENCODED: ConstHttpFile =
+        ConstHttpFile::new_encoded(b"\x1F\x8B\x08", "text/plain", "\"gz\"", "gzip");
+    let shared: SharedHttpFile<'static> = ENCODED.into();
+    assert_eq!(shared.file, None);
+    assert_eq!(shared.data.as_slice().as_ptr(), ENCODED.data.as_ptr());
+    assert_eq!(shared.content_encoding(), Some("gzip"));
+}
+
+#[test]
+fn test_response_header_pairs() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use alloc::vec::Vec;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    let request = http::Request::get("/foo.txt").body(()).unwrap();
+    let pairs = FILE.response_header_pairs(&request);
+    let expected = FILE
+        .response_headers(http::Response::builder())
+        .body(())
+        .unwrap();
+    assert_eq!(pairs.len(), expected.headers().len());
+    for (name, value) in expected.headers() {
+        assert!(pairs
+            .iter()
+            .any(|(n, v)| n == name.as_str() && v.as_bytes() == value.as_bytes()));
+    }
+    assert!(pairs
+        .iter()
+        .any(|(n, v)| n == "etag" && v == "\"q25fZAd-fY\""));
+
+    // the headers follow the response to the request
+    let request = http::Request::get("/foo.txt")
+        .header(http::header::RANGE, "bytes=1-")
+        .body(())
+        .unwrap();
+    let pairs = FILE.response_header_pairs(&request);
+    let ranges: Vec<_> = pairs.iter().filter(|(n, _)| n == "content-range").collect();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0].1, "bytes 1-2/3");
+}
+
+#[test]
+fn test_b64url_try() {
+    use crate::{b64url_const, b64url_encoded_len, b64url_try, B64Error};
+
+    let data: [u8; 32] = core::array::from_fn(|i| (i * 37 + 11) as u8);
+    for len in 0..data.len() {
+        let required = b64url_encoded_len(len);
+        let mut buf = [0u8; 48];
+        assert_eq!(b64url_try(&data[..len], &mut buf, 2), Ok(required));
+        let (expected, end) = b64url_const(&data[..len], [0u8; 48], 2);
+        assert_eq!(end, 2 + required);
+        assert_eq!(buf, expected);
+
+        let mut exact = [0u8; 48];
+        assert_eq!(
+            b64url_try(&data[..len], &mut exact[..required], 0),
+            Ok(required)
+        );
+        if required > 0 {
+            assert_eq!(
+                b64url_try(&data[..len], &mut exact[..required - 1], 0),
+                Err(B64Error::BufferTooSmall { required })
+            );
+        }
+    }
+    assert_eq!(
+        b64url_try(b"foo", &mut [0u8; 4], 5),
+        Err(B64Error::OffsetOutOfBounds)
+    );
+    assert_eq!(b64url_try(b"", &mut [0u8; 4], 4), Ok(0));
+}
+
+#[test]
+fn test_response_decision() {
+    use crate::{const_http_file, CacheBusting, ConstHttpFile, HttpFile, ResponseDecision};
+    use core::num::NonZeroU8;
+
+    const SUFFIX: &CacheBusting = &CacheBusting::Suffix(NonZeroU8::new(b'~'), None);
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+    let etag = FILE.etag;
+    let decide = |method: &str, headers: &[(&'static str, &'static str)]| {
+        ResponseDecision::new(&FILE, method, "/file.txt", None, |name| {
+            headers.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+        })
+    };
+
+    assert_eq!(decide("GET", &[]), ResponseDecision::Full { body: true });
+    assert_eq!(decide("HEAD", &[]), ResponseDecision::Full { body: false });
+    assert_eq!(decide("POST", &[]), ResponseDecision::MethodNotAllowed);
+    assert_eq!(decide("OPTIONS", &[]), ResponseDecision::Options);
+    assert_eq!(
+        ResponseDecision::new(&FILE, "OPTIONS", "*", None, |_| None),
+        ResponseDecision::ServerOptions
+    );
+    assert_eq!(
+        decide("GET", &[("if-match", "\"other\"")]),
+        ResponseDecision::PreconditionFailed
+    );
+    let decision = ResponseDecision::new(&FILE, "GET", "/file.txt", None, |name| {
+        (name == "if-none-match").then_some(etag)
+    });
+    assert_eq!(decision, ResponseDecision::NotModified);
+    assert_eq!(decision.status(), 304);
+    assert_eq!(
+        decide("GET", &[("range", "bytes=2-4")]),
+        ResponseDecision::PartialContent {
+            first: 2,
+            last: 4,
+            total: 10
+        }
+    );
+    assert_eq!(
+        decide("HEAD", &[("range", "bytes=2-4")]),
+        ResponseDecision::Full { body: false }
+    );
+    assert_eq!(
+        decide("GET", &[("range", "bytes=20-")]),
+        ResponseDecision::RangeNotSatisfiable { total: 10 }
+    );
+    assert_eq!(
+        decide("GET", &[("range", "bytes=2-4"), ("if-range", "\"other\"")]),
+        ResponseDecision::Full { body: true }
+    );
+
+    let busted = FILE.with_cache_busting(SUFFIX);
+    let decision = ResponseDecision::new(&busted, "GET", "/file~old.txt", None, |_| None);
+    let location = ["/file~", FILE.etag_str(), ".txt"].concat();
+    assert_eq!(decision, ResponseDecision::Redirect(location.clone()));
+    assert_eq!(decision.status(), 307);
+    assert_eq!(
+        ResponseDecision::new(&busted, "GET", &location, None, |_| None),
+        ResponseDecision::Full { body: true }
+    );
+}
+
+#[test]
+fn test_b64_const_padding() {
+    use crate::{b64_const, b64_encoded_len, b64url_const, Base64Alphabet};
+
+    let cases: [(&[u8], &[u8]); 5] = [
+        (b"", b""),
+        (b"f", b"Zg=="),
+        (b"fo", b"Zm8="),
+        (b"foo", b"Zm9v"),
+        (b"\xfb\xff\xbf", b"+/+/"),
+    ];
+    for (data, expected) in cases {
+        let (buf, end) = b64_const(data, [0u8; 8], 1, Base64Alphabet::Standard, true);
+        assert_eq!(&buf[1..end], expected);
+        assert_eq!(end - 1, b64_encoded_len(data.len(), true));
+
+        let (buf, end) = b64_const(data, [0u8; 8], 0, Base64Alphabet::Url, false);
+        assert_eq!((buf, end), b64url_const(data, [0u8; 8], 0));
+        assert_eq!(end, b64_encoded_len(data.len(), false));
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_std_http_file_max_size() {
+    use crate::StdHttpFile;
+
+    let err = StdHttpFile::new("src").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let file = StdHttpFile::new_with_max_size("Cargo.toml", 1 << 20).unwrap();
+    let len = file.data.len() as u64;
+    assert!(StdHttpFile::new_with_max_size("Cargo.toml", len).is_ok());
+    let err = StdHttpFile::new_with_max_size("Cargo.toml", len - 1).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_std_http_file_hashed_while_reading() {
+    use crate::{compute_etag_nonconst, StdHttpFile};
+
+    // the data spans several reads, which must all be hashed in order
+    let data: alloc::vec::Vec<u8> = (0..300_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let path = std::env::temp_dir().join(format!(
+        "static-http-file-hashed-{}.bin",
+        std::process::id()
+    ));
+    std::fs::write(&path, &data).unwrap();
+    let path_str = path.to_str().unwrap().to_owned();
+
+    let file = StdHttpFile::new(path_str.clone()).unwrap();
+    assert_eq!(file.data.as_slice(), data.as_slice());
+    assert_eq!(file.etag, compute_etag_nonconst(&data));
+    let file = StdHttpFile::new_with_mime(path_str, "application/octet-stream").unwrap();
+    assert_eq!(file.etag, compute_etag_nonconst(&data));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_std_http_file_etag_algorithm() {
+    use crate::{EtagAlgorithm, StdHttpFile};
+
+    let data: alloc::vec::Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    let path = std::env::temp_dir().join(format!(
+        "static-http-file-etag-algorithm-{}.bin",
+        std::process::id()
+    ));
+    std::fs::write(&path, &data).unwrap();
+    let path_str = path.to_str().unwrap().to_owned();
+
+    let full = StdHttpFile::new_with_etag_algorithm(path_str.clone(), EtagAlgorithm::Full).unwrap();
+    assert_eq!(full.etag, StdHttpFile::new(path_str.clone()).unwrap().etag);
+
+    let sampled = EtagAlgorithm::Sampled {
+        prefix: 16,
+        suffix: 16,
+    };
+    let mut file = StdHttpFile::new_with_etag_algorithm(path_str, sampled).unwrap();
+    assert_eq!(file.etag, sampled.compute(&data));
+    assert_ne!(file.etag, full.etag);
+    // reloading keeps the algorithm the file was created with
+    file.reload().unwrap();
+    assert_eq!(file.etag, sampled.compute(&data));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_shared_http_file_from_reader() {
+    use crate::{compute_etag_nonconst, SharedHttpFile};
+
+    let data = std::fs::read("Cargo.toml").unwrap();
+    let file = std::fs::File::open("Cargo.toml").unwrap();
+    let shared = SharedHttpFile::from_reader(file, Some("Cargo.toml")).unwrap();
+    assert_eq!(shared.data.as_slice(), data.as_slice());
+    assert_eq!(shared.etag.as_str(), compute_etag_nonconst(&data));
+    assert_eq!(shared.file.as_ref().map(|f| f.as_str()), Some("Cargo.toml"));
+
+    let shared = SharedHttpFile::from_reader(&b"%PDF-1.7"[..], None).unwrap();
+    assert_eq!(shared.mime.as_str(), "application/pdf");
+
+    let shared = SharedHttpFile::from_reader_with_max_size(&b"%PDF-1.7"[..], None, 8).unwrap();
+    assert_eq!(shared.data.as_slice(), b"%PDF-1.7");
+    let err = SharedHttpFile::from_reader_with_max_size(&b"%PDF-1.7"[..], None, 7).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // a reader claiming to have read more than the buffer holds is rejected instead of trusted
+    struct Liar;
+    impl std::io::Read for Liar {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            Ok(buf.len() + 1)
+        }
+    }
+    let err = SharedHttpFile::from_reader(Liar, None).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_std_shared_http_file_conversion() {
+    use crate::{CacheBusting, HttpFile, SharedHttpFile, StdHttpFile};
+    use alloc::borrow::Cow;
+    use bytedata::{ByteData, StringData};
+
+    let file = StdHttpFile::new("Cargo.toml").unwrap();
+    let shared = SharedHttpFile::from(file.clone());
+    assert_eq!(shared.data.as_slice(), file.data.as_slice());
+    assert_eq!(shared.etag.as_str(), file.etag());
+    assert_eq!(shared.mime.as_str(), file.content_type());
+    assert_eq!(shared.file.as_ref().map(|f| f.as_str()), Some("Cargo.toml"));
+
+    let back = StdHttpFile::try_from(shared).unwrap();
+    assert_eq!(back.file, file.file);
+    assert_eq!(back.etag, file.etag);
+    assert_eq!(back.data.as_slice(), file.data.as_slice());
+
+    let unnamed = SharedHttpFile::new(
+        ByteData::from_static(b"abc"),
+        StringData::from_static("text/plain"),
+        StringData::from_static("\"abc\""),
+    );
+    let file = StdHttpFile::from(unnamed.clone());
+    assert_eq!(file.file, "");
+    assert_eq!(file.content_type(), "text/plain");
+    // static strings are borrowed instead of copied
+    assert!(matches!(file.mime, Cow::Borrowed("text/plain")));
+    assert!(matches!(file.etag, Cow::Borrowed("\"abc\"")));
+
+    let owned = SharedHttpFile::new(
+        ByteData::from_static(b"abc"),
+        StringData::from(String::from("text/plain")),
+        StringData::from_static("\"abc\""),
+    );
+    let file = StdHttpFile::from(owned);
+    assert!(matches!(file.mime, Cow::Owned(_)));
+    assert_eq!(file.content_type(), "text/plain");
+
+    // a file without cache busting cannot keep it
+    let busted = unnamed.with_cache_busting(CacheBusting::Query(StringData::from_static("v")));
+    let file = StdHttpFile::from(busted);
+    assert_eq!(file.cache_busting(), &CacheBusting::None);
+}
+
+#[test]
+fn test_shared_http_file_cache_busting() {
+    use crate::{CacheBusting, HttpFile, HttpFileResponse, SharedHttpFile};
+    use bytedata::{ByteData, StringData};
+    use core::num::NonZeroU8;
+
+    let file = SharedHttpFile::new_named(
+        ByteData::from_static(b"foo"),
+        StringData::from_static("text/plain"),
+        StringData::from_static("\"q25fZAd-fY\""),
+        StringData::from_static("/foo.txt"),
+    );
+    assert_eq!(file.cache_busting(), &CacheBusting::None);
+
+    let query = file
+        .clone()
+        .with_cache_busting(CacheBusting::Query(StringData::from_static("v")));
+    let request = http::Request::get("/foo.txt").body(()).unwrap();
+    let response = query.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 307);
+    assert_eq!(response.headers()["location"], "/foo.txt?v=q25fZAd-fY");
+    let request = http::Request::get("/foo.txt?v=q25fZAd-fY")
+        .body(())
+        .unwrap();
+    let response = query.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 200);
+
+    let suffix = file.with_cache_busting(CacheBusting::Suffix(NonZeroU8::new(b'~'), None));
+    let request = http::Request::get("/foo.txt").body(()).unwrap();
+    let response = suffix.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 307);
+    let request = http::Request::get("/foo~q25fZAd-fY.txt").body(()).unwrap();
+    let response = suffix.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 200);
+}
+
+#[test]
+fn test_header_pairs() {
+    use crate::{header_pairs, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile =
+        ConstHttpFile::new_encoded(b"foo", "text/plain", "\"q25fZAd-fY\"", "gzip");
+    let pairs = header_pairs(&FILE);
+    let names: alloc::vec::Vec<_> = pairs.iter().map(|(name, _)| &**name).collect();
+    assert_eq!(
+        names,
+        [
+            "content-type",
+            "etag",
+            "content-encoding",
+            "vary",
+            "x-content-type-options",
+            "accept-ranges",
+            "cache-control"
+        ]
+    );
+
+    let request = http::Request::get("/").body(()).unwrap();
+    let response = FILE.respond_borrowed::<ByteData>(&request).unwrap();
+    for (name, value) in &pairs {
+        assert_eq!(response.headers()[&**name], &**value);
+    }
+
+    // the pairs and the response headers share their order, including the headers added by wrappers
+    let vary = TestFile {
+        vary: &[http::header::ACCEPT_LANGUAGE],
+        ..TestFile::new(FILE)
+    };
+    assert_eq!(header_pairs(&vary), vary.response_header_pairs(&request));
+    assert!(header_pairs(&vary)
+        .iter()
+        .any(|(name, value)| name == "vary" && value == "accept-language, accept-encoding"));
+}
+
+#[cfg(feature = "expose")]
+#[test]
+fn test_expose_filter_strip_prefix_chain() {
+    use crate::{ExposeFilter, ExposeFilterTrait};
+
+    let filter = ExposeFilter::strip_prefix("public-");
+    assert_eq!(
+        filter
+            .filter_map_dir("/", "public-assets")
+            .unwrap()
+            .as_str(),
+        "assets"
+    );
+    assert!(filter.filter_map_dir("/", "assets").is_none());
+
+    let filter = ExposeFilter::not_hidden().chain(ExposeFilter::strip_prefix("public-"));
+    assert_eq!(
+        filter
+            .filter_map_file("/", "public-app.js")
+            .unwrap()
+            .as_str(),
+        "app.js"
+    );
+    assert!(filter.filter_map_file("/", ".public-app.js").is_none());
+    assert!(filter.filter_map_file("/", "app.js").is_none());
+
+    let filter = ExposeFilter::not_hidden().chain(ExposeFilter::not_hidden());
+    assert_eq!(
+        filter.filter_map_file("/", "app.js").unwrap().as_str(),
+        "app.js"
+    );
+
+    // only filters which may rename entries make a lookup of a missing name list the directory
+    assert!(!filter.renames_entries());
+    assert!(!ExposeFilter::extensions(&["js"]).renames_entries());
+    assert!(!ExposeFilter::all(&[ExposeFilter::not_hidden()]).renames_entries());
+    assert!(ExposeFilter::strip_prefix("public-").renames_entries());
+    assert!(ExposeFilter::not_hidden()
+        .chain(ExposeFilter::strip_prefix("public-"))
+        .renames_entries());
+    assert!(ExposeFilter::any(&[
+        ExposeFilter::not_hidden(),
+        ExposeFilter::strip_prefix("public-")
+    ])
+    .renames_entries());
+}
+
+#[cfg(feature = "expose")]
+#[test]
+fn test_exposed_directory_renaming_filter() {
+    use crate::{DirWarmup, ExposeFilter, ExposedDirectory};
+    use bytedata::ByteData;
+
+    let root =
+        std::env::temp_dir().join(format!("static-http-file-renaming-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("public-docs")).unwrap();
+    std::fs::write(root.join("public-docs/public-a.txt"), "foo").unwrap();
+    std::fs::write(root.join("public-app.js"), "console.log(1);").unwrap();
+    std::fs::write(root.join("app.js"), "secret").unwrap();
+    let root_str = root.to_str().unwrap().to_owned();
+
+    let get = |dir: &ExposedDirectory, uri: &str| {
+        let request = http::Request::builder().uri(uri).body(()).unwrap();
+        dir.respond::<ByteData>(&request).map(|res| res.unwrap())
+    };
+
+    for warmup in [DirWarmup::Warm, DirWarmup::Cold] {
+        let dir = ExposedDirectory::new_blocking(
+            warmup,
+            "/",
+            root_str.clone(),
+            ExposeFilter::strip_prefix("public-"),
+        )
+        .unwrap();
+
+        // the file exposed as `app.js` is read from `public-app.js`, not from the unexposed `app.js`
+        let res = get(&dir, "/app.js").unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.body().as_slice(), b"console.log(1);");
+        let res = get(&dir, "/docs/a.txt").unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.body().as_slice(), b"foo");
+
+        assert!(get(&dir, "/public-app.js").is_none());
+        assert!(get(&dir, "/public-docs/public-a.txt").is_none());
+        assert!(get(&dir, "/docs/public-a.txt").is_none());
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(feature = "expose")]
+#[test]
+fn test_expose_filter_all_any() {
+    use crate::{ExposeFilter, ExposeFilterTrait};
+
+    let js = ExposeFilter::all(&[
+        ExposeFilter::not_hidden(),
+        ExposeFilter::extensions(&["js"]),
+    ]);
+    assert!(js.filter_map_file("/", "app.js").is_some());
+    assert!(js.filter_map_file("/", ".app.js").is_none());
+    assert!(js.filter_map_file("/", "app.css").is_none());
+    assert!(js.filter_map_dir("/", "scripts").is_some());
+    assert!(js.filter_map_dir("/", ".git").is_none());
+
+    // the prefix is removed before the extension is checked, so the renamed entry is what the last filter sees
+    let renamed = ExposeFilter::all(&[
+        ExposeFilter::strip_prefix("public-"),
+        ExposeFilter::strip_prefix("v1-"),
+    ]);
+    assert_eq!(
+        renamed
+            .filter_map_file("/", "public-v1-app.js")
+            .unwrap()
+            .as_str(),
+        "app.js"
+    );
+    assert!(renamed.filter_map_file("/", "v1-public-app.js").is_none());
+
+    let either = ExposeFilter::any(&[
+        ExposeFilter::strip_prefix("public-"),
+        ExposeFilter::not_hidden(),
+    ]);
+    assert_eq!(
+        either.filter_map_file("/", "public-.env").unwrap().as_str(),
+        ".env"
+    );
+    assert_eq!(
+        either.filter_map_file("/", "app.js").unwrap().as_str(),
+        "app.js"
+    );
+    assert!(either.filter_map_file("/", ".env").is_none());
+
+    assert!(ExposeFilter::all(&[])
+        .filter_map_file("/", "app.js")
+        .is_some());
+    assert!(ExposeFilter::any(&[])
+        .filter_map_file("/", "app.js")
+        .is_none());
+}
+
+#[cfg(feature = "expose")]
+#[test]
+fn test_expose_error() {
+    use crate::{DirWarmup, ExposeError, ExposedDirectory};
+    use alloc::string::ToString;
+    use std::error::Error;
+
+    let root = std::env::temp_dir().join(format!("static-http-file-error-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("app.js"), "console.log(1);").unwrap();
+    let root_str = root.to_str().unwrap().to_owned();
+
+    let dir =
+        ExposedDirectory::new_blocking(DirWarmup::Cold, "/", root_str, |_: &str, _: &str| true)
+            .unwrap();
+    assert!(dir.get("/app.js").is_ok());
+    assert!(matches!(dir.get("/missing.js"), Err(ExposeError::NotFound)));
+    assert!(matches!(dir.get("/app.js/"), Err(ExposeError::NotFound)));
+
+    let err = ExposeError::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+    assert!(matches!(err, ExposeError::Io(_)));
+    assert!(err.source().is_some());
+    assert!(err.to_string().starts_with("failed to read file"));
+    assert!(ExposeError::NotFound.source().is_none());
+
+    // a file which cannot be read does not fail the warmup of the other files
+    std::fs::File::create(root.join("huge.bin"))
+        .unwrap()
+        .set_len(crate::DEFAULT_MAX_FILE_SIZE + 1)
+        .unwrap();
+    let root_str = root.to_str().unwrap().to_owned();
+    for warmup in [DirWarmup::Warm, DirWarmup::Hot] {
+        let dir =
+            ExposedDirectory::new_blocking(warmup, "/", root_str.clone(), |_: &str, _: &str| true)
+                .unwrap();
+        assert!(dir.get("/app.js").is_ok());
+        assert!(matches!(dir.get("/huge.bin"), Err(ExposeError::Io(_))));
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_select_encoding() {
+    use crate::select_encoding;
+
+    let available = ["br", "gzip", "identity"];
+    assert_eq!(
+        select_encoding("br;q=1.0, gzip;q=0.8, *;q=0.1", &available),
+        Some("br")
+    );
+    assert_eq!(
+        select_encoding("br;q=0.5, gzip;q=0.8", &available),
+        Some("identity")
+    );
+    assert_eq!(
+        select_encoding("br;q=0.5, gzip;q=0.8, identity;q=0", &available),
+        Some("gzip")
+    );
+    assert_eq!(
+        select_encoding("GZIP;Q=0.001", &available),
+        Some("identity")
+    );
+    assert_eq!(
+        select_encoding("GZIP;Q=0.001, identity;q=0", &available),
+        Some("gzip")
+    );
+
+    // q=0 excludes a coding, even if the wildcard allows it
+    assert_eq!(select_encoding("gzip;q=0, identity;q=0", &available), None);
+    assert_eq!(select_encoding("*;q=0, gzip;q=0", &available), None);
+    assert_eq!(select_encoding("*;q=0", &["identity"]), None);
+    assert_eq!(select_encoding("*;q=0.5, gzip;q=0", &available), Some("br"));
+    assert_eq!(select_encoding("*;q=0.5, gzip;q=0", &["gzip"]), None);
+
+    // the wildcard applies to everything not listed, including identity
+    assert_eq!(select_encoding("*", &available), Some("br"));
+    assert_eq!(select_encoding("*;q=0.1, gzip", &available), Some("gzip"));
+    assert_eq!(select_encoding("*", &["identity"]), Some("identity"));
+
+    // an empty header only allows the data without coding
+    assert_eq!(select_encoding("", &available), Some("identity"));
+    assert_eq!(select_encoding("", &["gzip"]), None);
+
+    // malformed quality values are ignored
+    assert_eq!(
+        select_encoding("gzip;q=2, br;q=0.0001", &["gzip", "br"]),
+        None
+    );
+    assert_eq!(select_encoding("gzip;q=1.000", &["gzip"]), Some("gzip"));
+    assert_eq!(select_encoding("gzip;q=1.001", &["gzip"]), None);
+}
+
+#[test]
+fn test_negotiated_http_file() {
+    use crate::{
+        const_http_file, select_media_type, ConstHttpFile, HttpFileResponse, NegotiatedHttpFile,
+    };
+    use bytedata::ByteData;
+
+    const HTML: ConstHttpFile =
+        const_http_file!(b"<!DOCTYPE html>" as &[u8], "text/html; charset=utf-8");
+    const XHTML: ConstHttpFile =
+        const_http_file!(b"<?xml version=\"1.0\"?>" as &[u8], "application/xhtml+xml");
+    let page = NegotiatedHttpFile::new(HTML).with_variant(XHTML);
+    let respond = |accept: Option<&str>| {
+        let mut request = http::Request::get("/page");
+        if let Some(accept) = accept {
+            request = request.header(http::header::ACCEPT, accept);
+        }
+        page.respond_borrowed::<ByteData>(&request.body(()).unwrap())
+            .unwrap()
+    };
+
+    let res = respond(Some("application/xhtml+xml,text/html;q=0.9,*/*;q=0.8"));
+    assert_eq!(
+        res.headers()[http::header::CONTENT_TYPE],
+        "application/xhtml+xml"
+    );
+    assert_eq!(res.headers()[http::header::ETAG], XHTML.etag);
+    assert_eq!(res.body().as_slice(), XHTML.data);
+    assert_eq!(res.headers()[http::header::VARY], "accept");
+
+    for accept in [
+        None,
+        Some("*/*"),
+        Some("text/*"),
+        Some("image/png"),
+        Some("application/xhtml+xml;q=0"),
+    ] {
+        let res = respond(accept);
+        assert_eq!(res.body().as_slice(), HTML.data, "{:?}", accept);
+        assert_eq!(res.headers()[http::header::VARY], "accept");
+    }
+
+    // the most specific range determines the quality
+    let res = respond(Some("application/*;q=0.1, text/html;q=0.5, */*"));
+    assert_eq!(res.body().as_slice(), HTML.data);
+    let res = respond(Some("text/*;q=0.1, */*"));
+    assert_eq!(res.body().as_slice(), XHTML.data);
+
+    assert_eq!(
+        select_media_type(
+            "text/html;level=1;q=0.5, application/*;q=0.6",
+            &["text/html", "application/json"]
+        ),
+        Some("application/json")
+    );
+    assert_eq!(select_media_type("", &["text/html"]), None);
+
+    // the guard evaluates the conditional headers against the selected variant
+    let request = http::Request::get("/page")
+        .header(http::header::ACCEPT, "application/xhtml+xml")
+        .header(http::header::IF_NONE_MATCH, XHTML.etag)
+        .body(())
+        .unwrap();
+    match page.respond_guard::<ByteData>(&request) {
+        Err(Ok(res)) => {
+            assert_eq!(res.status(), http::StatusCode::NOT_MODIFIED);
+            assert_eq!(res.headers()[http::header::ETAG], XHTML.etag);
+            assert_eq!(res.headers()[http::header::VARY], "accept");
+        }
+        _ => panic!("expected a not modified response"),
+    }
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn test_negotiated_http_file_delegates() {
+    use crate::{
+        const_http_file, ConstHttpFile, GzipOnDemand, HttpFileResponse, NegotiatedHttpFile,
+    };
+    use bytedata::ByteData;
+
+    const HTML: ConstHttpFile = const_http_file!(
+        b"<!DOCTYPE html><p>hello hello hello hello hello hello hello</p>" as &[u8],
+        "text/html"
+    );
+    const XHTML: ConstHttpFile = const_http_file!(
+        b"<?xml version=\"1.0\"?><p>hello hello hello hello hello hello hello</p>" as &[u8],
+        "application/xhtml+xml"
+    );
+    let page =
+        NegotiatedHttpFile::new(GzipOnDemand::new(HTML)).with_variant(GzipOnDemand::new(XHTML));
+    let request = http::Request::get("/page")
+        .header(http::header::ACCEPT, "application/xhtml+xml")
+        .header(http::header::ACCEPT_ENCODING, "gzip")
+        .body(())
+        .unwrap();
+
+    // the variant compresses its own data, and the headers selecting the variant are added to its `Vary`
+    let res = page.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(
+        res.headers()[http::header::CONTENT_TYPE],
+        "application/xhtml+xml"
+    );
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "gzip");
+    assert_eq!(res.headers()[http::header::VARY], "accept, accept-encoding");
+    assert_ne!(res.body().as_slice(), XHTML.data);
+
+    let res = page.respond::<ByteData>(&request).unwrap();
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "gzip");
+    assert_eq!(res.headers()[http::header::VARY], "accept, accept-encoding");
+}
+
+#[test]
+fn test_negotiated_http_file_save_data() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse, NegotiatedHttpFile};
+    use bytedata::ByteData;
+
+    const FULL: ConstHttpFile = const_http_file!(b"a full quality image" as &[u8], "image/png");
+    const LOW: ConstHttpFile = const_http_file!(b"a low image" as &[u8], "image/png");
+    const WEBP: ConstHttpFile = const_http_file!(b"a webp" as &[u8], "image/webp");
+    let image = NegotiatedHttpFile::new(FULL)
+        .with_variant(LOW)
+        .with_variant(WEBP)
+        .with_save_data(true);
+    let respond = |accept: Option<&str>, save_data: Option<&str>| {
+        let mut request = http::Request::get("/image");
+        if let Some(accept) = accept {
+            request = request.header(http::header::ACCEPT, accept);
+        }
+        if let Some(save_data) = save_data {
+            request = request.header("save-data", save_data);
+        }
+        image
+            .respond_borrowed::<ByteData>(&request.body(()).unwrap())
+            .unwrap()
+    };
+
+    // without the hint the variant is selected as usual
+    for save_data in [None, Some("off")] {
+        let res = respond(Some("image/png"), save_data);
+        assert_eq!(res.body().as_slice(), FULL.data);
+        assert_eq!(res.headers()[http::header::VARY], "accept, save-data");
+        let res = respond(None, save_data);
+        assert_eq!(res.body().as_slice(), FULL.data);
+    }
+
+    // the smallest acceptable variant is preferred, regardless of the quality
+    let res = respond(Some("image/png"), Some("on"));
+    assert_eq!(res.body().as_slice(), LOW.data);
+    assert_eq!(res.headers()[http::header::VARY], "accept, save-data");
+    let res = respond(Some("image/png, image/webp;q=0.1"), Some(" On "));
+    assert_eq!(res.body().as_slice(), WEBP.data);
+    let res = respond(None, Some("on"));
+    assert_eq!(res.body().as_slice(), WEBP.data);
+    let res = respond(Some("text/html"), Some("on"));
+    assert_eq!(res.body().as_slice(), FULL.data);
+
+    // the hint is ignored unless enabled
+    let image = image.with_save_data(false);
+    let request = http::Request::get("/image")
+        .header(http::header::ACCEPT, "image/png")
+        .header("save-data", "on")
+        .body(())
+        .unwrap();
+    let res = image.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(res.body().as_slice(), FULL.data);
+    assert_eq!(res.headers()[http::header::VARY], "accept");
+}
+
+#[test]
+fn test_const_len() {
+    use crate::{const_http_file, ConstHttpFile, QueryCacheBustedHttpFile};
+    use bytedata::StringData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"0123456789" as &[u8], "text/plain");
+    const LEN: usize = FILE.const_len();
+    assert_eq!(LEN, 10);
+
+    const BUSTED: QueryCacheBustedHttpFile<ConstHttpFile> = QueryCacheBustedHttpFile::new_const(
+        StringData::from_static("/file.txt?v=q25fZAd-fY"),
+        StringData::from_static("v"),
+        FILE,
+    );
+    const BUSTED_LEN: usize = BUSTED.const_data_len();
+    assert_eq!(BUSTED_LEN, LEN);
+}
+
+#[test]
+fn test_parse_query_string_form() {
+    use crate::{
+        parse_query_string_iter_fn, parse_query_string_raw, parse_query_string_utf8,
+        urldecode_form, urldecode_form_iter_fn,
+    };
+    use alloc::{borrow::Cow, vec::Vec};
+
+    let mut pairs = Vec::new();
+    let res = parse_query_string_iter_fn("a=hello+world&b+c=1%2B1", |k, v| {
+        pairs.push((k.into_owned(), v.into_owned()))
+    });
+    assert_eq!(res, Ok(()));
+    assert_eq!(
+        pairs,
+        [
+            (b"a".to_vec(), b"hello world".to_vec()),
+            (b"b c".to_vec(), b"1+1".to_vec()),
+        ]
+    );
+
+    let mut pairs = Vec::new();
+    let res = parse_query_string_raw("a=hello+world&b+c=1%2B1", |k, v| pairs.push((k, v)));
+    assert_eq!(res, Ok(()));
+    assert!(matches!(pairs[0].1, Cow::Borrowed(b"hello+world")));
+    assert_eq!(pairs[1].0.as_ref(), b"b+c");
+    assert_eq!(pairs[1].1.as_ref(), b"1+1");
+    assert_eq!(parse_query_string_raw("a=1&b=%2", |_, _| {}), Err(6));
+
+    let mut values = Vec::new();
+    parse_query_string_utf8("q=caf%C3%A9+au+lait", |_, v| values.push(v)).unwrap();
+    assert_eq!(values, ["café au lait"]);
+
+    assert_eq!(urldecode_form("+").unwrap().as_ref(), b" ");
+    assert!(matches!(
+        urldecode_form("plain"),
+        Ok(Cow::Borrowed(b"plain"))
+    ));
+    assert_eq!(urldecode_form("a+%"), Err(2));
+    let mut out = Vec::new();
+    let res = urldecode_form_iter_fn(b"++a", |chunk| out.extend_from_slice(chunk));
+    assert_eq!(res, Ok(()));
+    assert_eq!(out, b"  a");
+}
+
+#[test]
+fn test_etag_bytes() {
+    use crate::{const_http_file, ConstHttpFile, HttpFile, SharedHttpFile};
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    const ETAG: &[u8] = FILE.const_etag_bytes();
+    assert_eq!(ETAG.len(), 10);
+    assert_eq!(ETAG, FILE.etag_str().as_bytes());
+    assert_eq!(FILE.etag_bytes(), ETAG);
+
+    let weak = SharedHttpFile::from(FILE).with_weak_etag();
+    assert_eq!(weak.etag_bytes(), weak.etag_str().as_bytes());
+}
+
+#[test]
+fn test_head_response() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"Hello, world!" as &[u8], "text/plain");
+
+    for method in ["GET", "HEAD"] {
+        let request = http::Request::builder()
+            .method(method)
+            .uri("/hello.txt")
+            .body(())
+            .unwrap();
+        let response = FILE.head_response::<ByteData>(&request).unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-length"], "13");
+        assert_eq!(response.headers()["content-type"], "text/plain");
+        assert!(response.body().is_empty());
+    }
+
+    let request = http::Request::get("/hello.txt")
+        .header("range", "bytes=0-4")
+        .body(())
+        .unwrap();
+    let response = FILE.head_response::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 206);
+    assert_eq!(response.headers()["content-length"], "5");
+    assert_eq!(response.headers()["content-range"], "bytes 0-4/13");
+    assert!(response.body().is_empty());
+
+    let request = http::Request::get("/hello.txt")
+        .header("if-none-match", FILE.etag)
+        .body(())
+        .unwrap();
+    let response = FILE.head_response::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 304);
+    assert!(response.headers().get("content-length").is_none());
+
+    let request = http::Request::post("/hello.txt").body(()).unwrap();
+    let response = FILE.head_response::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 405);
+    assert!(response.body().is_empty());
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn test_head_response_gzip_on_demand() {
+    use crate::{const_http_file, ConstHttpFile, GzipOnDemand, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const TEXT: &[u8] =
+        b"body { margin: 0; padding: 0; } body { margin: 0; padding: 0; } body { margin: 0; }";
+    const FILE: ConstHttpFile = const_http_file!(TEXT, "text/css");
+    let file = GzipOnDemand::new(FILE);
+    let request = |method: &str| {
+        http::Request::builder()
+            .method(method)
+            .uri("/style.css")
+            .header(http::header::ACCEPT_ENCODING, "gzip")
+            .body(())
+            .unwrap()
+    };
+
+    let full = file.respond_borrowed::<ByteData>(&request("GET")).unwrap();
+    assert_eq!(full.headers()[http::header::CONTENT_ENCODING], "gzip");
+    let response = file.head_response::<ByteData>(&request("HEAD")).unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()[http::header::CONTENT_ENCODING], "gzip");
+    assert_eq!(
+        response.headers()[http::header::ETAG],
+        full.headers()[http::header::ETAG]
+    );
+    assert_eq!(
+        response.headers()[http::header::CONTENT_LENGTH],
+        full.body().len().to_string().as_str()
+    );
+    assert!(response.body().is_empty());
+
+    let pairs = file.response_header_pairs(&request("GET"));
+    assert!(pairs
+        .iter()
+        .any(|(name, value)| name == "content-encoding" && value == "gzip"));
+}
+
+#[cfg(feature = "brotli")]
+#[test]
+fn test_head_response_brotli_on_demand() {
+    use crate::{const_http_file, BrotliOnDemand, ConstHttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const TEXT: &[u8] =
+        b"body { color: red; } body { color: red; } body { color: red; } body { color: red; }";
+    const FILE: ConstHttpFile = const_http_file!(TEXT, "text/css");
+    let file = BrotliOnDemand::new(FILE);
+    let request = |method: &str| {
+        http::Request::builder()
+            .method(method)
+            .uri("/style.css")
+            .header(http::header::ACCEPT_ENCODING, "br")
+            .body(())
+            .unwrap()
+    };
+
+    let full = file.respond_borrowed::<ByteData>(&request("GET")).unwrap();
+    assert_eq!(full.headers()[http::header::CONTENT_ENCODING], "br");
+    let response = file.head_response::<ByteData>(&request("HEAD")).unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()[http::header::CONTENT_ENCODING], "br");
+    assert_eq!(
+        response.headers()[http::header::CONTENT_LENGTH],
+        full.body().len().to_string().as_str()
+    );
+    assert!(response.body().is_empty());
+
+    let pairs = file.response_header_pairs(&request("GET"));
+    assert!(pairs
+        .iter()
+        .any(|(name, value)| name == "content-encoding" && value == "br"));
+}
+
+#[test]
+fn test_head_response_negotiated() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse, NegotiatedHttpFile};
+    use bytedata::ByteData;
+
+    const HTML: ConstHttpFile =
+        const_http_file!(b"<!DOCTYPE html>" as &[u8], "text/html; charset=utf-8");
+    const XHTML: ConstHttpFile =
+        const_http_file!(b"<?xml version=\"1.0\"?>" as &[u8], "application/xhtml+xml");
+    let page = NegotiatedHttpFile::new(HTML).with_variant(XHTML);
+    let request = http::Request::head("/page")
+        .header(http::header::ACCEPT, "application/xhtml+xml")
+        .body(())
+        .unwrap();
+
+    let response = page.head_response::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()[http::header::CONTENT_TYPE],
+        "application/xhtml+xml"
+    );
+    assert_eq!(response.headers()[http::header::CONTENT_LENGTH], "21");
+    assert_eq!(response.headers()[http::header::ETAG], XHTML.etag);
+    assert!(response.body().is_empty());
+
+    let pairs = page.response_header_pairs(&request);
+    assert!(pairs
+        .iter()
+        .any(|(name, value)| name == "content-type" && value == "application/xhtml+xml"));
+}
+
+#[cfg(feature = "nonce")]
+#[test]
+fn test_head_response_nonce() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse, NonceHttpFile};
+    use bytedata::ByteData;
+
+    const TEXT: &[u8] = b"<script nonce=\"{{CSP_NONCE}}\">a()</script>";
+    const PAGE: ConstHttpFile = const_http_file!(TEXT, "text/html");
+    let page = NonceHttpFile::new(PAGE);
+    let request = http::Request::head("/").body(()).unwrap();
+
+    // the placeholder of 13 bytes is replaced by a nonce of 22 bytes
+    let response = page.head_response::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()[http::header::CONTENT_LENGTH],
+        (TEXT.len() + 9).to_string().as_str()
+    );
+    assert_eq!(response.headers()[http::header::CACHE_CONTROL], "no-store");
+    assert!(response.headers()[http::header::CONTENT_SECURITY_POLICY]
+        .to_str()
+        .unwrap()
+        .starts_with("script-src 'nonce-"));
+    assert!(response.body().is_empty());
+
+    let pairs = page.response_header_pairs(&request);
+    assert!(pairs
+        .iter()
+        .any(|(name, value)| name == "content-security-policy"
+            && value.starts_with("script-src 'nonce-")));
+    assert!(pairs
+        .iter()
+        .any(|(name, value)| name == "cache-control" && value == "no-store"));
+}
+
+#[test]
+fn test_suffix_cache_busting_dot() {
+    use crate::{const_http_file, ConstHttpFile, HttpFileResponse, SuffixCacheBustedHttpFile};
+    use bytedata::ByteData;
+    use core::num::NonZeroU8;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "application/javascript");
+    let file = SuffixCacheBustedHttpFile::new("/app.js", NonZeroU8::new(b'.'), FILE);
+    assert_eq!(file.url().as_str(), "/app.q25fZAd-fY.js");
+
+    let request = |uri: &str| http::Request::get(uri).body(()).unwrap();
+    let res = file
+        .respond_borrowed::<ByteData>(&request("/app.js"))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(res.headers()[http::header::LOCATION], "/app.q25fZAd-fY.js");
+    let res = file
+        .respond_borrowed::<ByteData>(&request("/app.q25fZAd-fY.js"))
+        .unwrap();
+    assert_eq!(res.status(), http::StatusCode::OK);
+
+    // a stale etag is replaced, while ordinary parts of the name are kept
+    let res = file
+        .respond_borrowed::<ByteData>(&request("/app.0123456789.js"))
+        .unwrap();
+    assert_eq!(res.headers()[http::header::LOCATION], "/app.q25fZAd-fY.js");
+    let res = file
+        .respond_borrowed::<ByteData>(&request("/jquery.min.js"))
+        .unwrap();
+    assert_eq!(
+        res.headers()[http::header::LOCATION],
+        "/jquery.min.q25fZAd-fY.js"
+    );
+}
+
+#[cfg(feature = "mime")]
+#[test]
+fn test_detect_mime() {
+    use crate::{detect_mime, KNOWN_EXTENSIONS};
+
+    for (ext, mime) in KNOWN_EXTENSIONS {
+        let path = alloc::format!("file.{}", ext);
+        let detected = detect_mime(&path, b"").unwrap();
+        assert_eq!(detected.essence_str(), *mime);
+    }
+    assert_eq!(
+        detect_mime("file", b"%PDF-1.7"),
+        Some(mime::APPLICATION_PDF)
+    );
+    assert_eq!(detect_mime("file", b""), None);
+}
+
+#[cfg(feature = "tower")]
+#[test]
+fn test_directory_service() {
+    use crate::{DirWarmup, DirectoryService, ExposedDirectory};
+    use bytedata::ByteData;
+    use core::future::Future;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use tower_service::Service;
+
+    // the service future is always ready, so it is polled once without a runtime
+    fn poll_once<F: Future + Unpin>(mut future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(
+            |_| RawWaker::new(core::ptr::null(), &VTABLE),
+            |_| {},
+            |_| {},
+            |_| {},
+        );
+        let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) };
+        match core::pin::Pin::new(&mut future).poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("the future is not ready"),
+        }
+    }
+
+    let root = std::env::temp_dir().join(format!("static-http-file-tower-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("app.js"), "console.log(1);").unwrap();
+    let root_str = root.to_str().unwrap().to_owned();
+
+    let dir =
+        ExposedDirectory::new_blocking(DirWarmup::Hot, "/static", root_str, |_: &str, _: &str| {
+            true
+        })
+        .unwrap();
+    let mut service: DirectoryService<ByteData<'static>> = DirectoryService::new(dir);
+    let cloned = service.clone();
+    assert!(cloned.directory().get("/static/app.js").is_ok());
+
+    let request = http::Request::get("/static/app.js").body(()).unwrap();
+    let response = poll_once(service.call(request)).unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.body().as_slice(), b"console.log(1);");
+
+    let request = http::Request::get("/static/missing.js").body(()).unwrap();
+    let response = poll_once(service.call(request)).unwrap();
+    assert_eq!(response.status(), 404);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(all(feature = "tower", feature = "tokio_1"))]
+#[test]
+fn test_directory_service_cold() {
+    use crate::{DirWarmup, DirectoryService, ExposedDirectory};
+    use bytedata::ByteData;
+    use tower_service::Service;
+
+    let root = std::env::temp_dir().join(format!(
+        "static-http-file-tower-cold-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("app.js"), "console.log(1);").unwrap();
+    let root_str = root.to_str().unwrap().to_owned();
+
+    let dir =
+        ExposedDirectory::new_blocking(DirWarmup::Cold, "/static", root_str, |_: &str, _: &str| {
+            true
+        })
+        .unwrap();
+    assert_eq!(dir.warmup(), DirWarmup::Cold);
+    let mut service: DirectoryService<ByteData<'static>> = DirectoryService::new(dir);
+    let runtime = tokio_1::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    // outside of a runtime the file is read by the caller
+    let future = service.call(http::Request::get("/static/app.js").body(()).unwrap());
+    let response = runtime.block_on(future).unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.body().as_slice(), b"console.log(1);");
+
+    // within a runtime the file is read on a blocking thread
+    runtime.block_on(async {
+        let request = http::Request::get("/static/app.js").body(()).unwrap();
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.body().as_slice(), b"console.log(1);");
+
+        let request = http::Request::get("/static/missing.js").body(()).unwrap();
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.status(), 404);
+    });
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_respond_with_cache() {
+    use crate::{const_http_file, CacheBusting, ConstHttpFile, HttpFileResponse};
+    use bytedata::{ByteData, StringData};
+    use core::time::Duration;
+
+    const BUSTING: &CacheBusting = &CacheBusting::Query(StringData::from_static("v"));
+    const FILE: ConstHttpFile =
+        const_http_file!(b"foo" as &[u8], "text/plain").with_cache_busting(BUSTING);
+
+    let request = http::Request::get("/foo.txt?v=q25fZAd-fY")
+        .body(())
+        .unwrap();
+    let response = FILE.respond::<ByteData>(&request).unwrap();
+    assert_eq!(
+        response.headers()["cache-control"],
+        "public, max-age=31536000, immutable"
+    );
+    let response = FILE
+        .respond_with_cache::<ByteData>(&request, Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["cache-control"], "public, max-age=60");
+    let response = FILE
+        .respond_with_cache::<ByteData>(&request, Duration::from_secs(2 * 31536000))
+        .unwrap();
+    assert_eq!(
+        response.headers()["cache-control"],
+        "public, max-age=63072000, immutable"
+    );
+
+    // the redirect is never cached
+    let request = http::Request::get("/foo.txt").body(()).unwrap();
+    let response = FILE
+        .respond_with_cache::<ByteData>(&request, Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(response.status(), 307);
+    assert_eq!(response.headers()["cache-control"], "no-cache");
+}
+
+#[cfg(feature = "brotli")]
+#[test]
+fn test_brotli_on_demand() {
+    use crate::{const_http_file, BrotliOnDemand, ConstHttpFile, HttpFile, HttpFileResponse};
+    use bytedata::ByteData;
+    use std::io::Read;
+
+    const TEXT: &[u8] =
+        b"body { color: red; } body { color: red; } body { color: red; } body { color: red; }";
+    const FILE: ConstHttpFile = const_http_file!(TEXT, "text/css");
+    let file = BrotliOnDemand::new(FILE);
+    let request = |accept: &str| {
+        http::Request::get("/style.css")
+            .header(http::header::ACCEPT_ENCODING, accept)
+            .body(())
+            .unwrap()
+    };
+
+    let res = file.respond_borrowed::<ByteData>(&request("gzip")).unwrap();
+    assert!(res.headers().get(http::header::CONTENT_ENCODING).is_none());
+    assert_eq!(res.headers()[http::header::VARY], "accept-encoding");
+    assert_eq!(res.body().as_slice(), TEXT);
+
+    let res = file
+        .respond_borrowed::<ByteData>(&request("gzip, br"))
+        .unwrap();
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "br");
+    assert_eq!(
+        res.headers()[http::header::ETAG],
+        format!("\"{}-br\"", FILE.etag_str()).as_str()
+    );
+    let mut decoded = Vec::new();
+    brotli::Decompressor::new(res.body().as_slice(), 4096)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, TEXT);
+
+    // data which does not shrink is never compressed
+    const TINY: ConstHttpFile = const_http_file!(b"a" as &[u8], "text/plain");
+    let tiny = BrotliOnDemand::new(TINY);
+    assert!(tiny.brotli().is_none());
+}
+
+#[cfg(all(feature = "brotli", feature = "flate2"))]
+#[test]
+fn test_brotli_over_gzip_on_demand() {
+    use crate::{const_http_file, BrotliOnDemand, ConstHttpFile, GzipOnDemand, HttpFileResponse};
+    use bytedata::ByteData;
+
+    const TEXT: &[u8] =
+        b"body { color: red; } body { color: red; } body { color: red; } body { color: red; }";
+    const FILE: ConstHttpFile = const_http_file!(TEXT, "text/css");
+    let file = BrotliOnDemand::new(GzipOnDemand::new(FILE));
+    let request = |accept: &str| {
+        http::Request::get("/style.css")
+            .header(http::header::ACCEPT_ENCODING, accept)
+            .body(())
+            .unwrap()
+    };
+
+    let res = file
+        .respond_borrowed::<ByteData>(&request("gzip, br"))
+        .unwrap();
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "br");
+    let res = file.respond_borrowed::<ByteData>(&request("gzip")).unwrap();
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "gzip");
+    assert_eq!(res.headers()[http::header::VARY], "accept-encoding");
+    let res = file
+        .respond_borrowed::<ByteData>(&request("identity"))
+        .unwrap();
+    assert!(res.headers().get(http::header::CONTENT_ENCODING).is_none());
+    assert_eq!(res.body().as_slice(), TEXT);
+
+    // the quality values of the client decide between brotli and gzip
+    for (accept, encoding) in [
+        ("gzip, br;q=0.5", "gzip"),
+        ("br;q=0.8, x-gzip;q=0.9", "gzip"),
+        ("gzip;q=0.5, br", "br"),
+        ("br;q=0.5, *", "gzip"),
+    ] {
+        let res = file.respond_borrowed::<ByteData>(&request(accept)).unwrap();
+        assert_eq!(res.headers()[http::header::CONTENT_ENCODING], encoding);
+    }
+    let mut req = request("gzip;q=0.5");
+    req.headers_mut().append(
+        http::header::ACCEPT_ENCODING,
+        http::header::HeaderValue::from_static("br;q=0.4"),
+    );
+    let res = file.respond_borrowed::<ByteData>(&req).unwrap();
+    assert_eq!(res.headers()[http::header::CONTENT_ENCODING], "gzip");
+}
+
+#[cfg(all(feature = "brotli", feature = "flate2"))]
+#[test]
+fn test_available_encodings() {
+    use crate::{
+        const_http_file, BrotliOnDemand, ConstHttpFile, GzipOnDemand, HttpFile, NegotiatedHttpFile,
+        WithSecurityHeaders,
+    };
+
+    const TEXT: &[u8] =
+        b"body { color: red; } body { color: red; } body { color: red; } body { color: red; }";
+    const FILE: ConstHttpFile = const_http_file!(TEXT, "text/css");
+    const TINY: ConstHttpFile = const_http_file!(b"a" as &[u8], "text/plain");
+    assert_eq!(FILE.available_encodings(), ["identity"]);
+
+    let file = BrotliOnDemand::new(GzipOnDemand::new(FILE));
+    assert_eq!(file.available_encodings(), ["br", "gzip", "identity"]);
+    assert_eq!(
+        WithSecurityHeaders::new(file).available_encodings(),
+        ["br", "gzip", "identity"]
+    );
+
+    // data which does not shrink is only served as is
+    let tiny = BrotliOnDemand::new(GzipOnDemand::new(TINY));
+    assert_eq!(tiny.available_encodings(), ["identity"]);
+
+    let negotiated =
+        NegotiatedHttpFile::new(GzipOnDemand::new(TINY)).with_variant(GzipOnDemand::new(FILE));
+    assert_eq!(negotiated.available_encodings(), ["identity", "gzip"]);
+}
+
+#[test]
+fn test_compute_weak_etag() {
+    use crate::{
+        compute_etag, compute_weak_etag, const_etag, const_weak_etag, validate_etag, ConstHttpFile,
+        HttpFile, SharedHttpFile,
+    };
+
+    const WEAK: &str = const_weak_etag!(b"foo");
+    assert_eq!(WEAK, "W/\"q25fZAd-fY\"");
+    assert_eq!(&compute_weak_etag(b"foo")[2..], &compute_etag(b"foo")[..]);
+    assert_eq!(&WEAK[2..], const_etag!(b"foo"));
+    assert!(validate_etag(WEAK));
+
+    const FILE: ConstHttpFile = ConstHttpFile::new(b"foo", "text/plain", WEAK);
+    const ETAG_STR: &str = FILE.const_etag_str();
+    assert_eq!(ETAG_STR, "q25fZAd-fY");
+    assert_eq!(FILE.etag_str(), "q25fZAd-fY");
+    assert_eq!(FILE.etag_bytes(), b"q25fZAd-fY");
+
+    let shared = SharedHttpFile::from(FILE);
+    assert_eq!(shared.etag(), WEAK);
+    assert_eq!(shared.etag_str(), "q25fZAd-fY");
+}
+
+#[test]
+fn test_const_directory() {
+    use crate::{const_directory, const_http_file, ConstDirectory, HttpFile};
+    use bytedata::ByteData;
+
+    const DIR: ConstDirectory = ConstDirectory::new(&[
+        ("/a/b.txt", const_http_file!(b"b" as &[u8], "text/plain")),
+        ("/a/c.txt", const_http_file!(b"c" as &[u8], "text/plain")),
+        (
+            "/app.js",
+            const_http_file!(b"console.log(1);" as &[u8], "text/javascript"),
+        ),
+        (
+            "/index.html",
+            const_http_file!(b"<!DOCTYPE html>" as &[u8], "text/html"),
+        ),
+    ]);
+    for (path, file) in DIR.files() {
+        assert_eq!(DIR.get(path), Some(file));
+    }
+    assert_eq!(DIR.get("/a/c.txt").map(|file| file.data()), Some(&b"c"[..]));
+    assert!(DIR.get("/a").is_none());
+    assert!(DIR.get("/zzz").is_none());
+    assert!(DIR.get("").is_none());
+    assert!(ConstDirectory::new(&[]).get("/").is_none());
+
+    let request = http::Request::get("/app.js").body(()).unwrap();
+    let response = DIR.respond::<ByteData>(&request).unwrap().unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "text/javascript");
+    let request = http::Request::get("/missing.js").body(()).unwrap();
+    assert!(DIR.respond::<ByteData>(&request).is_none());
+
+    const INCLUDED: ConstDirectory = const_directory! {
+        "/.gitignore" => "../.gitignore",
+        "/Cargo.toml" => "../Cargo.toml",
+    };
+    assert_eq!(INCLUDED.len(), 2);
+    assert_eq!(
+        INCLUDED.get("/Cargo.toml").and_then(|file| file.file),
+        Some("../Cargo.toml")
+    );
+}
+
+#[test]
+#[should_panic(expected = "sorted and unique")]
+fn test_const_directory_unsorted() {
+    use crate::{const_http_file, ConstDirectory};
+
+    static FILES: &[(&str, crate::ConstHttpFile)] = &[
+        ("/b", const_http_file!(b"b" as &[u8], "text/plain")),
+        ("/a", const_http_file!(b"a" as &[u8], "text/plain")),
+    ];
+    ConstDirectory::new(FILES);
+}
+
+#[test]
+fn test_http_date() {
+    use crate::{format_http_date, parse_http_date};
+
+    assert_eq!(&format_http_date(0), b"Thu, 01 Jan 1970 00:00:00 GMT");
+    assert_eq!(
+        &format_http_date(951782400),
+        b"Tue, 29 Feb 2000 00:00:00 GMT"
+    );
+    for seconds in [0, 59, 86399, 784111777, 951782400, 1709164799, 4102444800] {
+        let date = format_http_date(seconds);
+        let date = core::str::from_utf8(&date).unwrap();
+        assert_eq!(parse_http_date(date), Some(seconds), "{}", date);
+    }
+    assert_eq!(
+        parse_http_date("Sun, 06 Nov 1994 08:49:60 GMT"),
+        Some(784111799)
+    );
+    assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 UTC"), None);
+    assert_eq!(parse_http_date("Sun, 06 Nov 1994 24:49:37 GMT"), None);
+    assert_eq!(parse_http_date("Tue, 29 Feb 1994 08:49:37 GMT"), None);
+    assert_eq!(parse_http_date("Sun, 06 Nop 1994 08:49:37 GMT"), None);
+    assert_eq!(parse_http_date("Sun, 06 Nov 1969 08:49:37 GMT"), None);
+    assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), None);
+}
+
+#[test]
+fn test_if_unmodified_since() {
+    use crate::{const_http_file, header_pairs, ConstHttpFileResponse, ResponseDecision};
+    use bytedata::ByteData;
+
+    const FILE: ConstHttpFile = const_http_file!(b"foo" as &[u8], "text/plain");
+    const MODIFIED: u64 = 784111777;
+    const EARLIER: &str = "Sat, 05 Nov 1994 08:49:37 GMT";
+    const SAME: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+    const LATER: &str = "Mon, 07 Nov 1994 08:49:37 GMT";
+
+    let file = TestFile {
+        last_modified: Some(MODIFIED),
+        ..TestFile::new(FILE)
+    };
+    let decide = |headers: &[(&str, &str)]| {
+        ResponseDecision::new(&file, "GET", "/foo.txt", None, |name| {
+            headers
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| *value)
+        })
+    };
+    const FULL: ResponseDecision = ResponseDecision::Full { body: true };
+    let other = "\"other\"";
+
+    // If-Unmodified-Since on its own
+    assert_eq!(
+        decide(&[("if-unmodified-since", EARLIER)]),
+        ResponseDecision::PreconditionFailed
+    );
+    assert_eq!(decide(&[("if-unmodified-since", SAME)]), FULL);
+    assert_eq!(decide(&[("if-unmodified-since", LATER)]), FULL);
+    assert_eq!(decide(&[("if-unmodified-since", "invalid")]), FULL);
+
+    // If-Match > If-Unmodified-Since
+    assert_eq!(
+        decide(&[("if-match", FILE.etag), ("if-unmodified-since", EARLIER)]),
+        FULL
+    );
+    assert_eq!(
+        decide(&[("if-match", other), ("if-unmodified-since", LATER)]),
+        ResponseDecision::PreconditionFailed
+    );
+
+    // If-Unmodified-Since > If-None-Match
+    assert_eq!(
+        decide(&[
+            ("if-unmodified-since", EARLIER),
+            ("if-none-match", FILE.etag)
+        ]),
+        ResponseDecision::PreconditionFailed
+    );
+    assert_eq!(
+        decide(&[("if-unmodified-since", LATER), ("if-none-match", FILE.etag)]),
+        ResponseDecision::NotModified
+    );
+
+    // If-None-Match > If-Modified-Since
+    assert_eq!(
+        decide(&[("if-none-match", other), ("if-modified-since", LATER)]),
+        FULL
+    );
+    assert_eq!(
+        decide(&[("if-none-match", FILE.etag), ("if-modified-since", EARLIER)]),
+        ResponseDecision::NotModified
+    );
+
+    // If-Unmodified-Since > If-Modified-Since
+    assert_eq!(
+        decide(&[
+            ("if-unmodified-since", EARLIER),
+            ("if-modified-since", LATER)
+        ]),
+        ResponseDecision::PreconditionFailed
+    );
+
+    // If-Modified-Since on its own
+    assert_eq!(
+        decide(&[("if-modified-since", SAME)]),
+        ResponseDecision::NotModified
+    );
+    assert_eq!(decide(&[("if-modified-since", EARLIER)]), FULL);
+
+    // without a modification date the dates are ignored
+    let undated = ResponseDecision::new(&FILE, "GET", "/foo.txt", None, |name| match name {
+        "if-unmodified-since" => Some(EARLIER),
+        _ => None,
+    });
+    assert_eq!(undated, FULL);
+
+    let request = http::Request::get("/foo.txt").body(()).unwrap();
+    let response = file.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(response.headers()["last-modified"], SAME);
+    assert!(header_pairs(&file)
+        .iter()
+        .any(|(name, value)| *name == "last-modified" && value == SAME));
+    let request = http::Request::get("/foo.txt")
+        .header("if-unmodified-since", EARLIER)
+        .body(())
+        .unwrap();
+    let response = file.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 412);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_last_modified_of_files() {
+    use crate::{SharedHttpFile, StdHttpFile};
+    use bytedata::{ByteData, StringData};
+
+    const MODIFIED: u64 = 784111777;
+    const SAME: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+
+    let shared = SharedHttpFile {
+        modified: Some(MODIFIED),
+        ..SharedHttpFile::new(
+            ByteData::from_static(b"foo"),
+            StringData::from_static("text/plain"),
+            StringData::from_static("\"foo\""),
+        )
+    };
+    assert_eq!(shared.last_modified(), Some(MODIFIED));
+    let request = http::Request::get("/foo.txt")
+        .header("if-modified-since", SAME)
+        .body(())
+        .unwrap();
+    let response = shared.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 304);
+
+    // the date survives the conversions, and a file read from disk has the date of its metadata
+    let std_file = StdHttpFile::from(shared);
+    assert_eq!(std_file.last_modified(), Some(MODIFIED));
+    let response = std_file.respond_borrowed::<ByteData>(&request).unwrap();
+    assert_eq!(response.status(), 304);
+    assert_eq!(
+        SharedHttpFile::from(std_file).last_modified(),
+        Some(MODIFIED)
+    );
+    let file = StdHttpFile::new("Cargo.toml").unwrap();
+    let modified = std::fs::metadata("Cargo.toml")
+        .unwrap()
+        .modified()
+        .unwrap()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(file.last_modified(), Some(modified.as_secs()));
+    let response = file.into_response::<ByteData>().unwrap();
+    assert!(response.headers().contains_key("last-modified"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_std_http_file_reload() {
+    use crate::{HttpFile, StdHttpFile};
+
+    let path = std::env::temp_dir().join(format!(
+        "static-http-file-reload-{}.txt",
+        std::process::id()
+    ));
+    std::fs::write(&path, b"hello").unwrap();
+    let path_str = path.to_str().unwrap().to_owned();
+
+    let mut file = StdHttpFile::new(path_str.clone()).unwrap().with_weak_etag();
+    assert!(file.modified.is_some());
+    assert!(!file.is_stale().unwrap());
+    let etag = file.etag.clone();
+
+    std::fs::write(&path, b"hello, world").unwrap();
+    assert!(file.is_stale().unwrap());
+    file.reload().unwrap();
+    assert!(!file.is_stale().unwrap());
+    assert_eq!(file.data(), b"hello, world");
+    assert_eq!(file.content_type(), "text/plain");
+    assert!(file.etag.starts_with("W/\""));
+    assert_ne!(file.etag, etag);
+
+    // an unknown modification time cannot show that the file is unchanged
+    let undated = StdHttpFile {
+        modified: None,
+        ..file.clone()
+    };
+    assert!(undated.is_stale().unwrap());
+
+    // a mime type, etag or size limit given when the file was created is kept when it is reloaded
+    let mut typed = StdHttpFile::new_with_mime(path_str.clone(), "text/x-custom").unwrap();
+    let mut pinned = StdHttpFile::new_with_etag(path_str.clone(), "\"v1\"").unwrap();
+    let mut limited = StdHttpFile::new_with_max_size(path_str, 16).unwrap();
+    std::fs::write(&path, b"hello, world and more").unwrap();
+    typed.reload().unwrap();
+    assert_eq!(typed.content_type(), "text/x-custom");
+    assert_eq!(typed.data(), b"hello, world and more");
+    pinned.reload().unwrap();
+    assert_eq!(pinned.etag, "\"v1\"");
+    assert_eq!(pinned.data(), b"hello, world and more");
+    let err = limited.reload().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(limited.data(), b"hello, world");
+    file.reload().unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(file.is_stale().is_err());
+    assert!(file.reload().is_err());
+    assert_eq!(file.data(), b"hello, world and more");
+}
@@ -1,10 +1,13 @@
-use core::num::NonZeroU8;
-use std::path::Path;
+use core::num::{NonZeroU8, NonZeroUsize};
+use std::{path::Path, time::SystemTime};
 
 use alloc::borrow::Cow;
 use bytedata::ByteData;
 
-use super::super::std::{compute_etag_nonconst, StdHttpFile};
+use super::super::std::{
+    check_file_metadata, compute_etag_nonconst, file_too_large, EtagHasher, ReadOptions,
+    StdHttpFile, DEFAULT_MAX_FILE_SIZE,
+};
 use crate::{HttpFile, HttpFileResponse};
 
 /// A static HTTP file that can be computed at compile time or in other constant contexts.
@@ -35,6 +38,9 @@ impl TokioHttpFile {
             data,
             mime,
             etag,
+            encoding: None,
+            modified: None,
+            options: ReadOptions::DEFAULT,
         }
         .into_tokio_file()
     }
@@ -51,6 +57,9 @@ impl TokioHttpFile {
             data,
             mime,
             etag: Cow::Owned(etag),
+            encoding: None,
+            modified: None,
+            options: ReadOptions::DEFAULT,
         }
         .into_tokio_file()
     }
@@ -58,15 +67,70 @@ impl TokioHttpFile {
     /// Create a new [`TokioHttpFile`] from a path.
     pub async fn new(path: impl Into<Cow<'static, str>>) -> std::io::Result<Self> {
         let path: Cow<'static, str> = path.into();
-        let data = read_file(path.as_ref().as_ref()).await?;
-        let mime =
-            crate::detect_mime_type(path.as_ref(), &data).unwrap_or("application/octet-data");
-        let etag = compute_etag_nonconst(&data);
+        let (data, modified, etag) =
+            read_file_hashed(path.as_ref().as_ref(), DEFAULT_MAX_FILE_SIZE).await?;
+        let mime = crate::detect_mime_type(path.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME);
         Ok(StdHttpFile {
             file: path,
             data: ByteData::from_shared(data),
             mime: Cow::Borrowed(mime),
             etag: Cow::Owned(etag),
+            encoding: None,
+            modified,
+            options: ReadOptions::DEFAULT,
+        }
+        .into_tokio_file())
+    }
+
+    /// Create a new [`TokioHttpFile`] from a path, reading at most `max_bytes` bytes instead of [`DEFAULT_MAX_FILE_SIZE`].
+    ///
+    /// Returns an error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) if the path is not a regular file,
+    /// and of kind [`InvalidData`](std::io::ErrorKind::InvalidData) if the file is larger than `max_bytes`.
+    pub async fn new_with_max_size(
+        path: impl Into<Cow<'static, str>>,
+        max_bytes: u64,
+    ) -> std::io::Result<Self> {
+        let path: Cow<'static, str> = path.into();
+        let (data, modified, etag) = read_file_hashed(path.as_ref().as_ref(), max_bytes).await?;
+        let mime = crate::detect_mime_type(path.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME);
+        Ok(StdHttpFile {
+            file: path,
+            data: ByteData::from_shared(data),
+            mime: Cow::Borrowed(mime),
+            etag: Cow::Owned(etag),
+            encoding: None,
+            modified,
+            options: ReadOptions {
+                max_bytes,
+                ..ReadOptions::DEFAULT
+            },
+        }
+        .into_tokio_file())
+    }
+
+    /// Create a new [`TokioHttpFile`] from a path with an explicit etag, such as a version tag, instead of one computed from the data.
+    /// The mime type is still detected from the path or data.
+    ///
+    /// Returns an error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) if the etag is not valid according to [`validate_etag`](crate::validate_etag).
+    pub async fn new_with_etag(
+        path: impl Into<Cow<'static, str>>,
+        etag: impl Into<Cow<'static, str>>,
+    ) -> std::io::Result<Self> {
+        let etag = super::super::std::checked_etag(etag.into())?;
+        let path: Cow<'static, str> = path.into();
+        let (data, modified) = read_file(path.as_ref().as_ref()).await?;
+        let mime = crate::detect_mime_type(path.as_ref(), &data).unwrap_or(crate::DEFAULT_MIME);
+        Ok(StdHttpFile {
+            file: path,
+            data: ByteData::from_shared(data),
+            mime: Cow::Borrowed(mime),
+            etag,
+            encoding: None,
+            modified,
+            options: ReadOptions {
+                explicit_etag: true,
+                ..ReadOptions::DEFAULT
+            },
         }
         .into_tokio_file())
     }
@@ -77,13 +141,19 @@ impl TokioHttpFile {
         mime: impl Into<Cow<'static, str>>,
     ) -> std::io::Result<Self> {
         let path: Cow<'static, str> = path.into();
-        let data = read_file(path.as_ref().as_ref()).await?;
-        let etag = compute_etag_nonconst(&data);
+        let (data, modified, etag) =
+            read_file_hashed(path.as_ref().as_ref(), DEFAULT_MAX_FILE_SIZE).await?;
         Ok(StdHttpFile {
             file: path,
             data: ByteData::from_shared(data),
             mime: mime.into(),
             etag: Cow::Owned(etag),
+            encoding: None,
+            modified,
+            options: ReadOptions {
+                explicit_mime: true,
+                ..ReadOptions::DEFAULT
+            },
         }
         .into_tokio_file())
     }
@@ -105,6 +175,31 @@ impl HttpFile<'static> for TokioHttpFile {
         self.inner.etag.as_ref()
     }
 
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        self.inner.content_encoding()
+    }
+
+    #[inline]
+    fn available_encodings(&self) -> Vec<&str> {
+        self.inner.available_encodings()
+    }
+
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.inner.last_modified()
+    }
+
+    #[inline]
+    fn cache_control(&self) -> crate::CacheControl {
+        self.inner.cache_control()
+    }
+
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        self.inner.accepts_ranges()
+    }
+
     #[inline]
     fn data(&self) -> &[u8] {
         self.inner.data.as_slice()
@@ -146,6 +241,36 @@ impl HttpFileResponse<'static> for TokioHttpFile {
         self.inner.respond_borrowed(request)
     }
 
+    #[inline]
+    fn etag_header_value(&self) -> http::header::HeaderValue {
+        self.inner.etag_header_value()
+    }
+
+    #[inline]
+    fn vary_headers(&self) -> &[http::header::HeaderName] {
+        self.inner.vary_headers()
+    }
+
+    #[inline]
+    fn network_error_logging(&self) -> Option<&crate::NetworkErrorLogging> {
+        self.inner.network_error_logging()
+    }
+
+    #[inline]
+    fn retry_after(&self) -> Option<u64> {
+        self.inner.retry_after()
+    }
+
+    #[inline]
+    fn sniff_protection(&self) -> bool {
+        self.inner.sniff_protection()
+    }
+
+    #[inline]
+    fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+        self.inner.security_policy()
+    }
+
     #[inline]
     fn response_headers(&self, response: http::response::Builder) -> http::response::Builder {
         self.inner.response_headers(response)
@@ -170,33 +295,59 @@ impl HttpFileResponse<'static> for TokioHttpFile {
         &self,
         old_uri: &http::Uri,
         left_sep: Option<NonZeroU8>,
+        len: Option<NonZeroUsize>,
     ) -> Option<Result<http::Response<T>, http::Error>> {
-        self.inner.cachebust_suffix(old_uri, left_sep)
+        self.inner.cachebust_suffix(old_uri, left_sep, len)
     }
 }
 
-async fn read_file(path: &Path) -> std::io::Result<bytedata::SharedBytes> {
+/// Reads a file along with its modification time, if the platform supports it.
+async fn read_file(path: &Path) -> std::io::Result<(bytedata::SharedBytes, Option<SystemTime>)> {
     let mut builder = bytedata::SharedBytesBuilder::new();
-    read_file_into(path, &mut builder).await?;
-    Ok(builder.build())
+    let modified = read_file_into(path, &mut builder, DEFAULT_MAX_FILE_SIZE, None).await?;
+    Ok((builder.build(), modified))
+}
+
+/// Reads a file along with its modification time like `read_file`,
+/// and the etag of the data which is computed while reading instead of in a second pass over the data.
+async fn read_file_hashed(
+    path: &Path,
+    max_bytes: u64,
+) -> std::io::Result<(bytedata::SharedBytes, Option<SystemTime>, String)> {
+    let mut builder = bytedata::SharedBytesBuilder::new();
+    let mut hasher = EtagHasher::new();
+    let modified = read_file_into(path, &mut builder, max_bytes, Some(&mut hasher)).await?;
+    Ok((builder.build(), modified, hasher.finish()))
 }
 
 async fn read_file_into(
     path: &Path,
     builder: &mut bytedata::SharedBytesBuilder,
-) -> std::io::Result<()> {
+    max_bytes: u64,
+    mut hasher: Option<&mut EtagHasher>,
+) -> std::io::Result<Option<SystemTime>> {
     use ::tokio_1::{fs::File, io::AsyncReadExt};
     use bytes_1::BufMut;
-    let mut file = File::open(path).await?;
+    let file = File::open(path).await?;
+    let metadata = file.metadata().await?;
+    check_file_metadata(&metadata, max_bytes)?;
+    // the file may grow after the metadata was read, so one byte past the limit is read to detect it
+    let mut file = file.take(max_bytes.saturating_add(1));
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut total = 0u64;
     loop {
-        let buf = builder.chunk_mut();
-        let n = file
-            .read(unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr(), buf.len()) })
-            .await?;
+        let n = file.read(&mut buf).await?;
         if n == 0 {
             break;
         }
-        unsafe { builder.advance_mut(n) };
+        if let Some(hasher) = hasher.as_deref_mut() {
+            hasher.update(&buf[..n]);
+        }
+        builder.put_slice(&buf[..n]);
+        total += n as u64;
+        if total > max_bytes {
+            return Err(file_too_large(max_bytes));
+        }
     }
-    Ok(())
+    Ok(metadata.modified().ok())
 }
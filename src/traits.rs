@@ -1,8 +1,17 @@
-use core::num::NonZeroU8;
+use core::num::{NonZeroU8, NonZeroUsize};
 
-use alloc::string::String;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 use bytedata::{ByteData, StringData};
 
+use crate::{
+    CacheControl, NetworkErrorLogging, ResponseDecision, SecurityHeaders, ALLOWED_METHODS,
+};
+
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub enum CacheBusting {
     #[default]
@@ -12,8 +21,240 @@ pub enum CacheBusting {
     Query(StringData<'static>),
     /// Cachebust by using the etag in the filename.
     /// The first byte of the suffix is the separator between the basename and the etag.
+    /// With `.` as the separator, names such as `app.js` are busted as `app.q25fZAd-fY.js` and `jquery.min.js` as `jquery.min.q25fZAd-fY.js`.
     /// The request path is expected to always contain an etag.
-    Suffix(Option<NonZeroU8>),
+    /// The second field optionally limits the number of etag characters used in the path.
+    Suffix(Option<NonZeroU8>, Option<NonZeroUsize>),
+}
+
+/// Percent-decodes the last segment of a path.
+/// Returns `None` if the segment has nothing to decode or does not decode into valid UTF-8.
+fn urldecode_last_segment(path: &str) -> Option<String> {
+    let start = path.rfind('/').map_or(0, |i| i + 1);
+    let segment = &path[start..];
+    if !segment.contains('%') {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(path.len());
+    decoded.extend_from_slice(path[..start].as_bytes());
+    crate::urldecode_into(segment.as_bytes(), &mut decoded).ok()?;
+    String::from_utf8(decoded).ok()
+}
+
+/// Finds the value of the first parameter named `key` in a query string.
+fn query_value<'q>(query: &'q str, key: &str) -> Option<&'q str> {
+    query.split('&').find_map(|pair| {
+        let mut pair = pair.splitn(2, '=');
+        if pair.next() == Some(key) {
+            pair.next()
+        } else {
+            None
+        }
+    })
+}
+
+/// Limits the etag to the first `len` characters, if it is long enough.
+fn truncate_etag(etag_str: &str, len: Option<NonZeroUsize>) -> &str {
+    match len {
+        Some(len) => etag_str.get(..len.get()).unwrap_or(etag_str),
+        None => etag_str,
+    }
+}
+
+/// Checks if the path, or its basename without the file extension, ends with the separator and the etag.
+fn suffix_is_fresh(path: &str, etag_str: &str, left_sep: Option<NonZeroU8>) -> bool {
+    let ends_with_etag = |s: &str| {
+        s.len() > etag_str.len()
+            && s.ends_with(etag_str)
+            && left_sep.map_or(true, |left_sep| {
+                s.as_bytes()[s.len() - etag_str.len() - 1] == left_sep.get()
+            })
+    };
+    if ends_with_etag(path) {
+        return true;
+    }
+    match crate::file_ext(path) {
+        Some(ext) => ends_with_etag(&path[..path.len() - ext.len() - 1]),
+        None => false,
+    }
+}
+
+/// Returns the cache-busted location of a request using `CacheBusting::Query`, or `None` if the query already contains the etag.
+pub(crate) fn cachebust_query_location(
+    old_path: &str,
+    query: Option<&str>,
+    query_key: &str,
+    etag_str: &str,
+) -> Option<String> {
+    if let Some(query) = query {
+        let query_val = query_value(query, query_key);
+        if query_val == Some(etag_str) {
+            return None;
+        }
+        let mut new_path = String::with_capacity(
+            old_path.len() + 1 + query_key.len() + 1 + etag_str.len() + query.len(),
+        );
+        new_path.push_str(old_path);
+        new_path.push('?');
+        new_path.push_str(query_key);
+        new_path.push('=');
+        new_path.push_str(etag_str);
+        if query_val.is_some() {
+            for x in query.split('&') {
+                let key = x.split('=').next().unwrap_or(x);
+                if key != query_key {
+                    new_path.push('&');
+                    new_path.push_str(x);
+                }
+            }
+        } else if !query.is_empty() {
+            new_path.push('&');
+            new_path.push_str(query);
+        }
+        Some(new_path)
+    } else {
+        let mut new_path =
+            String::with_capacity(old_path.len() + 1 + query_key.len() + 1 + etag_str.len());
+        new_path.push_str(old_path);
+        new_path.push('?');
+        new_path.push_str(query_key);
+        new_path.push('=');
+        new_path.push_str(etag_str);
+        Some(new_path)
+    }
+}
+
+/// Finds the last separator in the last segment of a raw path, which may be percent-encoded by a proxy.
+/// Returns the position of the separator and its length in the raw path.
+fn rfind_separator(path: &str, left_sep: NonZeroU8) -> Option<(usize, usize)> {
+    let start = path.rfind('/').map_or(0, |i| i + 1);
+    let bytes = path.as_bytes();
+    (start..bytes.len()).rev().find_map(|i| {
+        if bytes[i] == left_sep.get() {
+            return Some((i, 1));
+        }
+        let hex = |b: u8| (b as char).to_digit(16);
+        match (bytes[i], bytes.get(i + 1), bytes.get(i + 2)) {
+            (b'%', Some(&hi), Some(&lo)) => match (hex(hi), hex(lo)) {
+                (Some(hi), Some(lo)) if hi * 16 + lo == left_sep.get() as u32 => Some((i, 3)),
+                _ => None,
+            },
+            _ => None,
+        }
+    })
+}
+
+/// Removes the separator and a stale etag following it from the last segment of a raw path, such as `app~old` into `app`.
+///
+/// A `.` also separates the parts of ordinary names, such as `jquery.min`, so with it only a part which looks like an etag
+/// of the same length is removed.
+fn strip_stale_suffix(name: &str, left_sep: NonZeroU8, etag_len: usize) -> &str {
+    let Some((p, sep_len)) = rfind_separator(name, left_sep) else {
+        return name;
+    };
+    let stale = &name[p + sep_len..];
+    if left_sep.get() == b'.'
+        && (stale.len() != etag_len
+            || !stale
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'))
+    {
+        return name;
+    }
+    &name[..p]
+}
+
+/// Returns the cache-busted location of a request using `CacheBusting::Suffix`, or `None` if the path already ends with the etag.
+pub(crate) fn cachebust_suffix_location(
+    path: &str,
+    etag_str: &str,
+    left_sep: Option<NonZeroU8>,
+    len: Option<NonZeroUsize>,
+) -> Option<String> {
+    // proxies may percent-encode the separator, so the last segment is compared in its decoded form
+    let decoded_path = urldecode_last_segment(path);
+    let etag_str = truncate_etag(etag_str, len);
+    if suffix_is_fresh(decoded_path.as_deref().unwrap_or(path), etag_str, left_sep) {
+        return None;
+    }
+    // the location is built from the raw path, as decoding it could change the target, such as `%2F` into a `/`
+    let old_path = path;
+    let ext = crate::file_ext(old_path);
+    let new_path = if let Some(ext) = ext {
+        let basename = &old_path[..old_path.len() - ext.len() - 1];
+        // create new path in the form of `basename` + `left_sep` + `etag` + `ext`
+        // the current basename may contain an etag, so we need to remove it
+        let mut new_path =
+            String::with_capacity(basename.len() + 1 + etag_str.len() + 1 + ext.len());
+        if let Some(left_sep) = left_sep {
+            new_path.push_str(strip_stale_suffix(basename, left_sep, etag_str.len()));
+            new_path.push(left_sep.get() as char);
+        } else {
+            new_path.push_str(basename);
+        }
+        new_path.push_str(etag_str);
+        new_path.push('.');
+        new_path.push_str(ext);
+        new_path
+    } else {
+        let mut new_path = String::with_capacity(old_path.len() + 1 + etag_str.len());
+        if let Some(left_sep) = left_sep {
+            new_path.push_str(strip_stale_suffix(old_path, left_sep, etag_str.len()));
+            new_path.push(left_sep.get() as char);
+        } else {
+            new_path.push_str(old_path);
+        }
+        new_path.push_str(etag_str);
+        new_path
+    };
+    Some(new_path)
+}
+
+/// Converts an etag into a header value, where an invalid etag is treated like a missing one instead of failing every response.
+pub(crate) fn etag_to_header_value(etag: &str) -> http::header::HeaderValue {
+    match http::header::HeaderValue::from_str(etag) {
+        Ok(value) if crate::validate_etag(etag) => value,
+        _ => http::header::HeaderValue::from_static(""),
+    }
+}
+
+/// Converts headers into lowercase name and value pairs in their order, where values which are not valid UTF-8 are converted lossily.
+pub(crate) fn header_map_pairs(
+    headers: &http::HeaderMap,
+) -> Vec<(Cow<'static, str>, Cow<'static, str>)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (Cow::Owned(name.as_str().to_owned()), Cow::Owned(value))
+        })
+        .collect()
+}
+
+/// Returns a `GET` request with the target and headers of a `HEAD` request.
+/// The `Range` header is removed, as ranges are only defined for `GET` and are ignored for `HEAD`.
+fn head_as_get(request: &http::Request<()>) -> http::Request<()> {
+    let mut get = http::Request::new(());
+    *get.uri_mut() = request.uri().clone();
+    *get.version_mut() = request.version();
+    *get.headers_mut() = request.headers().clone();
+    get.headers_mut().remove(http::header::RANGE);
+    get
+}
+
+/// Builds a redirect to the cache-busted location.
+/// The redirect itself must not be cached, as the location changes whenever the file does.
+fn cachebust_redirect<'a, T: From<ByteData<'a>>>(
+    location: String,
+) -> Result<http::Response<T>, http::Error> {
+    http::Response::builder()
+        .status(http::StatusCode::TEMPORARY_REDIRECT)
+        .header(http::header::LOCATION, location)
+        .header(
+            http::header::CACHE_CONTROL,
+            http::header::HeaderValue::from_static("no-cache"),
+        )
+        .body(ByteData::from_static(&[]).into())
 }
 
 pub trait HttpFile<'a> {
@@ -23,23 +264,68 @@ pub trait HttpFile<'a> {
     fn data(&self) -> &[u8];
     /// Returns the etag of the file (including quotes).
     fn etag(&self) -> &str;
-    /// Returns the etag without quotes.
+    /// Returns the etag without quotes, and without the `W/` prefix of a weak etag.
     fn etag_str(&self) -> &str {
         let e = self.etag();
+        let e = e.strip_prefix("W/").unwrap_or(e);
         if e.len() > 2 && e.starts_with('"') && e.ends_with('"') {
             &e[1..e.len() - 1]
         } else {
             e
         }
     }
+    /// Returns the etag without quotes as bytes, which is the same as [`HttpFile::etag_str`].
+    fn etag_bytes(&self) -> &[u8] {
+        self.etag_str().as_bytes()
+    }
+    /// Returns the content encoding of the data, such as `gzip`, if the data is already encoded.
+    fn content_encoding(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the content codings the file can be served with in order of preference, such as `gzip`,
+    /// where `identity` is the unencoded data. Files which encode their data on demand, such as a `GzipOnDemand`,
+    /// encode it to check if the encoding is smaller.
+    fn available_encodings(&self) -> Vec<&str> {
+        alloc::vec![self.content_encoding().unwrap_or("identity")]
+    }
+    /// Returns when the file was last modified as seconds since the Unix epoch, which is emitted as the `Last-Modified` header
+    /// and compared with the `If-Unmodified-Since` and `If-Modified-Since` headers.
+    fn last_modified(&self) -> Option<u64> {
+        None
+    }
     /// Returns the cache busting method.
     fn cache_busting(&self) -> &CacheBusting {
         &CacheBusting::None
     }
+    /// Returns the directives of the `Cache-Control` header.
+    /// By default cache busted files are immutable and other files are always revalidated.
+    fn cache_control(&self) -> CacheControl {
+        if matches!(self.cache_busting(), CacheBusting::None) {
+            CacheControl::REVALIDATE
+        } else {
+            CacheControl::IMMUTABLE
+        }
+    }
+    /// Checks if a shared cache may store the response, based on [`HttpFile::cache_control`].
+    fn is_publicly_cacheable(&self) -> bool {
+        self.cache_control().is_publicly_cacheable()
+    }
+    /// Checks if byte ranges of the data may be requested, which is advertised in the `Accept-Ranges` header.
+    /// Representations that are generated per response, such as compressed data, should return `false`.
+    fn accepts_ranges(&self) -> bool {
+        true
+    }
     /// Extracts the data of the file.
     fn into_data(self) -> ByteData<'a>;
     /// Clones the data of the file. This may only copy the reference.
     fn clone_data(&self) -> ByteData<'a>;
+    /// Clones a range of the data of the file without copying it, or `None` if the range is out of bounds.
+    fn data_range(&self, range: core::ops::Range<usize>) -> Option<ByteData<'a>> {
+        if range.start > range.end || range.end > self.data().len() {
+            return None;
+        }
+        Some(self.clone_data().sliced(range))
+    }
 }
 
 pub trait HttpFileResponse<'a>: HttpFile<'a> + Sized {
@@ -47,55 +333,70 @@ pub trait HttpFileResponse<'a>: HttpFile<'a> + Sized {
         &self,
         request: &http::Request<()>,
     ) -> Result<http::response::Builder, Result<http::Response<T>, http::Error>> {
-        let method = request.method();
-        if method != http::Method::HEAD
-            && method != http::Method::OPTIONS
-            && method != http::Method::GET
-        {
-            return Err(http::Response::builder()
+        let uri = request.uri();
+        let decision = ResponseDecision::new(
+            self,
+            request.method().as_str(),
+            uri.path(),
+            uri.query(),
+            |name| {
+                request
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+            },
+        );
+        let response = match decision {
+            ResponseDecision::MethodNotAllowed => http::Response::builder()
                 .status(http::StatusCode::METHOD_NOT_ALLOWED)
-                .header(http::header::ALLOW, "GET, HEAD, OPTIONS")
-                .body(ByteData::from_static(&[]).into()));
-        }
-        match self.cache_busting() {
-            CacheBusting::None => {}
-            CacheBusting::Query(query_key) => {
-                if let Some(res) = self.cachebust_uri(request.uri(), query_key.as_str()) {
-                    return Err(res);
-                }
+                .header(http::header::ALLOW, ALLOWED_METHODS),
+            // asterisk-form targets the server rather than this file, so no file headers are included
+            ResponseDecision::ServerOptions => http::Response::builder()
+                .status(http::StatusCode::NO_CONTENT)
+                .header(http::header::ALLOW, ALLOWED_METHODS),
+            ResponseDecision::Redirect(location) => return Err(cachebust_redirect(location)),
+            ResponseDecision::Options => self
+                .response_headers(http::Response::builder())
+                .status(http::StatusCode::NO_CONTENT)
+                .header(http::header::ALLOW, ALLOWED_METHODS),
+            ResponseDecision::PreconditionFailed => {
+                http::Response::builder().status(http::StatusCode::PRECONDITION_FAILED)
             }
-            CacheBusting::Suffix(left_sep) => {
-                if let Some(res) = self.cachebust_suffix(request.uri(), *left_sep) {
-                    return Err(res);
-                }
+            ResponseDecision::NotModified => self
+                .response_headers(http::Response::builder())
+                .status(http::StatusCode::NOT_MODIFIED),
+            ResponseDecision::PartialContent { first, last, total } => {
+                let response = self.response_headers(http::Response::builder());
+                return match self.data_range(first as usize..(last as usize + 1)) {
+                    Some(data) => Err(response
+                        .status(http::StatusCode::PARTIAL_CONTENT)
+                        .header(
+                            http::header::CONTENT_RANGE,
+                            format!("bytes {}-{}/{}", first, last, total),
+                        )
+                        .body(data.into())),
+                    // a file which cannot slice its data sends the full representation with a `200 OK` instead,
+                    // which a client must accept in response to a range request
+                    None => Ok(response.status(http::StatusCode::OK)),
+                };
             }
-        }
-        let mut response = self.response_headers(http::Response::builder());
-        if method == http::Method::OPTIONS {
-            response = response
-                .status(http::StatusCode::NO_CONTENT)
-                .header(http::header::ALLOW, "GET, HEAD, OPTIONS");
-            return Err(response.body(ByteData::from_static(&[]).into()));
-        }
-        if let Some(etag) = request
-            .headers()
-            .get(http::header::IF_NONE_MATCH)
-            .and_then(|value| value.to_str().ok())
-        {
-            for esplit in etag.split(',') {
-                let esplit = esplit.trim();
-                if esplit == "*" || esplit == self.etag() {
-                    return Err(response
-                        .status(http::StatusCode::NOT_MODIFIED)
-                        .body(ByteData::from_static(&[]).into()));
+            ResponseDecision::RangeNotSatisfiable { total } => {
+                let mut response = http::Response::builder()
+                    .status(http::StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(http::header::CONTENT_RANGE, format!("bytes */{}", total));
+                if let Some(seconds) = self.retry_after() {
+                    response = response.header(http::header::RETRY_AFTER, seconds);
                 }
+                response
             }
-        }
-        if method == http::Method::HEAD {
-            Err(response.body(ByteData::from_static(&[]).into()))
-        } else {
-            Ok(response)
-        }
+            ResponseDecision::Full { body: true } => {
+                return Ok(self.response_headers(http::Response::builder()))
+            }
+            ResponseDecision::Full { body: false } => {
+                self.response_headers(http::Response::builder())
+            }
+        };
+        Err(response.body(ByteData::from_static(&[]).into()))
     }
 
     fn respond<T: From<ByteData<'a>>>(
@@ -118,26 +419,212 @@ pub trait HttpFileResponse<'a>: HttpFile<'a> + Sized {
         }
     }
 
+    /// Responds to a request like [`HttpFileResponse::respond`], but with the `max-age` of the `Cache-Control` header
+    /// replaced by `max_age`, which is truncated to whole seconds.
+    ///
+    /// The `immutable` directive is dropped if `max_age` is shorter than a year, as the response is expected to be revalidated.
+    /// The redirects of cache busting are not cached and are not changed.
+    ///
+    /// Example:
+    /// ```
+    /// # use core::time::Duration;
+    /// # use static_http_file::{const_http_file, ConstHttpFile, HttpFileResponse};
+    /// const FILE: ConstHttpFile = const_http_file!(b"Hello, world!" as &[u8], "text/plain");
+    /// let request = http::Request::get("/hello.txt").body(()).unwrap();
+    /// let response = FILE.respond_with_cache::<bytedata::ByteData>(&request, Duration::from_secs(60)).unwrap();
+    /// assert_eq!(response.headers()["cache-control"], "public, max-age=60, must-revalidate");
+    /// ```
+    fn respond_with_cache<T: From<ByteData<'a>>>(
+        self,
+        request: &http::Request<()>,
+        max_age: core::time::Duration,
+    ) -> Result<http::Response<T>, http::Error> {
+        let mut cache_control = self.cache_control().max_age(max_age);
+        if max_age.as_secs() < 31536000 {
+            cache_control = cache_control.without_immutable();
+        }
+        let mut response = self.respond(request)?;
+        if response.status() != http::StatusCode::TEMPORARY_REDIRECT
+            && response.headers().contains_key(http::header::CACHE_CONTROL)
+        {
+            let value = http::header::HeaderValue::from_str(&cache_control.to_string()).unwrap();
+            response
+                .headers_mut()
+                .insert(http::header::CACHE_CONTROL, value);
+        }
+        Ok(response)
+    }
+
+    /// Evaluates the request like [`HttpFileResponse::respond_borrowed`] but always responds with an empty body,
+    /// as if the request was a `HEAD` request, regardless of the request method.
+    ///
+    /// A `200` or `206` response has a `Content-Length` header with the length of the data that would have been sent.
+    /// The response is derived from [`HttpFileResponse::respond_borrowed`], so files which select or render their data
+    /// per request, such as the files compressed on demand, describe the representation that would have been sent.
+    ///
+    /// Example:
+    /// ```
+    /// # use static_http_file::{const_http_file, ConstHttpFile, HttpFileResponse};
+    /// const FILE: ConstHttpFile = const_http_file!(b"Hello, world!" as &[u8], "text/plain");
+    /// let request = http::Request::get("/hello.txt").body(()).unwrap();
+    /// let response = FILE.head_response::<bytedata::ByteData>(&request).unwrap();
+    /// assert_eq!(response.status(), 200);
+    /// assert_eq!(response.headers()["content-length"], "13");
+    /// assert!(response.body().is_empty());
+    /// ```
+    fn head_response<T: From<ByteData<'a>>>(
+        &self,
+        request: &http::Request<()>,
+    ) -> Result<http::Response<T>, http::Error> {
+        // a `HEAD` response has no body to measure, so the length is taken from the equivalent `GET` response
+        let get;
+        let request = if *request.method() == http::Method::HEAD {
+            get = head_as_get(request);
+            &get
+        } else {
+            request
+        };
+        let (mut parts, body) = self.respond_borrowed::<ByteData<'a>>(request)?.into_parts();
+        if parts.status == http::StatusCode::OK || parts.status == http::StatusCode::PARTIAL_CONTENT
+        {
+            parts.headers.insert(
+                http::header::CONTENT_LENGTH,
+                http::header::HeaderValue::from(body.len()),
+            );
+        }
+        Ok(http::Response::from_parts(
+            parts,
+            T::from(ByteData::from_static(&[])),
+        ))
+    }
+
+    /// Returns the etag as a header value, as emitted in the `ETag` header.
+    ///
+    /// An etag which is not valid according to [`validate_etag`](crate::validate_etag) results in an empty value, in which case no `ETag` header is emitted.
+    /// The default implementation copies the etag on each call, so files which can keep the value should override it.
+    fn etag_header_value(&self) -> http::header::HeaderValue {
+        etag_to_header_value(self.etag())
+    }
+
+    /// Returns the request headers that may affect the response, which are advertised in the `Vary` header.
+    fn vary_headers(&self) -> &[http::header::HeaderName] {
+        &[]
+    }
+
+    /// Returns the Network Error Logging policy, which is emitted as the `Report-To` and `NEL` headers.
+    fn network_error_logging(&self) -> Option<&NetworkErrorLogging> {
+        None
+    }
+
+    /// Returns the security headers, such as the cross-origin policies, which are emitted on the response.
+    fn security_policy(&self) -> Option<&SecurityHeaders> {
+        None
+    }
+
+    /// Returns the number of seconds a client is asked to wait in the `Retry-After` header when a range is not satisfiable.
+    /// This is useful for files which are still being generated, where the range may become available later.
+    fn retry_after(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns whether `X-Content-Type-Options: nosniff` is emitted, which stops browsers from interpreting the data
+    /// as a different content type, such as running a user provided `.svg` as a script.
+    ///
+    /// Defaults to `true`. Override it with `fn sniff_protection(&self) -> bool { false }` if the header is already
+    /// set by a proxy in front of the server.
+    fn sniff_protection(&self) -> bool {
+        true
+    }
+
+    /// Adds additional security headers to the response, such as a `Content-Security-Policy` with a per-response nonce.
+    /// This is called last by [`HttpFileResponse::response_headers`] and does nothing by default.
+    fn security_headers(&self, response: http::response::Builder) -> http::response::Builder {
+        response
+    }
+
     fn response_headers(&self, mut response: http::response::Builder) -> http::response::Builder {
-        response = response
-            .header(
-                http::header::CONTENT_TYPE,
-                http::header::HeaderValue::from_str(self.content_type()).unwrap(),
-            )
-            .header(
-                http::header::ETAG,
-                http::header::HeaderValue::from_str(self.etag()).unwrap(),
+        response = response.header(
+            http::header::CONTENT_TYPE,
+            http::header::HeaderValue::from_str(self.content_type()).unwrap(),
+        );
+        let etag = self.etag_header_value();
+        if !etag.is_empty() {
+            response = response.header(http::header::ETAG, etag);
+        }
+        if let Some(last_modified) = self.last_modified() {
+            response = response.header(
+                http::header::LAST_MODIFIED,
+                http::header::HeaderValue::from_bytes(&crate::format_http_date(last_modified))
+                    .unwrap(),
             );
-        if !matches!(self.cache_busting(), CacheBusting::None) {
-            response.header(
-                http::header::CACHE_CONTROL,
-                http::header::HeaderValue::from_static("public, max-age=31536000, immutable"),
-            )
+        }
+        let encoding = self.content_encoding();
+        if let Some(encoding) = encoding {
+            response = response.header(
+                http::header::CONTENT_ENCODING,
+                http::header::HeaderValue::from_str(encoding).unwrap(),
+            );
+        }
+        let vary = self.vary_headers();
+        const ENCODING_VARY: &[http::header::HeaderName] = &[http::header::ACCEPT_ENCODING];
+        let encoding_vary = if encoding.is_some() {
+            ENCODING_VARY
         } else {
-            response.header(
-                http::header::CACHE_CONTROL,
-                http::header::HeaderValue::from_static("public, max-age=0, must-revalidate"),
-            )
+            &[]
+        };
+        if !vary.is_empty() || !encoding_vary.is_empty() {
+            let mut value = String::new();
+            let names = vary.iter().chain(encoding_vary);
+            for (i, name) in names.clone().enumerate() {
+                if names.clone().take(i).any(|n| n == name) {
+                    continue;
+                }
+                if !value.is_empty() {
+                    value.push_str(", ");
+                }
+                value.push_str(name.as_str());
+            }
+            response = response.header(http::header::VARY, value);
+        }
+        if let Some(nel) = self.network_error_logging() {
+            response = response
+                .header("report-to", nel.report_to().clone())
+                .header("nel", nel.nel().clone());
+        }
+        if let Some(security) = self.security_policy() {
+            response = security.apply(response);
+        }
+        if self.sniff_protection() {
+            response = response.header(
+                http::header::X_CONTENT_TYPE_OPTIONS,
+                http::header::HeaderValue::from_static("nosniff"),
+            );
+        }
+        if self.accepts_ranges() {
+            response = response.header(
+                http::header::ACCEPT_RANGES,
+                http::header::HeaderValue::from_static("bytes"),
+            );
+        }
+        response = response.header(
+            http::header::CACHE_CONTROL,
+            self.cache_control().to_string(),
+        );
+        self.security_headers(response)
+    }
+
+    /// Returns the headers of the response to a request as name and value pairs, for servers which are not based on the `http` crate.
+    /// The names are lowercase and values which are not valid UTF-8 are converted lossily.
+    ///
+    /// The headers are those of [`HttpFileResponse::respond_borrowed`], so files which render their data per response,
+    /// such as a file with a nonce per response, should be served with the body of that response instead.
+    fn response_header_pairs(
+        &self,
+        request: &http::Request<()>,
+    ) -> Vec<(Cow<'static, str>, Cow<'static, str>)> {
+        match self.respond_borrowed::<ByteData<'a>>(request) {
+            Ok(response) => header_map_pairs(response.headers()),
+            Err(_) => Vec::new(),
         }
     }
 
@@ -147,71 +634,32 @@ pub trait HttpFileResponse<'a>: HttpFile<'a> + Sized {
             .body(T::from(self.into_data()))
     }
 
+    /// Checks if the URI already contains the current cache busting token, meaning that no redirect is needed.
+    /// This is always `true` when the cache busting method is `CacheBusting::None`.
+    fn cachebust_is_fresh(&self, uri: &http::Uri) -> bool {
+        match self.cache_busting() {
+            CacheBusting::None => true,
+            CacheBusting::Query(query_key) => {
+                uri.query()
+                    .and_then(|query| query_value(query, query_key.as_str()))
+                    == Some(self.etag_str())
+            }
+            CacheBusting::Suffix(left_sep, len) => {
+                let decoded_path = urldecode_last_segment(uri.path());
+                let path = decoded_path.as_deref().unwrap_or(uri.path());
+                suffix_is_fresh(path, truncate_etag(self.etag_str(), *len), *left_sep)
+            }
+        }
+    }
+
     /// Detects if the request needs to be redirected to a cache-busted URI. Used when the cache busting method is `CacheBusting::Query`.
     fn cachebust_uri<T: From<ByteData<'a>>>(
         &self,
         old_uri: &http::Uri,
         query_key: &str,
     ) -> Option<Result<http::Response<T>, http::Error>> {
-        if let Some(query) = old_uri.query() {
-            let query_val = query.split('&').find_map(|pair| {
-                let mut pair = pair.splitn(2, '=');
-                if pair.next() == Some(query_key) {
-                    pair.next()
-                } else {
-                    None
-                }
-            });
-            let etag_str = self.etag_str();
-            if query_val != Some(etag_str) {
-                let old_path = old_uri.path();
-                let mut new_path = String::with_capacity(
-                    old_path.len() + 1 + query_key.len() + 1 + etag_str.len() + query.len(),
-                );
-                new_path.push_str(old_path);
-                new_path.push('?');
-                new_path.push_str(query_key);
-                new_path.push('=');
-                new_path.push_str(etag_str);
-                if query_val.is_some() {
-                    for x in query.split('&') {
-                        if !x.starts_with(query_key)
-                            || (x.len() > query_key.len() && !x[query_key.len()..].starts_with('='))
-                        {
-                            new_path.push('&');
-                            new_path.push_str(x);
-                        }
-                    }
-                } else if !query.is_empty() {
-                    new_path.push('&');
-                    new_path.push_str(query);
-                }
-                Some(
-                    http::Response::builder()
-                        .status(http::StatusCode::TEMPORARY_REDIRECT)
-                        .header(http::header::LOCATION, new_path)
-                        .body(ByteData::from_static(&[]).into()),
-                )
-            } else {
-                None
-            }
-        } else {
-            let old_path = old_uri.path();
-            let etag_str = self.etag_str();
-            let mut new_path =
-                String::with_capacity(old_path.len() + 1 + query_key.len() + 1 + etag_str.len());
-            new_path.push_str(old_path);
-            new_path.push('?');
-            new_path.push_str(query_key);
-            new_path.push('=');
-            new_path.push_str(etag_str);
-            Some(
-                http::Response::builder()
-                    .status(http::StatusCode::TEMPORARY_REDIRECT)
-                    .header(http::header::LOCATION, new_path)
-                    .body(ByteData::from_static(&[]).into()),
-            )
-        }
+        cachebust_query_location(old_uri.path(), old_uri.query(), query_key, self.etag_str())
+            .map(cachebust_redirect)
     }
 
     /// Detects if the request needs to be redirected to a cache-busted URI. Used when the cache busting method is `CacheBusting::Suffix`.
@@ -219,68 +667,9 @@ pub trait HttpFileResponse<'a>: HttpFile<'a> + Sized {
         &self,
         old_uri: &http::Uri,
         left_sep: Option<NonZeroU8>,
+        len: Option<NonZeroUsize>,
     ) -> Option<Result<http::Response<T>, http::Error>> {
-        let old_path = old_uri.path();
-        let etag_str = self.etag_str();
-        if old_path.ends_with(etag_str) && old_path.len() > etag_str.len() {
-            if let Some(left_sep) = left_sep {
-                if old_path.as_bytes()[old_path.len() - etag_str.len() - 1] == left_sep.get() {
-                    return None;
-                }
-            } else {
-                return None;
-            }
-        }
-        let ext = super::file_ext(old_path);
-        let new_path = if let Some(ext) = ext {
-            let basename = &old_path[..old_path.len() - ext.len() - 1];
-            if basename.ends_with(etag_str) && basename.len() > etag_str.len() {
-                if let Some(left_sep) = left_sep {
-                    if basename.as_bytes()[basename.len() - etag_str.len() - 1] == left_sep.get() {
-                        return None;
-                    }
-                } else {
-                    return None;
-                }
-            }
-            // create new path in the form of `basename` + `left_sep` + `etag` + `ext`
-            // the current basename may contain an etag, so we need to remove it
-            let mut new_path =
-                String::with_capacity(basename.len() + 1 + etag_str.len() + 1 + ext.len());
-            new_path.push_str(basename);
-            if let Some(left_sep) = left_sep {
-                // remove left_sep and trailing from the basename appended into new_path
-                if let Some(p) = basename.rfind(left_sep.get() as char) {
-                    if basename.rfind('/').unwrap_or(0) < p {
-                        new_path.truncate(p);
-                    }
-                }
-                new_path.push(left_sep.get() as char);
-            }
-            new_path.push_str(etag_str);
-            new_path.push('.');
-            new_path.push_str(ext);
-            new_path
-        } else {
-            let mut new_path = String::with_capacity(old_path.len() + 1 + etag_str.len());
-            new_path.push_str(old_path);
-            if let Some(left_sep) = left_sep {
-                // remove left_sep and trailing from the basename appended into new_path
-                if let Some(p) = old_path.rfind(left_sep.get() as char) {
-                    if old_path.rfind('/').unwrap_or(0) < p {
-                        new_path.truncate(p);
-                    }
-                }
-                new_path.push(left_sep.get() as char);
-            }
-            new_path.push_str(etag_str);
-            new_path
-        };
-        Some(
-            http::Response::builder()
-                .status(http::StatusCode::TEMPORARY_REDIRECT)
-                .header(http::header::LOCATION, new_path)
-                .body(ByteData::from_static(&[]).into()),
-        )
+        cachebust_suffix_location(old_uri.path(), self.etag_str(), left_sep, len)
+            .map(cachebust_redirect)
     }
 }
@@ -0,0 +1,258 @@
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
+/// Decodes a percent-encoded string by calling `f` with each chunk of decoded bytes.
+/// Chunks without escape sequences are passed through as borrowed slices of the input.
+///
+/// Fails with the byte offset of the first invalid escape sequence, which is a `%` not followed by two hex digits.
+/// This includes escape sequences truncated by the end of the input, such as in `abc%` or `abc%A`.
+/// On failure `f` has only been called with the bytes before the invalid escape sequence, and nothing after it is passed on.
+pub fn urldecode_iter_fn(input: &[u8], f: impl FnMut(&[u8])) -> Result<(), usize> {
+    decode_iter_fn(input, false, f)
+}
+
+/// Decodes a percent-encoded form value, as used in query strings, where a `+` is decoded as a space.
+/// Otherwise this works like [`urldecode_iter_fn`].
+pub fn urldecode_form_iter_fn(input: &[u8], f: impl FnMut(&[u8])) -> Result<(), usize> {
+    decode_iter_fn(input, true, f)
+}
+
+fn decode_iter_fn(input: &[u8], form: bool, mut f: impl FnMut(&[u8])) -> Result<(), usize> {
+    let mut start = 0;
+    let mut i = 0;
+    while i < input.len() {
+        if form && input[i] == b'+' {
+            if start != i {
+                f(&input[start..i]);
+            }
+            f(b" ");
+            i += 1;
+            start = i;
+            continue;
+        }
+        if input[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        if start != i {
+            f(&input[start..i]);
+        }
+        let (Some(hi), Some(lo)) = (
+            input.get(i + 1).and_then(|b| hex_value(*b)),
+            input.get(i + 2).and_then(|b| hex_value(*b)),
+        ) else {
+            return Err(i);
+        };
+        f(&[(hi << 4) | lo]);
+        i += 3;
+        start = i;
+    }
+    if start != input.len() {
+        f(&input[start..]);
+    }
+    Ok(())
+}
+
+/// Decodes a percent-encoded string and appends the result to `out`.
+///
+/// Fails with the byte offset of the first invalid escape sequence, in which case `out` contains the bytes decoded before it.
+pub fn urldecode_into(input: &[u8], out: &mut Vec<u8>) -> Result<(), usize> {
+    urldecode_iter_fn(input, |chunk| out.extend_from_slice(chunk))
+}
+
+/// Decodes a percent-encoded string. The input is borrowed if it contains no escape sequences.
+///
+/// Example:
+/// ```
+/// # use static_http_file::urldecode;
+/// assert_eq!(urldecode("app%7Eq25fZAd-fY.js").unwrap().as_ref(), b"app~q25fZAd-fY.js");
+/// assert_eq!(urldecode("100%"), Err(3));
+/// ```
+pub fn urldecode(input: &str) -> Result<Cow<'_, [u8]>, usize> {
+    if !input.contains('%') {
+        return Ok(Cow::Borrowed(input.as_bytes()));
+    }
+    let mut out = Vec::with_capacity(input.len());
+    urldecode_into(input.as_bytes(), &mut out)?;
+    Ok(Cow::Owned(out))
+}
+
+/// Decodes a percent-encoded form value, where a `+` is decoded as a space. The input is borrowed if it contains nothing to decode.
+///
+/// Example:
+/// ```
+/// # use static_http_file::urldecode_form;
+/// assert_eq!(urldecode_form("hello+world%2B").unwrap().as_ref(), b"hello world+");
+/// ```
+pub fn urldecode_form(input: &str) -> Result<Cow<'_, [u8]>, usize> {
+    if !input.contains(['%', '+']) {
+        return Ok(Cow::Borrowed(input.as_bytes()));
+    }
+    let mut out = Vec::with_capacity(input.len());
+    urldecode_form_iter_fn(input.as_bytes(), |chunk| out.extend_from_slice(chunk))?;
+    Ok(Cow::Owned(out))
+}
+
+/// An iterator over the key and value pairs of a query string, which are not percent-decoded.
+/// Empty pairs are skipped and a key without `=` has an empty value.
+///
+/// Example:
+/// ```
+/// # use static_http_file::QueryStringIterator;
+/// let pairs: Vec<_> = QueryStringIterator::new_with_separators("a=1;b&c=%20", b"&;").collect();
+/// assert_eq!(pairs, [("a", "1"), ("b", ""), ("c", "%20")]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct QueryStringIterator<'a> {
+    input: &'a str,
+    rest: &'a str,
+    separators: &'a [u8],
+}
+
+impl<'a> QueryStringIterator<'a> {
+    /// Create an iterator where pairs are separated by `&`.
+    pub const fn new(input: &'a str) -> Self {
+        Self::new_with_separators(input, b"&")
+    }
+
+    /// Create an iterator where pairs are separated by any of the ASCII bytes in `separators`, such as `b"&;"`.
+    /// Bytes which are not ASCII are ignored.
+    pub const fn new_with_separators(input: &'a str, separators: &'a [u8]) -> Self {
+        QueryStringIterator {
+            input,
+            rest: input,
+            separators,
+        }
+    }
+
+    /// The query string being iterated.
+    pub const fn input(&self) -> &'a str {
+        self.input
+    }
+
+    /// The byte offset of a key or value returned by the iterator within the query string.
+    fn offset_of(&self, part: &str) -> usize {
+        part.as_ptr() as usize - self.input.as_ptr() as usize
+    }
+}
+
+impl<'a> From<&'a str> for QueryStringIterator<'a> {
+    fn from(input: &'a str) -> Self {
+        QueryStringIterator::new(input)
+    }
+}
+
+impl<'a> Iterator for QueryStringIterator<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let end = self
+                .rest
+                .bytes()
+                .position(|b| b.is_ascii() && self.separators.contains(&b))
+                .unwrap_or(self.rest.len());
+            let pair = &self.rest[..end];
+            self.rest = &self.rest[(end + 1).min(self.rest.len())..];
+            if pair.is_empty() {
+                continue;
+            }
+            return Some(match pair.find('=') {
+                Some(eq) => (&pair[..eq], &pair[eq + 1..]),
+                None => (pair, &pair[pair.len()..]),
+            });
+        }
+    }
+}
+
+/// Parses a query string, such as `a=1&b=hello+world`, by calling `f` with each decoded key and value,
+/// where a `+` is decoded as a space as in HTML forms.
+/// A `&str` is split at `&`, while a [`QueryStringIterator`] can be used for other separators.
+/// The decoded bytes are not required to be valid UTF-8; use [`parse_query_string_utf8`] to validate them.
+///
+/// Fails with the byte offset of the first invalid escape sequence, in which case `f` has been called for the preceding pairs.
+pub fn parse_query_string_iter_fn<'a>(
+    query: impl Into<QueryStringIterator<'a>>,
+    f: impl FnMut(Cow<'a, [u8]>, Cow<'a, [u8]>),
+) -> Result<(), usize> {
+    parse_query_string_with(query.into(), urldecode_form, f)
+}
+
+/// Parses a query string like [`parse_query_string_iter_fn`], but only percent-decodes the keys and values,
+/// so a `+` is kept as is.
+///
+/// Example:
+/// ```
+/// # use static_http_file::parse_query_string_raw;
+/// let mut values = Vec::new();
+/// parse_query_string_raw("tz=UTC+1&q=a%2Bb", |_, v| values.push(v.into_owned())).unwrap();
+/// assert_eq!(values, [b"UTC+1".to_vec(), b"a+b".to_vec()]);
+/// ```
+pub fn parse_query_string_raw<'a>(
+    query: impl Into<QueryStringIterator<'a>>,
+    f: impl FnMut(Cow<'a, [u8]>, Cow<'a, [u8]>),
+) -> Result<(), usize> {
+    parse_query_string_with(query.into(), urldecode, f)
+}
+
+fn parse_query_string_with<'a>(
+    pairs: QueryStringIterator<'a>,
+    decode: fn(&'a str) -> Result<Cow<'a, [u8]>, usize>,
+    mut f: impl FnMut(Cow<'a, [u8]>, Cow<'a, [u8]>),
+) -> Result<(), usize> {
+    for (key, value) in pairs.clone() {
+        let decoded_key = decode(key).map_err(|i| pairs.offset_of(key) + i)?;
+        let decoded_value = decode(value).map_err(|i| pairs.offset_of(value) + i)?;
+        f(decoded_key, decoded_value);
+    }
+    Ok(())
+}
+
+/// Parses a query string like [`parse_query_string_iter_fn`], but requires each decoded key and value to be valid UTF-8.
+/// A `+` is decoded as a space.
+///
+/// Fails with the byte offset of the first invalid escape sequence, or of the first key or value which does not decode into valid UTF-8.
+///
+/// Example:
+/// ```
+/// # use static_http_file::parse_query_string_utf8;
+/// let mut pairs = Vec::new();
+/// parse_query_string_utf8("v=q25fZAd-fY&name=a%20b", |k, v| pairs.push((k, v))).unwrap();
+/// assert_eq!(pairs.len(), 2);
+/// assert_eq!((pairs[0].0.as_ref(), pairs[0].1.as_ref()), ("v", "q25fZAd-fY"));
+/// assert_eq!((pairs[1].0.as_ref(), pairs[1].1.as_ref()), ("name", "a b"));
+/// assert_eq!(parse_query_string_utf8("v=1&name=%FF%FE", |_, _| {}), Err(9));
+/// ```
+pub fn parse_query_string_utf8<'a>(
+    query: impl Into<QueryStringIterator<'a>>,
+    mut f: impl FnMut(Cow<'a, str>, Cow<'a, str>),
+) -> Result<(), usize> {
+    let pairs = query.into();
+    for (key, value) in pairs.clone() {
+        let decoded_key = urldecode_utf8(key, pairs.offset_of(key))?;
+        let decoded_value = urldecode_utf8(value, pairs.offset_of(value))?;
+        f(decoded_key, decoded_value);
+    }
+    Ok(())
+}
+
+/// Decodes a part of a query string starting at `offset`, failing with the offset of the error.
+fn urldecode_utf8(part: &str, offset: usize) -> Result<Cow<'_, str>, usize> {
+    match urldecode_form(part).map_err(|i| offset + i)? {
+        Cow::Borrowed(bytes) => core::str::from_utf8(bytes)
+            .map(Cow::Borrowed)
+            .map_err(|_| offset),
+        Cow::Owned(bytes) => String::from_utf8(bytes).map(Cow::Owned).map_err(|_| offset),
+    }
+}
+
+const fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
@@ -0,0 +1,196 @@
+use core::num::{NonZeroU8, NonZeroUsize};
+
+use alloc::vec::Vec;
+use bytedata::ByteData;
+use http::header::{HeaderValue, InvalidHeaderValue};
+
+use crate::{CacheBusting, CacheControl, HttpFile, HttpFileResponse};
+
+/// A wrapper which adds `X-Content-Type-Options: nosniff`, `X-Frame-Options` and an optional `Content-Security-Policy`
+/// to the responses of the inner file.
+///
+/// The wrapper is cheap to construct, so a policy with a per-response nonce can be set by wrapping the file for each response.
+///
+/// Example:
+/// ```
+/// # use static_http_file::{const_http_file, ConstHttpFile, HttpFileResponse, WithSecurityHeaders};
+/// const FILE: ConstHttpFile = const_http_file!(b"<!DOCTYPE html>" as &[u8], "text/html; charset=utf-8");
+/// let file = WithSecurityHeaders::new(FILE)
+///     .with_content_security_policy("script-src 'nonce-abc123'")
+///     .unwrap();
+/// let response = file.response_headers(http::Response::builder()).body(()).unwrap();
+/// assert_eq!(response.headers()["x-content-type-options"], "nosniff");
+/// assert_eq!(response.headers()["x-frame-options"], "DENY");
+/// assert_eq!(response.headers()["content-security-policy"], "script-src 'nonce-abc123'");
+/// ```
+pub struct WithSecurityHeaders<T> {
+    inner: T,
+    frame_options: Option<HeaderValue>,
+    content_security_policy: Option<HeaderValue>,
+}
+
+impl<T> WithSecurityHeaders<T> {
+    /// Wrap a file, adding `X-Content-Type-Options: nosniff` and `X-Frame-Options: DENY` to its responses.
+    pub const fn new(inner: T) -> Self {
+        WithSecurityHeaders {
+            inner,
+            frame_options: Some(HeaderValue::from_static("DENY")),
+            content_security_policy: None,
+        }
+    }
+
+    /// Wrap a file with an explicit `X-Frame-Options` value and `Content-Security-Policy`.
+    /// Passing `None` omits the respective header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value is not a valid header value, which fails the build when used in a constant.
+    pub const fn new_const(
+        inner: T,
+        frame_options: Option<&'static str>,
+        content_security_policy: Option<&'static str>,
+    ) -> Self {
+        WithSecurityHeaders {
+            inner,
+            frame_options: match frame_options {
+                Some(value) => Some(HeaderValue::from_static(value)),
+                None => None,
+            },
+            content_security_policy: match content_security_policy {
+                Some(value) => Some(HeaderValue::from_static(value)),
+                None => None,
+            },
+        }
+    }
+
+    /// Set the value of the `X-Frame-Options` header, such as `SAMEORIGIN`, or `None` to omit it.
+    ///
+    /// Returns an error if the value is not a valid header value.
+    pub fn with_frame_options(mut self, value: Option<&str>) -> Result<Self, InvalidHeaderValue> {
+        self.frame_options = value.map(HeaderValue::from_str).transpose()?;
+        Ok(self)
+    }
+
+    /// Set the value of the `Content-Security-Policy` header.
+    ///
+    /// Returns an error if the policy is not a valid header value, such that no response fails because of it.
+    pub fn with_content_security_policy(mut self, value: &str) -> Result<Self, InvalidHeaderValue> {
+        self.content_security_policy = Some(HeaderValue::from_str(value)?);
+        Ok(self)
+    }
+
+    /// The value of the `X-Frame-Options` header.
+    pub const fn frame_options(&self) -> Option<&HeaderValue> {
+        self.frame_options.as_ref()
+    }
+
+    /// The value of the `Content-Security-Policy` header.
+    pub const fn content_security_policy(&self) -> Option<&HeaderValue> {
+        self.content_security_policy.as_ref()
+    }
+
+    /// Returns a reference to the wrapped file.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwraps the file.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<'l, T: HttpFile<'l>> HttpFile<'l> for WithSecurityHeaders<T> {
+    #[inline]
+    fn content_type(&self) -> &str {
+        self.inner.content_type()
+    }
+
+    #[inline]
+    fn etag(&self) -> &str {
+        self.inner.etag()
+    }
+
+    #[inline]
+    fn etag_str(&self) -> &str {
+        self.inner.etag_str()
+    }
+
+    #[inline]
+    fn content_encoding(&self) -> Option<&str> {
+        self.inner.content_encoding()
+    }
+
+    #[inline]
+    fn available_encodings(&self) -> Vec<&str> {
+        self.inner.available_encodings()
+    }
+
+    #[inline]
+    fn cache_busting(&self) -> &CacheBusting {
+        self.inner.cache_busting()
+    }
+
+    #[inline]
+    fn last_modified(&self) -> Option<u64> {
+        self.inner.last_modified()
+    }
+
+    #[inline]
+    fn cache_control(&self) -> CacheControl {
+        self.inner.cache_control()
+    }
+
+    #[inline]
+    fn accepts_ranges(&self) -> bool {
+        self.inner.accepts_ranges()
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.inner.data()
+    }
+
+    #[inline]
+    fn into_data(self) -> ByteData<'l> {
+        self.inner.into_data()
+    }
+
+    #[inline]
+    fn clone_data(&self) -> ByteData<'l> {
+        self.inner.clone_data()
+    }
+
+    #[inline]
+    fn data_range(&self, range: core::ops::Range<usize>) -> Option<ByteData<'l>> {
+        self.inner.data_range(range)
+    }
+}
+
+// the response logic is not delegated to the inner file, as it would bypass the headers of the wrapper
+impl<'l, T: HttpFileResponse<'l>> HttpFileResponse<'l> for WithSecurityHeaders<T> {
+    crate::cachebusted_http_file::wrap_resp!(@headers);
+    crate::cachebusted_http_file::wrap_resp!(@cachebust 'l);
+
+    #[inline]
+    fn security_policy(&self) -> Option<&crate::SecurityHeaders> {
+        self.inner.security_policy()
+    }
+
+    // the wrapper guarantees the header even if the inner file opted out of it
+    #[inline]
+    fn sniff_protection(&self) -> bool {
+        true
+    }
+
+    fn security_headers(&self, response: http::response::Builder) -> http::response::Builder {
+        let mut response = self.inner.security_headers(response);
+        if let Some(value) = &self.frame_options {
+            response = response.header("x-frame-options", value.clone());
+        }
+        if let Some(value) = &self.content_security_policy {
+            response = response.header("content-security-policy", value.clone());
+        }
+        response
+    }
+}